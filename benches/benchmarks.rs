@@ -1,6 +1,7 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use unicase::UniCase;
 use unicase_collections::unicase_btree_map::UniCaseBTreeMap;
+use unicase_collections::unicase_hash_map::UniCaseHashMap;
 
 fn unicase_btree_map_benchmark(c: &mut Criterion) {
     let mut map = UniCaseBTreeMap::new();
@@ -41,5 +42,41 @@ fn unicase_btree_map_benchmark(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, unicase_btree_map_benchmark);
+fn unicase_hash_map_benchmark(c: &mut Criterion) {
+    let mut map = UniCaseHashMap::new();
+    for i in 0..1000 {
+        map.insert(format!("X-Header-{}", i), i);
+        map.insert(format!("Straße-{}", i), i);
+    }
+
+    c.bench_function("UniCaseHashMap get str", |b| {
+        b.iter(|| {
+            for _i in 0..1000 {
+                let _ = map.get(black_box("x-HEADER-500"));
+            }
+        })
+    });
+
+    c.bench_function("UniCaseHashMap get non-ASCII str", |b| {
+        b.iter(|| {
+            for _i in 0..1000 {
+                let _ = map.get(black_box("STRASSE-500"));
+            }
+        })
+    });
+
+    c.bench_function("UniCaseHashMap get missing str", |b| {
+        b.iter(|| {
+            for _i in 0..1000 {
+                let _ = map.get(black_box("Content-Type"));
+            }
+        })
+    });
+}
+
+criterion_group!(
+    benches,
+    unicase_btree_map_benchmark,
+    unicase_hash_map_benchmark
+);
 criterion_main!(benches);
@@ -0,0 +1,61 @@
+#!/usr/bin/env python3
+"""Generates src/fold/case_folding.rs from data/CaseFolding.txt.
+
+Usage: python3 scripts/case_folding.py > src/fold/case_folding.rs
+"""
+
+import os
+import re
+
+ROOT = os.path.join(os.path.dirname(os.path.abspath(__file__)), "..")
+
+
+def parse(path):
+    version = None
+    mappings = {"C": [], "S": [], "F": [], "T": []}
+    with open(path, encoding="utf-8") as f:
+        for line in f:
+            if version is None:
+                m = re.match(r"# CaseFolding-(\d+\.\d+\.\d+)\.txt", line)
+                if m:
+                    version = m.group(1)
+            line = line.split("#", 1)[0].strip()
+            if not line:
+                continue
+            code, status, mapping = [field.strip() for field in line.split(";")[:3]]
+            mappings[status].append((int(code, 16), [int(c, 16) for c in mapping.split()]))
+    for entries in mappings.values():
+        entries.sort()
+    return version, mappings
+
+
+def rust_char(c):
+    return "'\\u{%04x}'" % c
+
+
+def main():
+    version, mappings = parse(os.path.join(ROOT, "data", "CaseFolding.txt"))
+    simple = sorted(mappings["C"] + mappings["S"])
+
+    print("// Generated by scripts/case_folding.py from data/CaseFolding.txt.")
+    print("// Do not edit by hand.")
+    print()
+    print("/// The Unicode version of the case folding data.")
+    print("pub const UNICODE_VERSION: (u8, u8, u8) = (%s);" % ", ".join(version.split(".")))
+    print()
+    print("/// Mappings with status C and S, sorted by code point.")
+    print("pub(crate) static SIMPLE: &[(char, char)] = &[")
+    for code, mapping in simple:
+        print("    (%s, %s)," % (rust_char(code), rust_char(mapping[0])))
+    print("];")
+    print()
+    print("/// Mappings with status F, sorted by code point. Unused slots are `'\\0'`.")
+    print("pub(crate) static FULL: &[(char, [char; 3])] = &[")
+    for code, mapping in mappings["F"]:
+        padded = mapping + [0] * (3 - len(mapping))
+        print("    (%s, [%s])," % (rust_char(code), ", ".join(rust_char(c) for c in padded)))
+    print("];")
+
+
+if __name__ == "__main__":
+    main()
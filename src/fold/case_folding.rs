@@ -0,0 +1,1601 @@
+// Generated by scripts/case_folding.py from data/CaseFolding.txt.
+// Do not edit by hand.
+
+/// The Unicode version of the case folding data.
+pub const UNICODE_VERSION: (u8, u8, u8) = (16, 0, 0);
+
+/// Mappings with status C and S, sorted by code point.
+pub(crate) static SIMPLE: &[(char, char)] = &[
+    ('\u{0041}', '\u{0061}'),
+    ('\u{0042}', '\u{0062}'),
+    ('\u{0043}', '\u{0063}'),
+    ('\u{0044}', '\u{0064}'),
+    ('\u{0045}', '\u{0065}'),
+    ('\u{0046}', '\u{0066}'),
+    ('\u{0047}', '\u{0067}'),
+    ('\u{0048}', '\u{0068}'),
+    ('\u{0049}', '\u{0069}'),
+    ('\u{004a}', '\u{006a}'),
+    ('\u{004b}', '\u{006b}'),
+    ('\u{004c}', '\u{006c}'),
+    ('\u{004d}', '\u{006d}'),
+    ('\u{004e}', '\u{006e}'),
+    ('\u{004f}', '\u{006f}'),
+    ('\u{0050}', '\u{0070}'),
+    ('\u{0051}', '\u{0071}'),
+    ('\u{0052}', '\u{0072}'),
+    ('\u{0053}', '\u{0073}'),
+    ('\u{0054}', '\u{0074}'),
+    ('\u{0055}', '\u{0075}'),
+    ('\u{0056}', '\u{0076}'),
+    ('\u{0057}', '\u{0077}'),
+    ('\u{0058}', '\u{0078}'),
+    ('\u{0059}', '\u{0079}'),
+    ('\u{005a}', '\u{007a}'),
+    ('\u{00b5}', '\u{03bc}'),
+    ('\u{00c0}', '\u{00e0}'),
+    ('\u{00c1}', '\u{00e1}'),
+    ('\u{00c2}', '\u{00e2}'),
+    ('\u{00c3}', '\u{00e3}'),
+    ('\u{00c4}', '\u{00e4}'),
+    ('\u{00c5}', '\u{00e5}'),
+    ('\u{00c6}', '\u{00e6}'),
+    ('\u{00c7}', '\u{00e7}'),
+    ('\u{00c8}', '\u{00e8}'),
+    ('\u{00c9}', '\u{00e9}'),
+    ('\u{00ca}', '\u{00ea}'),
+    ('\u{00cb}', '\u{00eb}'),
+    ('\u{00cc}', '\u{00ec}'),
+    ('\u{00cd}', '\u{00ed}'),
+    ('\u{00ce}', '\u{00ee}'),
+    ('\u{00cf}', '\u{00ef}'),
+    ('\u{00d0}', '\u{00f0}'),
+    ('\u{00d1}', '\u{00f1}'),
+    ('\u{00d2}', '\u{00f2}'),
+    ('\u{00d3}', '\u{00f3}'),
+    ('\u{00d4}', '\u{00f4}'),
+    ('\u{00d5}', '\u{00f5}'),
+    ('\u{00d6}', '\u{00f6}'),
+    ('\u{00d8}', '\u{00f8}'),
+    ('\u{00d9}', '\u{00f9}'),
+    ('\u{00da}', '\u{00fa}'),
+    ('\u{00db}', '\u{00fb}'),
+    ('\u{00dc}', '\u{00fc}'),
+    ('\u{00dd}', '\u{00fd}'),
+    ('\u{00de}', '\u{00fe}'),
+    ('\u{0100}', '\u{0101}'),
+    ('\u{0102}', '\u{0103}'),
+    ('\u{0104}', '\u{0105}'),
+    ('\u{0106}', '\u{0107}'),
+    ('\u{0108}', '\u{0109}'),
+    ('\u{010a}', '\u{010b}'),
+    ('\u{010c}', '\u{010d}'),
+    ('\u{010e}', '\u{010f}'),
+    ('\u{0110}', '\u{0111}'),
+    ('\u{0112}', '\u{0113}'),
+    ('\u{0114}', '\u{0115}'),
+    ('\u{0116}', '\u{0117}'),
+    ('\u{0118}', '\u{0119}'),
+    ('\u{011a}', '\u{011b}'),
+    ('\u{011c}', '\u{011d}'),
+    ('\u{011e}', '\u{011f}'),
+    ('\u{0120}', '\u{0121}'),
+    ('\u{0122}', '\u{0123}'),
+    ('\u{0124}', '\u{0125}'),
+    ('\u{0126}', '\u{0127}'),
+    ('\u{0128}', '\u{0129}'),
+    ('\u{012a}', '\u{012b}'),
+    ('\u{012c}', '\u{012d}'),
+    ('\u{012e}', '\u{012f}'),
+    ('\u{0132}', '\u{0133}'),
+    ('\u{0134}', '\u{0135}'),
+    ('\u{0136}', '\u{0137}'),
+    ('\u{0139}', '\u{013a}'),
+    ('\u{013b}', '\u{013c}'),
+    ('\u{013d}', '\u{013e}'),
+    ('\u{013f}', '\u{0140}'),
+    ('\u{0141}', '\u{0142}'),
+    ('\u{0143}', '\u{0144}'),
+    ('\u{0145}', '\u{0146}'),
+    ('\u{0147}', '\u{0148}'),
+    ('\u{014a}', '\u{014b}'),
+    ('\u{014c}', '\u{014d}'),
+    ('\u{014e}', '\u{014f}'),
+    ('\u{0150}', '\u{0151}'),
+    ('\u{0152}', '\u{0153}'),
+    ('\u{0154}', '\u{0155}'),
+    ('\u{0156}', '\u{0157}'),
+    ('\u{0158}', '\u{0159}'),
+    ('\u{015a}', '\u{015b}'),
+    ('\u{015c}', '\u{015d}'),
+    ('\u{015e}', '\u{015f}'),
+    ('\u{0160}', '\u{0161}'),
+    ('\u{0162}', '\u{0163}'),
+    ('\u{0164}', '\u{0165}'),
+    ('\u{0166}', '\u{0167}'),
+    ('\u{0168}', '\u{0169}'),
+    ('\u{016a}', '\u{016b}'),
+    ('\u{016c}', '\u{016d}'),
+    ('\u{016e}', '\u{016f}'),
+    ('\u{0170}', '\u{0171}'),
+    ('\u{0172}', '\u{0173}'),
+    ('\u{0174}', '\u{0175}'),
+    ('\u{0176}', '\u{0177}'),
+    ('\u{0178}', '\u{00ff}'),
+    ('\u{0179}', '\u{017a}'),
+    ('\u{017b}', '\u{017c}'),
+    ('\u{017d}', '\u{017e}'),
+    ('\u{017f}', '\u{0073}'),
+    ('\u{0181}', '\u{0253}'),
+    ('\u{0182}', '\u{0183}'),
+    ('\u{0184}', '\u{0185}'),
+    ('\u{0186}', '\u{0254}'),
+    ('\u{0187}', '\u{0188}'),
+    ('\u{0189}', '\u{0256}'),
+    ('\u{018a}', '\u{0257}'),
+    ('\u{018b}', '\u{018c}'),
+    ('\u{018e}', '\u{01dd}'),
+    ('\u{018f}', '\u{0259}'),
+    ('\u{0190}', '\u{025b}'),
+    ('\u{0191}', '\u{0192}'),
+    ('\u{0193}', '\u{0260}'),
+    ('\u{0194}', '\u{0263}'),
+    ('\u{0196}', '\u{0269}'),
+    ('\u{0197}', '\u{0268}'),
+    ('\u{0198}', '\u{0199}'),
+    ('\u{019c}', '\u{026f}'),
+    ('\u{019d}', '\u{0272}'),
+    ('\u{019f}', '\u{0275}'),
+    ('\u{01a0}', '\u{01a1}'),
+    ('\u{01a2}', '\u{01a3}'),
+    ('\u{01a4}', '\u{01a5}'),
+    ('\u{01a6}', '\u{0280}'),
+    ('\u{01a7}', '\u{01a8}'),
+    ('\u{01a9}', '\u{0283}'),
+    ('\u{01ac}', '\u{01ad}'),
+    ('\u{01ae}', '\u{0288}'),
+    ('\u{01af}', '\u{01b0}'),
+    ('\u{01b1}', '\u{028a}'),
+    ('\u{01b2}', '\u{028b}'),
+    ('\u{01b3}', '\u{01b4}'),
+    ('\u{01b5}', '\u{01b6}'),
+    ('\u{01b7}', '\u{0292}'),
+    ('\u{01b8}', '\u{01b9}'),
+    ('\u{01bc}', '\u{01bd}'),
+    ('\u{01c4}', '\u{01c6}'),
+    ('\u{01c5}', '\u{01c6}'),
+    ('\u{01c7}', '\u{01c9}'),
+    ('\u{01c8}', '\u{01c9}'),
+    ('\u{01ca}', '\u{01cc}'),
+    ('\u{01cb}', '\u{01cc}'),
+    ('\u{01cd}', '\u{01ce}'),
+    ('\u{01cf}', '\u{01d0}'),
+    ('\u{01d1}', '\u{01d2}'),
+    ('\u{01d3}', '\u{01d4}'),
+    ('\u{01d5}', '\u{01d6}'),
+    ('\u{01d7}', '\u{01d8}'),
+    ('\u{01d9}', '\u{01da}'),
+    ('\u{01db}', '\u{01dc}'),
+    ('\u{01de}', '\u{01df}'),
+    ('\u{01e0}', '\u{01e1}'),
+    ('\u{01e2}', '\u{01e3}'),
+    ('\u{01e4}', '\u{01e5}'),
+    ('\u{01e6}', '\u{01e7}'),
+    ('\u{01e8}', '\u{01e9}'),
+    ('\u{01ea}', '\u{01eb}'),
+    ('\u{01ec}', '\u{01ed}'),
+    ('\u{01ee}', '\u{01ef}'),
+    ('\u{01f1}', '\u{01f3}'),
+    ('\u{01f2}', '\u{01f3}'),
+    ('\u{01f4}', '\u{01f5}'),
+    ('\u{01f6}', '\u{0195}'),
+    ('\u{01f7}', '\u{01bf}'),
+    ('\u{01f8}', '\u{01f9}'),
+    ('\u{01fa}', '\u{01fb}'),
+    ('\u{01fc}', '\u{01fd}'),
+    ('\u{01fe}', '\u{01ff}'),
+    ('\u{0200}', '\u{0201}'),
+    ('\u{0202}', '\u{0203}'),
+    ('\u{0204}', '\u{0205}'),
+    ('\u{0206}', '\u{0207}'),
+    ('\u{0208}', '\u{0209}'),
+    ('\u{020a}', '\u{020b}'),
+    ('\u{020c}', '\u{020d}'),
+    ('\u{020e}', '\u{020f}'),
+    ('\u{0210}', '\u{0211}'),
+    ('\u{0212}', '\u{0213}'),
+    ('\u{0214}', '\u{0215}'),
+    ('\u{0216}', '\u{0217}'),
+    ('\u{0218}', '\u{0219}'),
+    ('\u{021a}', '\u{021b}'),
+    ('\u{021c}', '\u{021d}'),
+    ('\u{021e}', '\u{021f}'),
+    ('\u{0220}', '\u{019e}'),
+    ('\u{0222}', '\u{0223}'),
+    ('\u{0224}', '\u{0225}'),
+    ('\u{0226}', '\u{0227}'),
+    ('\u{0228}', '\u{0229}'),
+    ('\u{022a}', '\u{022b}'),
+    ('\u{022c}', '\u{022d}'),
+    ('\u{022e}', '\u{022f}'),
+    ('\u{0230}', '\u{0231}'),
+    ('\u{0232}', '\u{0233}'),
+    ('\u{023a}', '\u{2c65}'),
+    ('\u{023b}', '\u{023c}'),
+    ('\u{023d}', '\u{019a}'),
+    ('\u{023e}', '\u{2c66}'),
+    ('\u{0241}', '\u{0242}'),
+    ('\u{0243}', '\u{0180}'),
+    ('\u{0244}', '\u{0289}'),
+    ('\u{0245}', '\u{028c}'),
+    ('\u{0246}', '\u{0247}'),
+    ('\u{0248}', '\u{0249}'),
+    ('\u{024a}', '\u{024b}'),
+    ('\u{024c}', '\u{024d}'),
+    ('\u{024e}', '\u{024f}'),
+    ('\u{0345}', '\u{03b9}'),
+    ('\u{0370}', '\u{0371}'),
+    ('\u{0372}', '\u{0373}'),
+    ('\u{0376}', '\u{0377}'),
+    ('\u{037f}', '\u{03f3}'),
+    ('\u{0386}', '\u{03ac}'),
+    ('\u{0388}', '\u{03ad}'),
+    ('\u{0389}', '\u{03ae}'),
+    ('\u{038a}', '\u{03af}'),
+    ('\u{038c}', '\u{03cc}'),
+    ('\u{038e}', '\u{03cd}'),
+    ('\u{038f}', '\u{03ce}'),
+    ('\u{0391}', '\u{03b1}'),
+    ('\u{0392}', '\u{03b2}'),
+    ('\u{0393}', '\u{03b3}'),
+    ('\u{0394}', '\u{03b4}'),
+    ('\u{0395}', '\u{03b5}'),
+    ('\u{0396}', '\u{03b6}'),
+    ('\u{0397}', '\u{03b7}'),
+    ('\u{0398}', '\u{03b8}'),
+    ('\u{0399}', '\u{03b9}'),
+    ('\u{039a}', '\u{03ba}'),
+    ('\u{039b}', '\u{03bb}'),
+    ('\u{039c}', '\u{03bc}'),
+    ('\u{039d}', '\u{03bd}'),
+    ('\u{039e}', '\u{03be}'),
+    ('\u{039f}', '\u{03bf}'),
+    ('\u{03a0}', '\u{03c0}'),
+    ('\u{03a1}', '\u{03c1}'),
+    ('\u{03a3}', '\u{03c3}'),
+    ('\u{03a4}', '\u{03c4}'),
+    ('\u{03a5}', '\u{03c5}'),
+    ('\u{03a6}', '\u{03c6}'),
+    ('\u{03a7}', '\u{03c7}'),
+    ('\u{03a8}', '\u{03c8}'),
+    ('\u{03a9}', '\u{03c9}'),
+    ('\u{03aa}', '\u{03ca}'),
+    ('\u{03ab}', '\u{03cb}'),
+    ('\u{03c2}', '\u{03c3}'),
+    ('\u{03cf}', '\u{03d7}'),
+    ('\u{03d0}', '\u{03b2}'),
+    ('\u{03d1}', '\u{03b8}'),
+    ('\u{03d5}', '\u{03c6}'),
+    ('\u{03d6}', '\u{03c0}'),
+    ('\u{03d8}', '\u{03d9}'),
+    ('\u{03da}', '\u{03db}'),
+    ('\u{03dc}', '\u{03dd}'),
+    ('\u{03de}', '\u{03df}'),
+    ('\u{03e0}', '\u{03e1}'),
+    ('\u{03e2}', '\u{03e3}'),
+    ('\u{03e4}', '\u{03e5}'),
+    ('\u{03e6}', '\u{03e7}'),
+    ('\u{03e8}', '\u{03e9}'),
+    ('\u{03ea}', '\u{03eb}'),
+    ('\u{03ec}', '\u{03ed}'),
+    ('\u{03ee}', '\u{03ef}'),
+    ('\u{03f0}', '\u{03ba}'),
+    ('\u{03f1}', '\u{03c1}'),
+    ('\u{03f4}', '\u{03b8}'),
+    ('\u{03f5}', '\u{03b5}'),
+    ('\u{03f7}', '\u{03f8}'),
+    ('\u{03f9}', '\u{03f2}'),
+    ('\u{03fa}', '\u{03fb}'),
+    ('\u{03fd}', '\u{037b}'),
+    ('\u{03fe}', '\u{037c}'),
+    ('\u{03ff}', '\u{037d}'),
+    ('\u{0400}', '\u{0450}'),
+    ('\u{0401}', '\u{0451}'),
+    ('\u{0402}', '\u{0452}'),
+    ('\u{0403}', '\u{0453}'),
+    ('\u{0404}', '\u{0454}'),
+    ('\u{0405}', '\u{0455}'),
+    ('\u{0406}', '\u{0456}'),
+    ('\u{0407}', '\u{0457}'),
+    ('\u{0408}', '\u{0458}'),
+    ('\u{0409}', '\u{0459}'),
+    ('\u{040a}', '\u{045a}'),
+    ('\u{040b}', '\u{045b}'),
+    ('\u{040c}', '\u{045c}'),
+    ('\u{040d}', '\u{045d}'),
+    ('\u{040e}', '\u{045e}'),
+    ('\u{040f}', '\u{045f}'),
+    ('\u{0410}', '\u{0430}'),
+    ('\u{0411}', '\u{0431}'),
+    ('\u{0412}', '\u{0432}'),
+    ('\u{0413}', '\u{0433}'),
+    ('\u{0414}', '\u{0434}'),
+    ('\u{0415}', '\u{0435}'),
+    ('\u{0416}', '\u{0436}'),
+    ('\u{0417}', '\u{0437}'),
+    ('\u{0418}', '\u{0438}'),
+    ('\u{0419}', '\u{0439}'),
+    ('\u{041a}', '\u{043a}'),
+    ('\u{041b}', '\u{043b}'),
+    ('\u{041c}', '\u{043c}'),
+    ('\u{041d}', '\u{043d}'),
+    ('\u{041e}', '\u{043e}'),
+    ('\u{041f}', '\u{043f}'),
+    ('\u{0420}', '\u{0440}'),
+    ('\u{0421}', '\u{0441}'),
+    ('\u{0422}', '\u{0442}'),
+    ('\u{0423}', '\u{0443}'),
+    ('\u{0424}', '\u{0444}'),
+    ('\u{0425}', '\u{0445}'),
+    ('\u{0426}', '\u{0446}'),
+    ('\u{0427}', '\u{0447}'),
+    ('\u{0428}', '\u{0448}'),
+    ('\u{0429}', '\u{0449}'),
+    ('\u{042a}', '\u{044a}'),
+    ('\u{042b}', '\u{044b}'),
+    ('\u{042c}', '\u{044c}'),
+    ('\u{042d}', '\u{044d}'),
+    ('\u{042e}', '\u{044e}'),
+    ('\u{042f}', '\u{044f}'),
+    ('\u{0460}', '\u{0461}'),
+    ('\u{0462}', '\u{0463}'),
+    ('\u{0464}', '\u{0465}'),
+    ('\u{0466}', '\u{0467}'),
+    ('\u{0468}', '\u{0469}'),
+    ('\u{046a}', '\u{046b}'),
+    ('\u{046c}', '\u{046d}'),
+    ('\u{046e}', '\u{046f}'),
+    ('\u{0470}', '\u{0471}'),
+    ('\u{0472}', '\u{0473}'),
+    ('\u{0474}', '\u{0475}'),
+    ('\u{0476}', '\u{0477}'),
+    ('\u{0478}', '\u{0479}'),
+    ('\u{047a}', '\u{047b}'),
+    ('\u{047c}', '\u{047d}'),
+    ('\u{047e}', '\u{047f}'),
+    ('\u{0480}', '\u{0481}'),
+    ('\u{048a}', '\u{048b}'),
+    ('\u{048c}', '\u{048d}'),
+    ('\u{048e}', '\u{048f}'),
+    ('\u{0490}', '\u{0491}'),
+    ('\u{0492}', '\u{0493}'),
+    ('\u{0494}', '\u{0495}'),
+    ('\u{0496}', '\u{0497}'),
+    ('\u{0498}', '\u{0499}'),
+    ('\u{049a}', '\u{049b}'),
+    ('\u{049c}', '\u{049d}'),
+    ('\u{049e}', '\u{049f}'),
+    ('\u{04a0}', '\u{04a1}'),
+    ('\u{04a2}', '\u{04a3}'),
+    ('\u{04a4}', '\u{04a5}'),
+    ('\u{04a6}', '\u{04a7}'),
+    ('\u{04a8}', '\u{04a9}'),
+    ('\u{04aa}', '\u{04ab}'),
+    ('\u{04ac}', '\u{04ad}'),
+    ('\u{04ae}', '\u{04af}'),
+    ('\u{04b0}', '\u{04b1}'),
+    ('\u{04b2}', '\u{04b3}'),
+    ('\u{04b4}', '\u{04b5}'),
+    ('\u{04b6}', '\u{04b7}'),
+    ('\u{04b8}', '\u{04b9}'),
+    ('\u{04ba}', '\u{04bb}'),
+    ('\u{04bc}', '\u{04bd}'),
+    ('\u{04be}', '\u{04bf}'),
+    ('\u{04c0}', '\u{04cf}'),
+    ('\u{04c1}', '\u{04c2}'),
+    ('\u{04c3}', '\u{04c4}'),
+    ('\u{04c5}', '\u{04c6}'),
+    ('\u{04c7}', '\u{04c8}'),
+    ('\u{04c9}', '\u{04ca}'),
+    ('\u{04cb}', '\u{04cc}'),
+    ('\u{04cd}', '\u{04ce}'),
+    ('\u{04d0}', '\u{04d1}'),
+    ('\u{04d2}', '\u{04d3}'),
+    ('\u{04d4}', '\u{04d5}'),
+    ('\u{04d6}', '\u{04d7}'),
+    ('\u{04d8}', '\u{04d9}'),
+    ('\u{04da}', '\u{04db}'),
+    ('\u{04dc}', '\u{04dd}'),
+    ('\u{04de}', '\u{04df}'),
+    ('\u{04e0}', '\u{04e1}'),
+    ('\u{04e2}', '\u{04e3}'),
+    ('\u{04e4}', '\u{04e5}'),
+    ('\u{04e6}', '\u{04e7}'),
+    ('\u{04e8}', '\u{04e9}'),
+    ('\u{04ea}', '\u{04eb}'),
+    ('\u{04ec}', '\u{04ed}'),
+    ('\u{04ee}', '\u{04ef}'),
+    ('\u{04f0}', '\u{04f1}'),
+    ('\u{04f2}', '\u{04f3}'),
+    ('\u{04f4}', '\u{04f5}'),
+    ('\u{04f6}', '\u{04f7}'),
+    ('\u{04f8}', '\u{04f9}'),
+    ('\u{04fa}', '\u{04fb}'),
+    ('\u{04fc}', '\u{04fd}'),
+    ('\u{04fe}', '\u{04ff}'),
+    ('\u{0500}', '\u{0501}'),
+    ('\u{0502}', '\u{0503}'),
+    ('\u{0504}', '\u{0505}'),
+    ('\u{0506}', '\u{0507}'),
+    ('\u{0508}', '\u{0509}'),
+    ('\u{050a}', '\u{050b}'),
+    ('\u{050c}', '\u{050d}'),
+    ('\u{050e}', '\u{050f}'),
+    ('\u{0510}', '\u{0511}'),
+    ('\u{0512}', '\u{0513}'),
+    ('\u{0514}', '\u{0515}'),
+    ('\u{0516}', '\u{0517}'),
+    ('\u{0518}', '\u{0519}'),
+    ('\u{051a}', '\u{051b}'),
+    ('\u{051c}', '\u{051d}'),
+    ('\u{051e}', '\u{051f}'),
+    ('\u{0520}', '\u{0521}'),
+    ('\u{0522}', '\u{0523}'),
+    ('\u{0524}', '\u{0525}'),
+    ('\u{0526}', '\u{0527}'),
+    ('\u{0528}', '\u{0529}'),
+    ('\u{052a}', '\u{052b}'),
+    ('\u{052c}', '\u{052d}'),
+    ('\u{052e}', '\u{052f}'),
+    ('\u{0531}', '\u{0561}'),
+    ('\u{0532}', '\u{0562}'),
+    ('\u{0533}', '\u{0563}'),
+    ('\u{0534}', '\u{0564}'),
+    ('\u{0535}', '\u{0565}'),
+    ('\u{0536}', '\u{0566}'),
+    ('\u{0537}', '\u{0567}'),
+    ('\u{0538}', '\u{0568}'),
+    ('\u{0539}', '\u{0569}'),
+    ('\u{053a}', '\u{056a}'),
+    ('\u{053b}', '\u{056b}'),
+    ('\u{053c}', '\u{056c}'),
+    ('\u{053d}', '\u{056d}'),
+    ('\u{053e}', '\u{056e}'),
+    ('\u{053f}', '\u{056f}'),
+    ('\u{0540}', '\u{0570}'),
+    ('\u{0541}', '\u{0571}'),
+    ('\u{0542}', '\u{0572}'),
+    ('\u{0543}', '\u{0573}'),
+    ('\u{0544}', '\u{0574}'),
+    ('\u{0545}', '\u{0575}'),
+    ('\u{0546}', '\u{0576}'),
+    ('\u{0547}', '\u{0577}'),
+    ('\u{0548}', '\u{0578}'),
+    ('\u{0549}', '\u{0579}'),
+    ('\u{054a}', '\u{057a}'),
+    ('\u{054b}', '\u{057b}'),
+    ('\u{054c}', '\u{057c}'),
+    ('\u{054d}', '\u{057d}'),
+    ('\u{054e}', '\u{057e}'),
+    ('\u{054f}', '\u{057f}'),
+    ('\u{0550}', '\u{0580}'),
+    ('\u{0551}', '\u{0581}'),
+    ('\u{0552}', '\u{0582}'),
+    ('\u{0553}', '\u{0583}'),
+    ('\u{0554}', '\u{0584}'),
+    ('\u{0555}', '\u{0585}'),
+    ('\u{0556}', '\u{0586}'),
+    ('\u{10a0}', '\u{2d00}'),
+    ('\u{10a1}', '\u{2d01}'),
+    ('\u{10a2}', '\u{2d02}'),
+    ('\u{10a3}', '\u{2d03}'),
+    ('\u{10a4}', '\u{2d04}'),
+    ('\u{10a5}', '\u{2d05}'),
+    ('\u{10a6}', '\u{2d06}'),
+    ('\u{10a7}', '\u{2d07}'),
+    ('\u{10a8}', '\u{2d08}'),
+    ('\u{10a9}', '\u{2d09}'),
+    ('\u{10aa}', '\u{2d0a}'),
+    ('\u{10ab}', '\u{2d0b}'),
+    ('\u{10ac}', '\u{2d0c}'),
+    ('\u{10ad}', '\u{2d0d}'),
+    ('\u{10ae}', '\u{2d0e}'),
+    ('\u{10af}', '\u{2d0f}'),
+    ('\u{10b0}', '\u{2d10}'),
+    ('\u{10b1}', '\u{2d11}'),
+    ('\u{10b2}', '\u{2d12}'),
+    ('\u{10b3}', '\u{2d13}'),
+    ('\u{10b4}', '\u{2d14}'),
+    ('\u{10b5}', '\u{2d15}'),
+    ('\u{10b6}', '\u{2d16}'),
+    ('\u{10b7}', '\u{2d17}'),
+    ('\u{10b8}', '\u{2d18}'),
+    ('\u{10b9}', '\u{2d19}'),
+    ('\u{10ba}', '\u{2d1a}'),
+    ('\u{10bb}', '\u{2d1b}'),
+    ('\u{10bc}', '\u{2d1c}'),
+    ('\u{10bd}', '\u{2d1d}'),
+    ('\u{10be}', '\u{2d1e}'),
+    ('\u{10bf}', '\u{2d1f}'),
+    ('\u{10c0}', '\u{2d20}'),
+    ('\u{10c1}', '\u{2d21}'),
+    ('\u{10c2}', '\u{2d22}'),
+    ('\u{10c3}', '\u{2d23}'),
+    ('\u{10c4}', '\u{2d24}'),
+    ('\u{10c5}', '\u{2d25}'),
+    ('\u{10c7}', '\u{2d27}'),
+    ('\u{10cd}', '\u{2d2d}'),
+    ('\u{13f8}', '\u{13f0}'),
+    ('\u{13f9}', '\u{13f1}'),
+    ('\u{13fa}', '\u{13f2}'),
+    ('\u{13fb}', '\u{13f3}'),
+    ('\u{13fc}', '\u{13f4}'),
+    ('\u{13fd}', '\u{13f5}'),
+    ('\u{1c80}', '\u{0432}'),
+    ('\u{1c81}', '\u{0434}'),
+    ('\u{1c82}', '\u{043e}'),
+    ('\u{1c83}', '\u{0441}'),
+    ('\u{1c84}', '\u{0442}'),
+    ('\u{1c85}', '\u{0442}'),
+    ('\u{1c86}', '\u{044a}'),
+    ('\u{1c87}', '\u{0463}'),
+    ('\u{1c88}', '\u{a64b}'),
+    ('\u{1c89}', '\u{1c8a}'),
+    ('\u{1c90}', '\u{10d0}'),
+    ('\u{1c91}', '\u{10d1}'),
+    ('\u{1c92}', '\u{10d2}'),
+    ('\u{1c93}', '\u{10d3}'),
+    ('\u{1c94}', '\u{10d4}'),
+    ('\u{1c95}', '\u{10d5}'),
+    ('\u{1c96}', '\u{10d6}'),
+    ('\u{1c97}', '\u{10d7}'),
+    ('\u{1c98}', '\u{10d8}'),
+    ('\u{1c99}', '\u{10d9}'),
+    ('\u{1c9a}', '\u{10da}'),
+    ('\u{1c9b}', '\u{10db}'),
+    ('\u{1c9c}', '\u{10dc}'),
+    ('\u{1c9d}', '\u{10dd}'),
+    ('\u{1c9e}', '\u{10de}'),
+    ('\u{1c9f}', '\u{10df}'),
+    ('\u{1ca0}', '\u{10e0}'),
+    ('\u{1ca1}', '\u{10e1}'),
+    ('\u{1ca2}', '\u{10e2}'),
+    ('\u{1ca3}', '\u{10e3}'),
+    ('\u{1ca4}', '\u{10e4}'),
+    ('\u{1ca5}', '\u{10e5}'),
+    ('\u{1ca6}', '\u{10e6}'),
+    ('\u{1ca7}', '\u{10e7}'),
+    ('\u{1ca8}', '\u{10e8}'),
+    ('\u{1ca9}', '\u{10e9}'),
+    ('\u{1caa}', '\u{10ea}'),
+    ('\u{1cab}', '\u{10eb}'),
+    ('\u{1cac}', '\u{10ec}'),
+    ('\u{1cad}', '\u{10ed}'),
+    ('\u{1cae}', '\u{10ee}'),
+    ('\u{1caf}', '\u{10ef}'),
+    ('\u{1cb0}', '\u{10f0}'),
+    ('\u{1cb1}', '\u{10f1}'),
+    ('\u{1cb2}', '\u{10f2}'),
+    ('\u{1cb3}', '\u{10f3}'),
+    ('\u{1cb4}', '\u{10f4}'),
+    ('\u{1cb5}', '\u{10f5}'),
+    ('\u{1cb6}', '\u{10f6}'),
+    ('\u{1cb7}', '\u{10f7}'),
+    ('\u{1cb8}', '\u{10f8}'),
+    ('\u{1cb9}', '\u{10f9}'),
+    ('\u{1cba}', '\u{10fa}'),
+    ('\u{1cbd}', '\u{10fd}'),
+    ('\u{1cbe}', '\u{10fe}'),
+    ('\u{1cbf}', '\u{10ff}'),
+    ('\u{1e00}', '\u{1e01}'),
+    ('\u{1e02}', '\u{1e03}'),
+    ('\u{1e04}', '\u{1e05}'),
+    ('\u{1e06}', '\u{1e07}'),
+    ('\u{1e08}', '\u{1e09}'),
+    ('\u{1e0a}', '\u{1e0b}'),
+    ('\u{1e0c}', '\u{1e0d}'),
+    ('\u{1e0e}', '\u{1e0f}'),
+    ('\u{1e10}', '\u{1e11}'),
+    ('\u{1e12}', '\u{1e13}'),
+    ('\u{1e14}', '\u{1e15}'),
+    ('\u{1e16}', '\u{1e17}'),
+    ('\u{1e18}', '\u{1e19}'),
+    ('\u{1e1a}', '\u{1e1b}'),
+    ('\u{1e1c}', '\u{1e1d}'),
+    ('\u{1e1e}', '\u{1e1f}'),
+    ('\u{1e20}', '\u{1e21}'),
+    ('\u{1e22}', '\u{1e23}'),
+    ('\u{1e24}', '\u{1e25}'),
+    ('\u{1e26}', '\u{1e27}'),
+    ('\u{1e28}', '\u{1e29}'),
+    ('\u{1e2a}', '\u{1e2b}'),
+    ('\u{1e2c}', '\u{1e2d}'),
+    ('\u{1e2e}', '\u{1e2f}'),
+    ('\u{1e30}', '\u{1e31}'),
+    ('\u{1e32}', '\u{1e33}'),
+    ('\u{1e34}', '\u{1e35}'),
+    ('\u{1e36}', '\u{1e37}'),
+    ('\u{1e38}', '\u{1e39}'),
+    ('\u{1e3a}', '\u{1e3b}'),
+    ('\u{1e3c}', '\u{1e3d}'),
+    ('\u{1e3e}', '\u{1e3f}'),
+    ('\u{1e40}', '\u{1e41}'),
+    ('\u{1e42}', '\u{1e43}'),
+    ('\u{1e44}', '\u{1e45}'),
+    ('\u{1e46}', '\u{1e47}'),
+    ('\u{1e48}', '\u{1e49}'),
+    ('\u{1e4a}', '\u{1e4b}'),
+    ('\u{1e4c}', '\u{1e4d}'),
+    ('\u{1e4e}', '\u{1e4f}'),
+    ('\u{1e50}', '\u{1e51}'),
+    ('\u{1e52}', '\u{1e53}'),
+    ('\u{1e54}', '\u{1e55}'),
+    ('\u{1e56}', '\u{1e57}'),
+    ('\u{1e58}', '\u{1e59}'),
+    ('\u{1e5a}', '\u{1e5b}'),
+    ('\u{1e5c}', '\u{1e5d}'),
+    ('\u{1e5e}', '\u{1e5f}'),
+    ('\u{1e60}', '\u{1e61}'),
+    ('\u{1e62}', '\u{1e63}'),
+    ('\u{1e64}', '\u{1e65}'),
+    ('\u{1e66}', '\u{1e67}'),
+    ('\u{1e68}', '\u{1e69}'),
+    ('\u{1e6a}', '\u{1e6b}'),
+    ('\u{1e6c}', '\u{1e6d}'),
+    ('\u{1e6e}', '\u{1e6f}'),
+    ('\u{1e70}', '\u{1e71}'),
+    ('\u{1e72}', '\u{1e73}'),
+    ('\u{1e74}', '\u{1e75}'),
+    ('\u{1e76}', '\u{1e77}'),
+    ('\u{1e78}', '\u{1e79}'),
+    ('\u{1e7a}', '\u{1e7b}'),
+    ('\u{1e7c}', '\u{1e7d}'),
+    ('\u{1e7e}', '\u{1e7f}'),
+    ('\u{1e80}', '\u{1e81}'),
+    ('\u{1e82}', '\u{1e83}'),
+    ('\u{1e84}', '\u{1e85}'),
+    ('\u{1e86}', '\u{1e87}'),
+    ('\u{1e88}', '\u{1e89}'),
+    ('\u{1e8a}', '\u{1e8b}'),
+    ('\u{1e8c}', '\u{1e8d}'),
+    ('\u{1e8e}', '\u{1e8f}'),
+    ('\u{1e90}', '\u{1e91}'),
+    ('\u{1e92}', '\u{1e93}'),
+    ('\u{1e94}', '\u{1e95}'),
+    ('\u{1e9b}', '\u{1e61}'),
+    ('\u{1e9e}', '\u{00df}'),
+    ('\u{1ea0}', '\u{1ea1}'),
+    ('\u{1ea2}', '\u{1ea3}'),
+    ('\u{1ea4}', '\u{1ea5}'),
+    ('\u{1ea6}', '\u{1ea7}'),
+    ('\u{1ea8}', '\u{1ea9}'),
+    ('\u{1eaa}', '\u{1eab}'),
+    ('\u{1eac}', '\u{1ead}'),
+    ('\u{1eae}', '\u{1eaf}'),
+    ('\u{1eb0}', '\u{1eb1}'),
+    ('\u{1eb2}', '\u{1eb3}'),
+    ('\u{1eb4}', '\u{1eb5}'),
+    ('\u{1eb6}', '\u{1eb7}'),
+    ('\u{1eb8}', '\u{1eb9}'),
+    ('\u{1eba}', '\u{1ebb}'),
+    ('\u{1ebc}', '\u{1ebd}'),
+    ('\u{1ebe}', '\u{1ebf}'),
+    ('\u{1ec0}', '\u{1ec1}'),
+    ('\u{1ec2}', '\u{1ec3}'),
+    ('\u{1ec4}', '\u{1ec5}'),
+    ('\u{1ec6}', '\u{1ec7}'),
+    ('\u{1ec8}', '\u{1ec9}'),
+    ('\u{1eca}', '\u{1ecb}'),
+    ('\u{1ecc}', '\u{1ecd}'),
+    ('\u{1ece}', '\u{1ecf}'),
+    ('\u{1ed0}', '\u{1ed1}'),
+    ('\u{1ed2}', '\u{1ed3}'),
+    ('\u{1ed4}', '\u{1ed5}'),
+    ('\u{1ed6}', '\u{1ed7}'),
+    ('\u{1ed8}', '\u{1ed9}'),
+    ('\u{1eda}', '\u{1edb}'),
+    ('\u{1edc}', '\u{1edd}'),
+    ('\u{1ede}', '\u{1edf}'),
+    ('\u{1ee0}', '\u{1ee1}'),
+    ('\u{1ee2}', '\u{1ee3}'),
+    ('\u{1ee4}', '\u{1ee5}'),
+    ('\u{1ee6}', '\u{1ee7}'),
+    ('\u{1ee8}', '\u{1ee9}'),
+    ('\u{1eea}', '\u{1eeb}'),
+    ('\u{1eec}', '\u{1eed}'),
+    ('\u{1eee}', '\u{1eef}'),
+    ('\u{1ef0}', '\u{1ef1}'),
+    ('\u{1ef2}', '\u{1ef3}'),
+    ('\u{1ef4}', '\u{1ef5}'),
+    ('\u{1ef6}', '\u{1ef7}'),
+    ('\u{1ef8}', '\u{1ef9}'),
+    ('\u{1efa}', '\u{1efb}'),
+    ('\u{1efc}', '\u{1efd}'),
+    ('\u{1efe}', '\u{1eff}'),
+    ('\u{1f08}', '\u{1f00}'),
+    ('\u{1f09}', '\u{1f01}'),
+    ('\u{1f0a}', '\u{1f02}'),
+    ('\u{1f0b}', '\u{1f03}'),
+    ('\u{1f0c}', '\u{1f04}'),
+    ('\u{1f0d}', '\u{1f05}'),
+    ('\u{1f0e}', '\u{1f06}'),
+    ('\u{1f0f}', '\u{1f07}'),
+    ('\u{1f18}', '\u{1f10}'),
+    ('\u{1f19}', '\u{1f11}'),
+    ('\u{1f1a}', '\u{1f12}'),
+    ('\u{1f1b}', '\u{1f13}'),
+    ('\u{1f1c}', '\u{1f14}'),
+    ('\u{1f1d}', '\u{1f15}'),
+    ('\u{1f28}', '\u{1f20}'),
+    ('\u{1f29}', '\u{1f21}'),
+    ('\u{1f2a}', '\u{1f22}'),
+    ('\u{1f2b}', '\u{1f23}'),
+    ('\u{1f2c}', '\u{1f24}'),
+    ('\u{1f2d}', '\u{1f25}'),
+    ('\u{1f2e}', '\u{1f26}'),
+    ('\u{1f2f}', '\u{1f27}'),
+    ('\u{1f38}', '\u{1f30}'),
+    ('\u{1f39}', '\u{1f31}'),
+    ('\u{1f3a}', '\u{1f32}'),
+    ('\u{1f3b}', '\u{1f33}'),
+    ('\u{1f3c}', '\u{1f34}'),
+    ('\u{1f3d}', '\u{1f35}'),
+    ('\u{1f3e}', '\u{1f36}'),
+    ('\u{1f3f}', '\u{1f37}'),
+    ('\u{1f48}', '\u{1f40}'),
+    ('\u{1f49}', '\u{1f41}'),
+    ('\u{1f4a}', '\u{1f42}'),
+    ('\u{1f4b}', '\u{1f43}'),
+    ('\u{1f4c}', '\u{1f44}'),
+    ('\u{1f4d}', '\u{1f45}'),
+    ('\u{1f59}', '\u{1f51}'),
+    ('\u{1f5b}', '\u{1f53}'),
+    ('\u{1f5d}', '\u{1f55}'),
+    ('\u{1f5f}', '\u{1f57}'),
+    ('\u{1f68}', '\u{1f60}'),
+    ('\u{1f69}', '\u{1f61}'),
+    ('\u{1f6a}', '\u{1f62}'),
+    ('\u{1f6b}', '\u{1f63}'),
+    ('\u{1f6c}', '\u{1f64}'),
+    ('\u{1f6d}', '\u{1f65}'),
+    ('\u{1f6e}', '\u{1f66}'),
+    ('\u{1f6f}', '\u{1f67}'),
+    ('\u{1f88}', '\u{1f80}'),
+    ('\u{1f89}', '\u{1f81}'),
+    ('\u{1f8a}', '\u{1f82}'),
+    ('\u{1f8b}', '\u{1f83}'),
+    ('\u{1f8c}', '\u{1f84}'),
+    ('\u{1f8d}', '\u{1f85}'),
+    ('\u{1f8e}', '\u{1f86}'),
+    ('\u{1f8f}', '\u{1f87}'),
+    ('\u{1f98}', '\u{1f90}'),
+    ('\u{1f99}', '\u{1f91}'),
+    ('\u{1f9a}', '\u{1f92}'),
+    ('\u{1f9b}', '\u{1f93}'),
+    ('\u{1f9c}', '\u{1f94}'),
+    ('\u{1f9d}', '\u{1f95}'),
+    ('\u{1f9e}', '\u{1f96}'),
+    ('\u{1f9f}', '\u{1f97}'),
+    ('\u{1fa8}', '\u{1fa0}'),
+    ('\u{1fa9}', '\u{1fa1}'),
+    ('\u{1faa}', '\u{1fa2}'),
+    ('\u{1fab}', '\u{1fa3}'),
+    ('\u{1fac}', '\u{1fa4}'),
+    ('\u{1fad}', '\u{1fa5}'),
+    ('\u{1fae}', '\u{1fa6}'),
+    ('\u{1faf}', '\u{1fa7}'),
+    ('\u{1fb8}', '\u{1fb0}'),
+    ('\u{1fb9}', '\u{1fb1}'),
+    ('\u{1fba}', '\u{1f70}'),
+    ('\u{1fbb}', '\u{1f71}'),
+    ('\u{1fbc}', '\u{1fb3}'),
+    ('\u{1fbe}', '\u{03b9}'),
+    ('\u{1fc8}', '\u{1f72}'),
+    ('\u{1fc9}', '\u{1f73}'),
+    ('\u{1fca}', '\u{1f74}'),
+    ('\u{1fcb}', '\u{1f75}'),
+    ('\u{1fcc}', '\u{1fc3}'),
+    ('\u{1fd3}', '\u{0390}'),
+    ('\u{1fd8}', '\u{1fd0}'),
+    ('\u{1fd9}', '\u{1fd1}'),
+    ('\u{1fda}', '\u{1f76}'),
+    ('\u{1fdb}', '\u{1f77}'),
+    ('\u{1fe3}', '\u{03b0}'),
+    ('\u{1fe8}', '\u{1fe0}'),
+    ('\u{1fe9}', '\u{1fe1}'),
+    ('\u{1fea}', '\u{1f7a}'),
+    ('\u{1feb}', '\u{1f7b}'),
+    ('\u{1fec}', '\u{1fe5}'),
+    ('\u{1ff8}', '\u{1f78}'),
+    ('\u{1ff9}', '\u{1f79}'),
+    ('\u{1ffa}', '\u{1f7c}'),
+    ('\u{1ffb}', '\u{1f7d}'),
+    ('\u{1ffc}', '\u{1ff3}'),
+    ('\u{2126}', '\u{03c9}'),
+    ('\u{212a}', '\u{006b}'),
+    ('\u{212b}', '\u{00e5}'),
+    ('\u{2132}', '\u{214e}'),
+    ('\u{2160}', '\u{2170}'),
+    ('\u{2161}', '\u{2171}'),
+    ('\u{2162}', '\u{2172}'),
+    ('\u{2163}', '\u{2173}'),
+    ('\u{2164}', '\u{2174}'),
+    ('\u{2165}', '\u{2175}'),
+    ('\u{2166}', '\u{2176}'),
+    ('\u{2167}', '\u{2177}'),
+    ('\u{2168}', '\u{2178}'),
+    ('\u{2169}', '\u{2179}'),
+    ('\u{216a}', '\u{217a}'),
+    ('\u{216b}', '\u{217b}'),
+    ('\u{216c}', '\u{217c}'),
+    ('\u{216d}', '\u{217d}'),
+    ('\u{216e}', '\u{217e}'),
+    ('\u{216f}', '\u{217f}'),
+    ('\u{2183}', '\u{2184}'),
+    ('\u{24b6}', '\u{24d0}'),
+    ('\u{24b7}', '\u{24d1}'),
+    ('\u{24b8}', '\u{24d2}'),
+    ('\u{24b9}', '\u{24d3}'),
+    ('\u{24ba}', '\u{24d4}'),
+    ('\u{24bb}', '\u{24d5}'),
+    ('\u{24bc}', '\u{24d6}'),
+    ('\u{24bd}', '\u{24d7}'),
+    ('\u{24be}', '\u{24d8}'),
+    ('\u{24bf}', '\u{24d9}'),
+    ('\u{24c0}', '\u{24da}'),
+    ('\u{24c1}', '\u{24db}'),
+    ('\u{24c2}', '\u{24dc}'),
+    ('\u{24c3}', '\u{24dd}'),
+    ('\u{24c4}', '\u{24de}'),
+    ('\u{24c5}', '\u{24df}'),
+    ('\u{24c6}', '\u{24e0}'),
+    ('\u{24c7}', '\u{24e1}'),
+    ('\u{24c8}', '\u{24e2}'),
+    ('\u{24c9}', '\u{24e3}'),
+    ('\u{24ca}', '\u{24e4}'),
+    ('\u{24cb}', '\u{24e5}'),
+    ('\u{24cc}', '\u{24e6}'),
+    ('\u{24cd}', '\u{24e7}'),
+    ('\u{24ce}', '\u{24e8}'),
+    ('\u{24cf}', '\u{24e9}'),
+    ('\u{2c00}', '\u{2c30}'),
+    ('\u{2c01}', '\u{2c31}'),
+    ('\u{2c02}', '\u{2c32}'),
+    ('\u{2c03}', '\u{2c33}'),
+    ('\u{2c04}', '\u{2c34}'),
+    ('\u{2c05}', '\u{2c35}'),
+    ('\u{2c06}', '\u{2c36}'),
+    ('\u{2c07}', '\u{2c37}'),
+    ('\u{2c08}', '\u{2c38}'),
+    ('\u{2c09}', '\u{2c39}'),
+    ('\u{2c0a}', '\u{2c3a}'),
+    ('\u{2c0b}', '\u{2c3b}'),
+    ('\u{2c0c}', '\u{2c3c}'),
+    ('\u{2c0d}', '\u{2c3d}'),
+    ('\u{2c0e}', '\u{2c3e}'),
+    ('\u{2c0f}', '\u{2c3f}'),
+    ('\u{2c10}', '\u{2c40}'),
+    ('\u{2c11}', '\u{2c41}'),
+    ('\u{2c12}', '\u{2c42}'),
+    ('\u{2c13}', '\u{2c43}'),
+    ('\u{2c14}', '\u{2c44}'),
+    ('\u{2c15}', '\u{2c45}'),
+    ('\u{2c16}', '\u{2c46}'),
+    ('\u{2c17}', '\u{2c47}'),
+    ('\u{2c18}', '\u{2c48}'),
+    ('\u{2c19}', '\u{2c49}'),
+    ('\u{2c1a}', '\u{2c4a}'),
+    ('\u{2c1b}', '\u{2c4b}'),
+    ('\u{2c1c}', '\u{2c4c}'),
+    ('\u{2c1d}', '\u{2c4d}'),
+    ('\u{2c1e}', '\u{2c4e}'),
+    ('\u{2c1f}', '\u{2c4f}'),
+    ('\u{2c20}', '\u{2c50}'),
+    ('\u{2c21}', '\u{2c51}'),
+    ('\u{2c22}', '\u{2c52}'),
+    ('\u{2c23}', '\u{2c53}'),
+    ('\u{2c24}', '\u{2c54}'),
+    ('\u{2c25}', '\u{2c55}'),
+    ('\u{2c26}', '\u{2c56}'),
+    ('\u{2c27}', '\u{2c57}'),
+    ('\u{2c28}', '\u{2c58}'),
+    ('\u{2c29}', '\u{2c59}'),
+    ('\u{2c2a}', '\u{2c5a}'),
+    ('\u{2c2b}', '\u{2c5b}'),
+    ('\u{2c2c}', '\u{2c5c}'),
+    ('\u{2c2d}', '\u{2c5d}'),
+    ('\u{2c2e}', '\u{2c5e}'),
+    ('\u{2c2f}', '\u{2c5f}'),
+    ('\u{2c60}', '\u{2c61}'),
+    ('\u{2c62}', '\u{026b}'),
+    ('\u{2c63}', '\u{1d7d}'),
+    ('\u{2c64}', '\u{027d}'),
+    ('\u{2c67}', '\u{2c68}'),
+    ('\u{2c69}', '\u{2c6a}'),
+    ('\u{2c6b}', '\u{2c6c}'),
+    ('\u{2c6d}', '\u{0251}'),
+    ('\u{2c6e}', '\u{0271}'),
+    ('\u{2c6f}', '\u{0250}'),
+    ('\u{2c70}', '\u{0252}'),
+    ('\u{2c72}', '\u{2c73}'),
+    ('\u{2c75}', '\u{2c76}'),
+    ('\u{2c7e}', '\u{023f}'),
+    ('\u{2c7f}', '\u{0240}'),
+    ('\u{2c80}', '\u{2c81}'),
+    ('\u{2c82}', '\u{2c83}'),
+    ('\u{2c84}', '\u{2c85}'),
+    ('\u{2c86}', '\u{2c87}'),
+    ('\u{2c88}', '\u{2c89}'),
+    ('\u{2c8a}', '\u{2c8b}'),
+    ('\u{2c8c}', '\u{2c8d}'),
+    ('\u{2c8e}', '\u{2c8f}'),
+    ('\u{2c90}', '\u{2c91}'),
+    ('\u{2c92}', '\u{2c93}'),
+    ('\u{2c94}', '\u{2c95}'),
+    ('\u{2c96}', '\u{2c97}'),
+    ('\u{2c98}', '\u{2c99}'),
+    ('\u{2c9a}', '\u{2c9b}'),
+    ('\u{2c9c}', '\u{2c9d}'),
+    ('\u{2c9e}', '\u{2c9f}'),
+    ('\u{2ca0}', '\u{2ca1}'),
+    ('\u{2ca2}', '\u{2ca3}'),
+    ('\u{2ca4}', '\u{2ca5}'),
+    ('\u{2ca6}', '\u{2ca7}'),
+    ('\u{2ca8}', '\u{2ca9}'),
+    ('\u{2caa}', '\u{2cab}'),
+    ('\u{2cac}', '\u{2cad}'),
+    ('\u{2cae}', '\u{2caf}'),
+    ('\u{2cb0}', '\u{2cb1}'),
+    ('\u{2cb2}', '\u{2cb3}'),
+    ('\u{2cb4}', '\u{2cb5}'),
+    ('\u{2cb6}', '\u{2cb7}'),
+    ('\u{2cb8}', '\u{2cb9}'),
+    ('\u{2cba}', '\u{2cbb}'),
+    ('\u{2cbc}', '\u{2cbd}'),
+    ('\u{2cbe}', '\u{2cbf}'),
+    ('\u{2cc0}', '\u{2cc1}'),
+    ('\u{2cc2}', '\u{2cc3}'),
+    ('\u{2cc4}', '\u{2cc5}'),
+    ('\u{2cc6}', '\u{2cc7}'),
+    ('\u{2cc8}', '\u{2cc9}'),
+    ('\u{2cca}', '\u{2ccb}'),
+    ('\u{2ccc}', '\u{2ccd}'),
+    ('\u{2cce}', '\u{2ccf}'),
+    ('\u{2cd0}', '\u{2cd1}'),
+    ('\u{2cd2}', '\u{2cd3}'),
+    ('\u{2cd4}', '\u{2cd5}'),
+    ('\u{2cd6}', '\u{2cd7}'),
+    ('\u{2cd8}', '\u{2cd9}'),
+    ('\u{2cda}', '\u{2cdb}'),
+    ('\u{2cdc}', '\u{2cdd}'),
+    ('\u{2cde}', '\u{2cdf}'),
+    ('\u{2ce0}', '\u{2ce1}'),
+    ('\u{2ce2}', '\u{2ce3}'),
+    ('\u{2ceb}', '\u{2cec}'),
+    ('\u{2ced}', '\u{2cee}'),
+    ('\u{2cf2}', '\u{2cf3}'),
+    ('\u{a640}', '\u{a641}'),
+    ('\u{a642}', '\u{a643}'),
+    ('\u{a644}', '\u{a645}'),
+    ('\u{a646}', '\u{a647}'),
+    ('\u{a648}', '\u{a649}'),
+    ('\u{a64a}', '\u{a64b}'),
+    ('\u{a64c}', '\u{a64d}'),
+    ('\u{a64e}', '\u{a64f}'),
+    ('\u{a650}', '\u{a651}'),
+    ('\u{a652}', '\u{a653}'),
+    ('\u{a654}', '\u{a655}'),
+    ('\u{a656}', '\u{a657}'),
+    ('\u{a658}', '\u{a659}'),
+    ('\u{a65a}', '\u{a65b}'),
+    ('\u{a65c}', '\u{a65d}'),
+    ('\u{a65e}', '\u{a65f}'),
+    ('\u{a660}', '\u{a661}'),
+    ('\u{a662}', '\u{a663}'),
+    ('\u{a664}', '\u{a665}'),
+    ('\u{a666}', '\u{a667}'),
+    ('\u{a668}', '\u{a669}'),
+    ('\u{a66a}', '\u{a66b}'),
+    ('\u{a66c}', '\u{a66d}'),
+    ('\u{a680}', '\u{a681}'),
+    ('\u{a682}', '\u{a683}'),
+    ('\u{a684}', '\u{a685}'),
+    ('\u{a686}', '\u{a687}'),
+    ('\u{a688}', '\u{a689}'),
+    ('\u{a68a}', '\u{a68b}'),
+    ('\u{a68c}', '\u{a68d}'),
+    ('\u{a68e}', '\u{a68f}'),
+    ('\u{a690}', '\u{a691}'),
+    ('\u{a692}', '\u{a693}'),
+    ('\u{a694}', '\u{a695}'),
+    ('\u{a696}', '\u{a697}'),
+    ('\u{a698}', '\u{a699}'),
+    ('\u{a69a}', '\u{a69b}'),
+    ('\u{a722}', '\u{a723}'),
+    ('\u{a724}', '\u{a725}'),
+    ('\u{a726}', '\u{a727}'),
+    ('\u{a728}', '\u{a729}'),
+    ('\u{a72a}', '\u{a72b}'),
+    ('\u{a72c}', '\u{a72d}'),
+    ('\u{a72e}', '\u{a72f}'),
+    ('\u{a732}', '\u{a733}'),
+    ('\u{a734}', '\u{a735}'),
+    ('\u{a736}', '\u{a737}'),
+    ('\u{a738}', '\u{a739}'),
+    ('\u{a73a}', '\u{a73b}'),
+    ('\u{a73c}', '\u{a73d}'),
+    ('\u{a73e}', '\u{a73f}'),
+    ('\u{a740}', '\u{a741}'),
+    ('\u{a742}', '\u{a743}'),
+    ('\u{a744}', '\u{a745}'),
+    ('\u{a746}', '\u{a747}'),
+    ('\u{a748}', '\u{a749}'),
+    ('\u{a74a}', '\u{a74b}'),
+    ('\u{a74c}', '\u{a74d}'),
+    ('\u{a74e}', '\u{a74f}'),
+    ('\u{a750}', '\u{a751}'),
+    ('\u{a752}', '\u{a753}'),
+    ('\u{a754}', '\u{a755}'),
+    ('\u{a756}', '\u{a757}'),
+    ('\u{a758}', '\u{a759}'),
+    ('\u{a75a}', '\u{a75b}'),
+    ('\u{a75c}', '\u{a75d}'),
+    ('\u{a75e}', '\u{a75f}'),
+    ('\u{a760}', '\u{a761}'),
+    ('\u{a762}', '\u{a763}'),
+    ('\u{a764}', '\u{a765}'),
+    ('\u{a766}', '\u{a767}'),
+    ('\u{a768}', '\u{a769}'),
+    ('\u{a76a}', '\u{a76b}'),
+    ('\u{a76c}', '\u{a76d}'),
+    ('\u{a76e}', '\u{a76f}'),
+    ('\u{a779}', '\u{a77a}'),
+    ('\u{a77b}', '\u{a77c}'),
+    ('\u{a77d}', '\u{1d79}'),
+    ('\u{a77e}', '\u{a77f}'),
+    ('\u{a780}', '\u{a781}'),
+    ('\u{a782}', '\u{a783}'),
+    ('\u{a784}', '\u{a785}'),
+    ('\u{a786}', '\u{a787}'),
+    ('\u{a78b}', '\u{a78c}'),
+    ('\u{a78d}', '\u{0265}'),
+    ('\u{a790}', '\u{a791}'),
+    ('\u{a792}', '\u{a793}'),
+    ('\u{a796}', '\u{a797}'),
+    ('\u{a798}', '\u{a799}'),
+    ('\u{a79a}', '\u{a79b}'),
+    ('\u{a79c}', '\u{a79d}'),
+    ('\u{a79e}', '\u{a79f}'),
+    ('\u{a7a0}', '\u{a7a1}'),
+    ('\u{a7a2}', '\u{a7a3}'),
+    ('\u{a7a4}', '\u{a7a5}'),
+    ('\u{a7a6}', '\u{a7a7}'),
+    ('\u{a7a8}', '\u{a7a9}'),
+    ('\u{a7aa}', '\u{0266}'),
+    ('\u{a7ab}', '\u{025c}'),
+    ('\u{a7ac}', '\u{0261}'),
+    ('\u{a7ad}', '\u{026c}'),
+    ('\u{a7ae}', '\u{026a}'),
+    ('\u{a7b0}', '\u{029e}'),
+    ('\u{a7b1}', '\u{0287}'),
+    ('\u{a7b2}', '\u{029d}'),
+    ('\u{a7b3}', '\u{ab53}'),
+    ('\u{a7b4}', '\u{a7b5}'),
+    ('\u{a7b6}', '\u{a7b7}'),
+    ('\u{a7b8}', '\u{a7b9}'),
+    ('\u{a7ba}', '\u{a7bb}'),
+    ('\u{a7bc}', '\u{a7bd}'),
+    ('\u{a7be}', '\u{a7bf}'),
+    ('\u{a7c0}', '\u{a7c1}'),
+    ('\u{a7c2}', '\u{a7c3}'),
+    ('\u{a7c4}', '\u{a794}'),
+    ('\u{a7c5}', '\u{0282}'),
+    ('\u{a7c6}', '\u{1d8e}'),
+    ('\u{a7c7}', '\u{a7c8}'),
+    ('\u{a7c9}', '\u{a7ca}'),
+    ('\u{a7cb}', '\u{0264}'),
+    ('\u{a7cc}', '\u{a7cd}'),
+    ('\u{a7d0}', '\u{a7d1}'),
+    ('\u{a7d6}', '\u{a7d7}'),
+    ('\u{a7d8}', '\u{a7d9}'),
+    ('\u{a7da}', '\u{a7db}'),
+    ('\u{a7dc}', '\u{019b}'),
+    ('\u{a7f5}', '\u{a7f6}'),
+    ('\u{ab70}', '\u{13a0}'),
+    ('\u{ab71}', '\u{13a1}'),
+    ('\u{ab72}', '\u{13a2}'),
+    ('\u{ab73}', '\u{13a3}'),
+    ('\u{ab74}', '\u{13a4}'),
+    ('\u{ab75}', '\u{13a5}'),
+    ('\u{ab76}', '\u{13a6}'),
+    ('\u{ab77}', '\u{13a7}'),
+    ('\u{ab78}', '\u{13a8}'),
+    ('\u{ab79}', '\u{13a9}'),
+    ('\u{ab7a}', '\u{13aa}'),
+    ('\u{ab7b}', '\u{13ab}'),
+    ('\u{ab7c}', '\u{13ac}'),
+    ('\u{ab7d}', '\u{13ad}'),
+    ('\u{ab7e}', '\u{13ae}'),
+    ('\u{ab7f}', '\u{13af}'),
+    ('\u{ab80}', '\u{13b0}'),
+    ('\u{ab81}', '\u{13b1}'),
+    ('\u{ab82}', '\u{13b2}'),
+    ('\u{ab83}', '\u{13b3}'),
+    ('\u{ab84}', '\u{13b4}'),
+    ('\u{ab85}', '\u{13b5}'),
+    ('\u{ab86}', '\u{13b6}'),
+    ('\u{ab87}', '\u{13b7}'),
+    ('\u{ab88}', '\u{13b8}'),
+    ('\u{ab89}', '\u{13b9}'),
+    ('\u{ab8a}', '\u{13ba}'),
+    ('\u{ab8b}', '\u{13bb}'),
+    ('\u{ab8c}', '\u{13bc}'),
+    ('\u{ab8d}', '\u{13bd}'),
+    ('\u{ab8e}', '\u{13be}'),
+    ('\u{ab8f}', '\u{13bf}'),
+    ('\u{ab90}', '\u{13c0}'),
+    ('\u{ab91}', '\u{13c1}'),
+    ('\u{ab92}', '\u{13c2}'),
+    ('\u{ab93}', '\u{13c3}'),
+    ('\u{ab94}', '\u{13c4}'),
+    ('\u{ab95}', '\u{13c5}'),
+    ('\u{ab96}', '\u{13c6}'),
+    ('\u{ab97}', '\u{13c7}'),
+    ('\u{ab98}', '\u{13c8}'),
+    ('\u{ab99}', '\u{13c9}'),
+    ('\u{ab9a}', '\u{13ca}'),
+    ('\u{ab9b}', '\u{13cb}'),
+    ('\u{ab9c}', '\u{13cc}'),
+    ('\u{ab9d}', '\u{13cd}'),
+    ('\u{ab9e}', '\u{13ce}'),
+    ('\u{ab9f}', '\u{13cf}'),
+    ('\u{aba0}', '\u{13d0}'),
+    ('\u{aba1}', '\u{13d1}'),
+    ('\u{aba2}', '\u{13d2}'),
+    ('\u{aba3}', '\u{13d3}'),
+    ('\u{aba4}', '\u{13d4}'),
+    ('\u{aba5}', '\u{13d5}'),
+    ('\u{aba6}', '\u{13d6}'),
+    ('\u{aba7}', '\u{13d7}'),
+    ('\u{aba8}', '\u{13d8}'),
+    ('\u{aba9}', '\u{13d9}'),
+    ('\u{abaa}', '\u{13da}'),
+    ('\u{abab}', '\u{13db}'),
+    ('\u{abac}', '\u{13dc}'),
+    ('\u{abad}', '\u{13dd}'),
+    ('\u{abae}', '\u{13de}'),
+    ('\u{abaf}', '\u{13df}'),
+    ('\u{abb0}', '\u{13e0}'),
+    ('\u{abb1}', '\u{13e1}'),
+    ('\u{abb2}', '\u{13e2}'),
+    ('\u{abb3}', '\u{13e3}'),
+    ('\u{abb4}', '\u{13e4}'),
+    ('\u{abb5}', '\u{13e5}'),
+    ('\u{abb6}', '\u{13e6}'),
+    ('\u{abb7}', '\u{13e7}'),
+    ('\u{abb8}', '\u{13e8}'),
+    ('\u{abb9}', '\u{13e9}'),
+    ('\u{abba}', '\u{13ea}'),
+    ('\u{abbb}', '\u{13eb}'),
+    ('\u{abbc}', '\u{13ec}'),
+    ('\u{abbd}', '\u{13ed}'),
+    ('\u{abbe}', '\u{13ee}'),
+    ('\u{abbf}', '\u{13ef}'),
+    ('\u{fb05}', '\u{fb06}'),
+    ('\u{ff21}', '\u{ff41}'),
+    ('\u{ff22}', '\u{ff42}'),
+    ('\u{ff23}', '\u{ff43}'),
+    ('\u{ff24}', '\u{ff44}'),
+    ('\u{ff25}', '\u{ff45}'),
+    ('\u{ff26}', '\u{ff46}'),
+    ('\u{ff27}', '\u{ff47}'),
+    ('\u{ff28}', '\u{ff48}'),
+    ('\u{ff29}', '\u{ff49}'),
+    ('\u{ff2a}', '\u{ff4a}'),
+    ('\u{ff2b}', '\u{ff4b}'),
+    ('\u{ff2c}', '\u{ff4c}'),
+    ('\u{ff2d}', '\u{ff4d}'),
+    ('\u{ff2e}', '\u{ff4e}'),
+    ('\u{ff2f}', '\u{ff4f}'),
+    ('\u{ff30}', '\u{ff50}'),
+    ('\u{ff31}', '\u{ff51}'),
+    ('\u{ff32}', '\u{ff52}'),
+    ('\u{ff33}', '\u{ff53}'),
+    ('\u{ff34}', '\u{ff54}'),
+    ('\u{ff35}', '\u{ff55}'),
+    ('\u{ff36}', '\u{ff56}'),
+    ('\u{ff37}', '\u{ff57}'),
+    ('\u{ff38}', '\u{ff58}'),
+    ('\u{ff39}', '\u{ff59}'),
+    ('\u{ff3a}', '\u{ff5a}'),
+    ('\u{10400}', '\u{10428}'),
+    ('\u{10401}', '\u{10429}'),
+    ('\u{10402}', '\u{1042a}'),
+    ('\u{10403}', '\u{1042b}'),
+    ('\u{10404}', '\u{1042c}'),
+    ('\u{10405}', '\u{1042d}'),
+    ('\u{10406}', '\u{1042e}'),
+    ('\u{10407}', '\u{1042f}'),
+    ('\u{10408}', '\u{10430}'),
+    ('\u{10409}', '\u{10431}'),
+    ('\u{1040a}', '\u{10432}'),
+    ('\u{1040b}', '\u{10433}'),
+    ('\u{1040c}', '\u{10434}'),
+    ('\u{1040d}', '\u{10435}'),
+    ('\u{1040e}', '\u{10436}'),
+    ('\u{1040f}', '\u{10437}'),
+    ('\u{10410}', '\u{10438}'),
+    ('\u{10411}', '\u{10439}'),
+    ('\u{10412}', '\u{1043a}'),
+    ('\u{10413}', '\u{1043b}'),
+    ('\u{10414}', '\u{1043c}'),
+    ('\u{10415}', '\u{1043d}'),
+    ('\u{10416}', '\u{1043e}'),
+    ('\u{10417}', '\u{1043f}'),
+    ('\u{10418}', '\u{10440}'),
+    ('\u{10419}', '\u{10441}'),
+    ('\u{1041a}', '\u{10442}'),
+    ('\u{1041b}', '\u{10443}'),
+    ('\u{1041c}', '\u{10444}'),
+    ('\u{1041d}', '\u{10445}'),
+    ('\u{1041e}', '\u{10446}'),
+    ('\u{1041f}', '\u{10447}'),
+    ('\u{10420}', '\u{10448}'),
+    ('\u{10421}', '\u{10449}'),
+    ('\u{10422}', '\u{1044a}'),
+    ('\u{10423}', '\u{1044b}'),
+    ('\u{10424}', '\u{1044c}'),
+    ('\u{10425}', '\u{1044d}'),
+    ('\u{10426}', '\u{1044e}'),
+    ('\u{10427}', '\u{1044f}'),
+    ('\u{104b0}', '\u{104d8}'),
+    ('\u{104b1}', '\u{104d9}'),
+    ('\u{104b2}', '\u{104da}'),
+    ('\u{104b3}', '\u{104db}'),
+    ('\u{104b4}', '\u{104dc}'),
+    ('\u{104b5}', '\u{104dd}'),
+    ('\u{104b6}', '\u{104de}'),
+    ('\u{104b7}', '\u{104df}'),
+    ('\u{104b8}', '\u{104e0}'),
+    ('\u{104b9}', '\u{104e1}'),
+    ('\u{104ba}', '\u{104e2}'),
+    ('\u{104bb}', '\u{104e3}'),
+    ('\u{104bc}', '\u{104e4}'),
+    ('\u{104bd}', '\u{104e5}'),
+    ('\u{104be}', '\u{104e6}'),
+    ('\u{104bf}', '\u{104e7}'),
+    ('\u{104c0}', '\u{104e8}'),
+    ('\u{104c1}', '\u{104e9}'),
+    ('\u{104c2}', '\u{104ea}'),
+    ('\u{104c3}', '\u{104eb}'),
+    ('\u{104c4}', '\u{104ec}'),
+    ('\u{104c5}', '\u{104ed}'),
+    ('\u{104c6}', '\u{104ee}'),
+    ('\u{104c7}', '\u{104ef}'),
+    ('\u{104c8}', '\u{104f0}'),
+    ('\u{104c9}', '\u{104f1}'),
+    ('\u{104ca}', '\u{104f2}'),
+    ('\u{104cb}', '\u{104f3}'),
+    ('\u{104cc}', '\u{104f4}'),
+    ('\u{104cd}', '\u{104f5}'),
+    ('\u{104ce}', '\u{104f6}'),
+    ('\u{104cf}', '\u{104f7}'),
+    ('\u{104d0}', '\u{104f8}'),
+    ('\u{104d1}', '\u{104f9}'),
+    ('\u{104d2}', '\u{104fa}'),
+    ('\u{104d3}', '\u{104fb}'),
+    ('\u{10570}', '\u{10597}'),
+    ('\u{10571}', '\u{10598}'),
+    ('\u{10572}', '\u{10599}'),
+    ('\u{10573}', '\u{1059a}'),
+    ('\u{10574}', '\u{1059b}'),
+    ('\u{10575}', '\u{1059c}'),
+    ('\u{10576}', '\u{1059d}'),
+    ('\u{10577}', '\u{1059e}'),
+    ('\u{10578}', '\u{1059f}'),
+    ('\u{10579}', '\u{105a0}'),
+    ('\u{1057a}', '\u{105a1}'),
+    ('\u{1057c}', '\u{105a3}'),
+    ('\u{1057d}', '\u{105a4}'),
+    ('\u{1057e}', '\u{105a5}'),
+    ('\u{1057f}', '\u{105a6}'),
+    ('\u{10580}', '\u{105a7}'),
+    ('\u{10581}', '\u{105a8}'),
+    ('\u{10582}', '\u{105a9}'),
+    ('\u{10583}', '\u{105aa}'),
+    ('\u{10584}', '\u{105ab}'),
+    ('\u{10585}', '\u{105ac}'),
+    ('\u{10586}', '\u{105ad}'),
+    ('\u{10587}', '\u{105ae}'),
+    ('\u{10588}', '\u{105af}'),
+    ('\u{10589}', '\u{105b0}'),
+    ('\u{1058a}', '\u{105b1}'),
+    ('\u{1058c}', '\u{105b3}'),
+    ('\u{1058d}', '\u{105b4}'),
+    ('\u{1058e}', '\u{105b5}'),
+    ('\u{1058f}', '\u{105b6}'),
+    ('\u{10590}', '\u{105b7}'),
+    ('\u{10591}', '\u{105b8}'),
+    ('\u{10592}', '\u{105b9}'),
+    ('\u{10594}', '\u{105bb}'),
+    ('\u{10595}', '\u{105bc}'),
+    ('\u{10c80}', '\u{10cc0}'),
+    ('\u{10c81}', '\u{10cc1}'),
+    ('\u{10c82}', '\u{10cc2}'),
+    ('\u{10c83}', '\u{10cc3}'),
+    ('\u{10c84}', '\u{10cc4}'),
+    ('\u{10c85}', '\u{10cc5}'),
+    ('\u{10c86}', '\u{10cc6}'),
+    ('\u{10c87}', '\u{10cc7}'),
+    ('\u{10c88}', '\u{10cc8}'),
+    ('\u{10c89}', '\u{10cc9}'),
+    ('\u{10c8a}', '\u{10cca}'),
+    ('\u{10c8b}', '\u{10ccb}'),
+    ('\u{10c8c}', '\u{10ccc}'),
+    ('\u{10c8d}', '\u{10ccd}'),
+    ('\u{10c8e}', '\u{10cce}'),
+    ('\u{10c8f}', '\u{10ccf}'),
+    ('\u{10c90}', '\u{10cd0}'),
+    ('\u{10c91}', '\u{10cd1}'),
+    ('\u{10c92}', '\u{10cd2}'),
+    ('\u{10c93}', '\u{10cd3}'),
+    ('\u{10c94}', '\u{10cd4}'),
+    ('\u{10c95}', '\u{10cd5}'),
+    ('\u{10c96}', '\u{10cd6}'),
+    ('\u{10c97}', '\u{10cd7}'),
+    ('\u{10c98}', '\u{10cd8}'),
+    ('\u{10c99}', '\u{10cd9}'),
+    ('\u{10c9a}', '\u{10cda}'),
+    ('\u{10c9b}', '\u{10cdb}'),
+    ('\u{10c9c}', '\u{10cdc}'),
+    ('\u{10c9d}', '\u{10cdd}'),
+    ('\u{10c9e}', '\u{10cde}'),
+    ('\u{10c9f}', '\u{10cdf}'),
+    ('\u{10ca0}', '\u{10ce0}'),
+    ('\u{10ca1}', '\u{10ce1}'),
+    ('\u{10ca2}', '\u{10ce2}'),
+    ('\u{10ca3}', '\u{10ce3}'),
+    ('\u{10ca4}', '\u{10ce4}'),
+    ('\u{10ca5}', '\u{10ce5}'),
+    ('\u{10ca6}', '\u{10ce6}'),
+    ('\u{10ca7}', '\u{10ce7}'),
+    ('\u{10ca8}', '\u{10ce8}'),
+    ('\u{10ca9}', '\u{10ce9}'),
+    ('\u{10caa}', '\u{10cea}'),
+    ('\u{10cab}', '\u{10ceb}'),
+    ('\u{10cac}', '\u{10cec}'),
+    ('\u{10cad}', '\u{10ced}'),
+    ('\u{10cae}', '\u{10cee}'),
+    ('\u{10caf}', '\u{10cef}'),
+    ('\u{10cb0}', '\u{10cf0}'),
+    ('\u{10cb1}', '\u{10cf1}'),
+    ('\u{10cb2}', '\u{10cf2}'),
+    ('\u{10d50}', '\u{10d70}'),
+    ('\u{10d51}', '\u{10d71}'),
+    ('\u{10d52}', '\u{10d72}'),
+    ('\u{10d53}', '\u{10d73}'),
+    ('\u{10d54}', '\u{10d74}'),
+    ('\u{10d55}', '\u{10d75}'),
+    ('\u{10d56}', '\u{10d76}'),
+    ('\u{10d57}', '\u{10d77}'),
+    ('\u{10d58}', '\u{10d78}'),
+    ('\u{10d59}', '\u{10d79}'),
+    ('\u{10d5a}', '\u{10d7a}'),
+    ('\u{10d5b}', '\u{10d7b}'),
+    ('\u{10d5c}', '\u{10d7c}'),
+    ('\u{10d5d}', '\u{10d7d}'),
+    ('\u{10d5e}', '\u{10d7e}'),
+    ('\u{10d5f}', '\u{10d7f}'),
+    ('\u{10d60}', '\u{10d80}'),
+    ('\u{10d61}', '\u{10d81}'),
+    ('\u{10d62}', '\u{10d82}'),
+    ('\u{10d63}', '\u{10d83}'),
+    ('\u{10d64}', '\u{10d84}'),
+    ('\u{10d65}', '\u{10d85}'),
+    ('\u{118a0}', '\u{118c0}'),
+    ('\u{118a1}', '\u{118c1}'),
+    ('\u{118a2}', '\u{118c2}'),
+    ('\u{118a3}', '\u{118c3}'),
+    ('\u{118a4}', '\u{118c4}'),
+    ('\u{118a5}', '\u{118c5}'),
+    ('\u{118a6}', '\u{118c6}'),
+    ('\u{118a7}', '\u{118c7}'),
+    ('\u{118a8}', '\u{118c8}'),
+    ('\u{118a9}', '\u{118c9}'),
+    ('\u{118aa}', '\u{118ca}'),
+    ('\u{118ab}', '\u{118cb}'),
+    ('\u{118ac}', '\u{118cc}'),
+    ('\u{118ad}', '\u{118cd}'),
+    ('\u{118ae}', '\u{118ce}'),
+    ('\u{118af}', '\u{118cf}'),
+    ('\u{118b0}', '\u{118d0}'),
+    ('\u{118b1}', '\u{118d1}'),
+    ('\u{118b2}', '\u{118d2}'),
+    ('\u{118b3}', '\u{118d3}'),
+    ('\u{118b4}', '\u{118d4}'),
+    ('\u{118b5}', '\u{118d5}'),
+    ('\u{118b6}', '\u{118d6}'),
+    ('\u{118b7}', '\u{118d7}'),
+    ('\u{118b8}', '\u{118d8}'),
+    ('\u{118b9}', '\u{118d9}'),
+    ('\u{118ba}', '\u{118da}'),
+    ('\u{118bb}', '\u{118db}'),
+    ('\u{118bc}', '\u{118dc}'),
+    ('\u{118bd}', '\u{118dd}'),
+    ('\u{118be}', '\u{118de}'),
+    ('\u{118bf}', '\u{118df}'),
+    ('\u{16e40}', '\u{16e60}'),
+    ('\u{16e41}', '\u{16e61}'),
+    ('\u{16e42}', '\u{16e62}'),
+    ('\u{16e43}', '\u{16e63}'),
+    ('\u{16e44}', '\u{16e64}'),
+    ('\u{16e45}', '\u{16e65}'),
+    ('\u{16e46}', '\u{16e66}'),
+    ('\u{16e47}', '\u{16e67}'),
+    ('\u{16e48}', '\u{16e68}'),
+    ('\u{16e49}', '\u{16e69}'),
+    ('\u{16e4a}', '\u{16e6a}'),
+    ('\u{16e4b}', '\u{16e6b}'),
+    ('\u{16e4c}', '\u{16e6c}'),
+    ('\u{16e4d}', '\u{16e6d}'),
+    ('\u{16e4e}', '\u{16e6e}'),
+    ('\u{16e4f}', '\u{16e6f}'),
+    ('\u{16e50}', '\u{16e70}'),
+    ('\u{16e51}', '\u{16e71}'),
+    ('\u{16e52}', '\u{16e72}'),
+    ('\u{16e53}', '\u{16e73}'),
+    ('\u{16e54}', '\u{16e74}'),
+    ('\u{16e55}', '\u{16e75}'),
+    ('\u{16e56}', '\u{16e76}'),
+    ('\u{16e57}', '\u{16e77}'),
+    ('\u{16e58}', '\u{16e78}'),
+    ('\u{16e59}', '\u{16e79}'),
+    ('\u{16e5a}', '\u{16e7a}'),
+    ('\u{16e5b}', '\u{16e7b}'),
+    ('\u{16e5c}', '\u{16e7c}'),
+    ('\u{16e5d}', '\u{16e7d}'),
+    ('\u{16e5e}', '\u{16e7e}'),
+    ('\u{16e5f}', '\u{16e7f}'),
+    ('\u{1e900}', '\u{1e922}'),
+    ('\u{1e901}', '\u{1e923}'),
+    ('\u{1e902}', '\u{1e924}'),
+    ('\u{1e903}', '\u{1e925}'),
+    ('\u{1e904}', '\u{1e926}'),
+    ('\u{1e905}', '\u{1e927}'),
+    ('\u{1e906}', '\u{1e928}'),
+    ('\u{1e907}', '\u{1e929}'),
+    ('\u{1e908}', '\u{1e92a}'),
+    ('\u{1e909}', '\u{1e92b}'),
+    ('\u{1e90a}', '\u{1e92c}'),
+    ('\u{1e90b}', '\u{1e92d}'),
+    ('\u{1e90c}', '\u{1e92e}'),
+    ('\u{1e90d}', '\u{1e92f}'),
+    ('\u{1e90e}', '\u{1e930}'),
+    ('\u{1e90f}', '\u{1e931}'),
+    ('\u{1e910}', '\u{1e932}'),
+    ('\u{1e911}', '\u{1e933}'),
+    ('\u{1e912}', '\u{1e934}'),
+    ('\u{1e913}', '\u{1e935}'),
+    ('\u{1e914}', '\u{1e936}'),
+    ('\u{1e915}', '\u{1e937}'),
+    ('\u{1e916}', '\u{1e938}'),
+    ('\u{1e917}', '\u{1e939}'),
+    ('\u{1e918}', '\u{1e93a}'),
+    ('\u{1e919}', '\u{1e93b}'),
+    ('\u{1e91a}', '\u{1e93c}'),
+    ('\u{1e91b}', '\u{1e93d}'),
+    ('\u{1e91c}', '\u{1e93e}'),
+    ('\u{1e91d}', '\u{1e93f}'),
+    ('\u{1e91e}', '\u{1e940}'),
+    ('\u{1e91f}', '\u{1e941}'),
+    ('\u{1e920}', '\u{1e942}'),
+    ('\u{1e921}', '\u{1e943}'),
+];
+
+/// Mappings with status F, sorted by code point. Unused slots are `'\0'`.
+pub(crate) static FULL: &[(char, [char; 3])] = &[
+    ('\u{00df}', ['\u{0073}', '\u{0073}', '\u{0000}']),
+    ('\u{0130}', ['\u{0069}', '\u{0307}', '\u{0000}']),
+    ('\u{0149}', ['\u{02bc}', '\u{006e}', '\u{0000}']),
+    ('\u{01f0}', ['\u{006a}', '\u{030c}', '\u{0000}']),
+    ('\u{0390}', ['\u{03b9}', '\u{0308}', '\u{0301}']),
+    ('\u{03b0}', ['\u{03c5}', '\u{0308}', '\u{0301}']),
+    ('\u{0587}', ['\u{0565}', '\u{0582}', '\u{0000}']),
+    ('\u{1e96}', ['\u{0068}', '\u{0331}', '\u{0000}']),
+    ('\u{1e97}', ['\u{0074}', '\u{0308}', '\u{0000}']),
+    ('\u{1e98}', ['\u{0077}', '\u{030a}', '\u{0000}']),
+    ('\u{1e99}', ['\u{0079}', '\u{030a}', '\u{0000}']),
+    ('\u{1e9a}', ['\u{0061}', '\u{02be}', '\u{0000}']),
+    ('\u{1e9e}', ['\u{0073}', '\u{0073}', '\u{0000}']),
+    ('\u{1f50}', ['\u{03c5}', '\u{0313}', '\u{0000}']),
+    ('\u{1f52}', ['\u{03c5}', '\u{0313}', '\u{0300}']),
+    ('\u{1f54}', ['\u{03c5}', '\u{0313}', '\u{0301}']),
+    ('\u{1f56}', ['\u{03c5}', '\u{0313}', '\u{0342}']),
+    ('\u{1f80}', ['\u{1f00}', '\u{03b9}', '\u{0000}']),
+    ('\u{1f81}', ['\u{1f01}', '\u{03b9}', '\u{0000}']),
+    ('\u{1f82}', ['\u{1f02}', '\u{03b9}', '\u{0000}']),
+    ('\u{1f83}', ['\u{1f03}', '\u{03b9}', '\u{0000}']),
+    ('\u{1f84}', ['\u{1f04}', '\u{03b9}', '\u{0000}']),
+    ('\u{1f85}', ['\u{1f05}', '\u{03b9}', '\u{0000}']),
+    ('\u{1f86}', ['\u{1f06}', '\u{03b9}', '\u{0000}']),
+    ('\u{1f87}', ['\u{1f07}', '\u{03b9}', '\u{0000}']),
+    ('\u{1f88}', ['\u{1f00}', '\u{03b9}', '\u{0000}']),
+    ('\u{1f89}', ['\u{1f01}', '\u{03b9}', '\u{0000}']),
+    ('\u{1f8a}', ['\u{1f02}', '\u{03b9}', '\u{0000}']),
+    ('\u{1f8b}', ['\u{1f03}', '\u{03b9}', '\u{0000}']),
+    ('\u{1f8c}', ['\u{1f04}', '\u{03b9}', '\u{0000}']),
+    ('\u{1f8d}', ['\u{1f05}', '\u{03b9}', '\u{0000}']),
+    ('\u{1f8e}', ['\u{1f06}', '\u{03b9}', '\u{0000}']),
+    ('\u{1f8f}', ['\u{1f07}', '\u{03b9}', '\u{0000}']),
+    ('\u{1f90}', ['\u{1f20}', '\u{03b9}', '\u{0000}']),
+    ('\u{1f91}', ['\u{1f21}', '\u{03b9}', '\u{0000}']),
+    ('\u{1f92}', ['\u{1f22}', '\u{03b9}', '\u{0000}']),
+    ('\u{1f93}', ['\u{1f23}', '\u{03b9}', '\u{0000}']),
+    ('\u{1f94}', ['\u{1f24}', '\u{03b9}', '\u{0000}']),
+    ('\u{1f95}', ['\u{1f25}', '\u{03b9}', '\u{0000}']),
+    ('\u{1f96}', ['\u{1f26}', '\u{03b9}', '\u{0000}']),
+    ('\u{1f97}', ['\u{1f27}', '\u{03b9}', '\u{0000}']),
+    ('\u{1f98}', ['\u{1f20}', '\u{03b9}', '\u{0000}']),
+    ('\u{1f99}', ['\u{1f21}', '\u{03b9}', '\u{0000}']),
+    ('\u{1f9a}', ['\u{1f22}', '\u{03b9}', '\u{0000}']),
+    ('\u{1f9b}', ['\u{1f23}', '\u{03b9}', '\u{0000}']),
+    ('\u{1f9c}', ['\u{1f24}', '\u{03b9}', '\u{0000}']),
+    ('\u{1f9d}', ['\u{1f25}', '\u{03b9}', '\u{0000}']),
+    ('\u{1f9e}', ['\u{1f26}', '\u{03b9}', '\u{0000}']),
+    ('\u{1f9f}', ['\u{1f27}', '\u{03b9}', '\u{0000}']),
+    ('\u{1fa0}', ['\u{1f60}', '\u{03b9}', '\u{0000}']),
+    ('\u{1fa1}', ['\u{1f61}', '\u{03b9}', '\u{0000}']),
+    ('\u{1fa2}', ['\u{1f62}', '\u{03b9}', '\u{0000}']),
+    ('\u{1fa3}', ['\u{1f63}', '\u{03b9}', '\u{0000}']),
+    ('\u{1fa4}', ['\u{1f64}', '\u{03b9}', '\u{0000}']),
+    ('\u{1fa5}', ['\u{1f65}', '\u{03b9}', '\u{0000}']),
+    ('\u{1fa6}', ['\u{1f66}', '\u{03b9}', '\u{0000}']),
+    ('\u{1fa7}', ['\u{1f67}', '\u{03b9}', '\u{0000}']),
+    ('\u{1fa8}', ['\u{1f60}', '\u{03b9}', '\u{0000}']),
+    ('\u{1fa9}', ['\u{1f61}', '\u{03b9}', '\u{0000}']),
+    ('\u{1faa}', ['\u{1f62}', '\u{03b9}', '\u{0000}']),
+    ('\u{1fab}', ['\u{1f63}', '\u{03b9}', '\u{0000}']),
+    ('\u{1fac}', ['\u{1f64}', '\u{03b9}', '\u{0000}']),
+    ('\u{1fad}', ['\u{1f65}', '\u{03b9}', '\u{0000}']),
+    ('\u{1fae}', ['\u{1f66}', '\u{03b9}', '\u{0000}']),
+    ('\u{1faf}', ['\u{1f67}', '\u{03b9}', '\u{0000}']),
+    ('\u{1fb2}', ['\u{1f70}', '\u{03b9}', '\u{0000}']),
+    ('\u{1fb3}', ['\u{03b1}', '\u{03b9}', '\u{0000}']),
+    ('\u{1fb4}', ['\u{03ac}', '\u{03b9}', '\u{0000}']),
+    ('\u{1fb6}', ['\u{03b1}', '\u{0342}', '\u{0000}']),
+    ('\u{1fb7}', ['\u{03b1}', '\u{0342}', '\u{03b9}']),
+    ('\u{1fbc}', ['\u{03b1}', '\u{03b9}', '\u{0000}']),
+    ('\u{1fc2}', ['\u{1f74}', '\u{03b9}', '\u{0000}']),
+    ('\u{1fc3}', ['\u{03b7}', '\u{03b9}', '\u{0000}']),
+    ('\u{1fc4}', ['\u{03ae}', '\u{03b9}', '\u{0000}']),
+    ('\u{1fc6}', ['\u{03b7}', '\u{0342}', '\u{0000}']),
+    ('\u{1fc7}', ['\u{03b7}', '\u{0342}', '\u{03b9}']),
+    ('\u{1fcc}', ['\u{03b7}', '\u{03b9}', '\u{0000}']),
+    ('\u{1fd2}', ['\u{03b9}', '\u{0308}', '\u{0300}']),
+    ('\u{1fd3}', ['\u{03b9}', '\u{0308}', '\u{0301}']),
+    ('\u{1fd6}', ['\u{03b9}', '\u{0342}', '\u{0000}']),
+    ('\u{1fd7}', ['\u{03b9}', '\u{0308}', '\u{0342}']),
+    ('\u{1fe2}', ['\u{03c5}', '\u{0308}', '\u{0300}']),
+    ('\u{1fe3}', ['\u{03c5}', '\u{0308}', '\u{0301}']),
+    ('\u{1fe4}', ['\u{03c1}', '\u{0313}', '\u{0000}']),
+    ('\u{1fe6}', ['\u{03c5}', '\u{0342}', '\u{0000}']),
+    ('\u{1fe7}', ['\u{03c5}', '\u{0308}', '\u{0342}']),
+    ('\u{1ff2}', ['\u{1f7c}', '\u{03b9}', '\u{0000}']),
+    ('\u{1ff3}', ['\u{03c9}', '\u{03b9}', '\u{0000}']),
+    ('\u{1ff4}', ['\u{03ce}', '\u{03b9}', '\u{0000}']),
+    ('\u{1ff6}', ['\u{03c9}', '\u{0342}', '\u{0000}']),
+    ('\u{1ff7}', ['\u{03c9}', '\u{0342}', '\u{03b9}']),
+    ('\u{1ffc}', ['\u{03c9}', '\u{03b9}', '\u{0000}']),
+    ('\u{fb00}', ['\u{0066}', '\u{0066}', '\u{0000}']),
+    ('\u{fb01}', ['\u{0066}', '\u{0069}', '\u{0000}']),
+    ('\u{fb02}', ['\u{0066}', '\u{006c}', '\u{0000}']),
+    ('\u{fb03}', ['\u{0066}', '\u{0066}', '\u{0069}']),
+    ('\u{fb04}', ['\u{0066}', '\u{0066}', '\u{006c}']),
+    ('\u{fb05}', ['\u{0073}', '\u{0074}', '\u{0000}']),
+    ('\u{fb06}', ['\u{0073}', '\u{0074}', '\u{0000}']),
+    ('\u{fb13}', ['\u{0574}', '\u{0576}', '\u{0000}']),
+    ('\u{fb14}', ['\u{0574}', '\u{0565}', '\u{0000}']),
+    ('\u{fb15}', ['\u{0574}', '\u{056b}', '\u{0000}']),
+    ('\u{fb16}', ['\u{057e}', '\u{0576}', '\u{0000}']),
+    ('\u{fb17}', ['\u{0574}', '\u{056d}', '\u{0000}']),
+];
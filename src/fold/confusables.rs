@@ -0,0 +1,112 @@
+use super::{FoldPolicy, FullUnicode, InvalidKey};
+use std::marker::PhantomData;
+use unicode_security::skeleton;
+
+/// Confusable-insensitive matching for names that must not be spoofed, like account
+/// or tenant names.
+///
+/// Keys are compared by the UTS #39 skeleton of the key, folded with `P` and then
+/// skeletonized and folded again, since folding can turn a letter into a lookalike of
+/// another one and the skeletons use some uppercase prototypes. Visually confusable
+/// keys like `paypal` and `pаypal` with a Cyrillic `а` are the same key, so inserting
+/// one of them into a set that already contains the other returns `false`. The
+/// confusables table of Unicode 16.0.0 is compiled into the binary.
+///
+/// Since the skeleton comes before case folding, an uppercase `I` is a lookalike of
+/// `l` like in `paypaI`, not the uppercase form of `i`: `IBM` and `ibm` are different
+/// keys, while `IBM` and `lbm` are the same key.
+///
+/// Skeletons are meant for detecting collisions, not for display. Computing them
+/// allocates, so this policy is slower than the others.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ConfusableInsensitive<P = FullUnicode>(PhantomData<P>);
+
+impl<P: FoldPolicy> FoldPolicy for ConfusableInsensitive<P> {
+    fn fold<I: Iterator<Item = char>>(chars: I) -> impl Iterator<Item = char> {
+        let input: String = chars.collect();
+        let folded: String = P::fold(skeleton(&input)).collect();
+        let skeleton: Vec<char> = skeleton(&folded).collect();
+        P::fold(skeleton.into_iter())
+    }
+
+    fn validate(key: &str) -> Result<(), InvalidKey> {
+        P::validate(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ConfusableInsensitive;
+    use crate::fold::{Ascii, FoldPolicy, Identifier, InvalidKey};
+
+    fn fold<P: FoldPolicy>(s: &str) -> String {
+        P::fold(s.chars()).collect()
+    }
+
+    #[test]
+    fn confusables() {
+        let expected = fold::<ConfusableInsensitive>("paypal");
+        for s in [
+            "PayPal",
+            "p\u{0430}ypal",
+            "\u{0440}\u{0430}\u{0443}\u{0440}\u{0430}l",
+            "paypa1",
+            "PAYPA1",
+        ] {
+            assert_eq!(fold::<ConfusableInsensitive>(s), expected, "{:?}", s);
+        }
+        assert_ne!(fold::<ConfusableInsensitive>("paypat"), expected);
+    }
+
+    #[test]
+    fn uppercase_i_is_confusable_with_l() {
+        let expected = fold::<ConfusableInsensitive>("paypal");
+        assert_eq!(fold::<ConfusableInsensitive>("paypaI"), expected);
+        assert_eq!(fold::<ConfusableInsensitive>("PAYPAI"), expected);
+        assert_ne!(fold::<ConfusableInsensitive>("paypai"), expected);
+        assert_eq!(
+            fold::<ConfusableInsensitive>("IBM"),
+            fold::<ConfusableInsensitive>("lbm")
+        );
+    }
+
+    #[test]
+    fn multi_char_prototypes() {
+        assert_eq!(
+            fold::<ConfusableInsensitive>("modern"),
+            fold::<ConfusableInsensitive>("rnodern")
+        );
+        assert_eq!(
+            fold::<ConfusableInsensitive>("G00GLE"),
+            fold::<ConfusableInsensitive>("google")
+        );
+    }
+
+    #[test]
+    fn inner_policy() {
+        assert_eq!(
+            fold::<ConfusableInsensitive<Ascii>>("\u{0430}"),
+            fold::<ConfusableInsensitive<Ascii>>("A")
+        );
+        assert_ne!(
+            fold::<ConfusableInsensitive<Ascii>>("Д"),
+            fold::<ConfusableInsensitive<Ascii>>("д")
+        );
+        assert_eq!(
+            fold::<ConfusableInsensitive>("Д"),
+            fold::<ConfusableInsensitive>("д")
+        );
+    }
+
+    #[test]
+    fn validate_uses_inner_policy() {
+        assert_eq!(
+            ConfusableInsensitive::<Identifier>::validate("paypal"),
+            Ok(())
+        );
+        assert_eq!(
+            ConfusableInsensitive::<Identifier>::validate("pay pal"),
+            Err(InvalidKey::at(3, ' '))
+        );
+    }
+}
@@ -0,0 +1,698 @@
+use super::FoldPolicy;
+
+/// Phonetic matching with Double Metaphone, for names that sound alike.
+///
+/// Keys are compared by their primary Double Metaphone code of at most four chars,
+/// e.g. `Smith`, `Smyth` and `SMITHE` are all `SM0`. Double Metaphone handles many
+/// spellings of non-English names better than [`Soundex`](super::Soundex). Use
+/// [`double_metaphone`] to get the alternate code as well.
+///
+/// Since many keys share a code, use a collection of `Vec<V>` values to keep all of
+/// them, e.g. `UniCaseIndexMap<Vec<V>, String, DoubleMetaphone>`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DoubleMetaphone;
+
+impl FoldPolicy for DoubleMetaphone {
+    fn fold<I: Iterator<Item = char>>(chars: I) -> impl Iterator<Item = char> {
+        Encoder::new(chars).encode().0.into_iter()
+    }
+}
+
+/// Returns the primary and the alternate Double Metaphone codes of `s`.
+pub fn double_metaphone(s: &str) -> (String, String) {
+    let (primary, alternate) = Encoder::new(s.chars()).encode();
+    (
+        primary.into_iter().collect(),
+        alternate.into_iter().collect(),
+    )
+}
+
+const MAX_LEN: usize = 4;
+
+/// The Double Metaphone algorithm by Lawrence Philips, following the rules and the
+/// structure of the implementation in Apache Commons Codec.
+struct Encoder {
+    value: Vec<char>,
+    slavo_germanic: bool,
+    primary: Vec<char>,
+    alternate: Vec<char>,
+}
+
+impl Encoder {
+    fn new<I: Iterator<Item = char>>(chars: I) -> Self {
+        let value: Vec<char> = chars.flat_map(char::to_uppercase).collect();
+        let start = value.iter().take_while(|c| c.is_whitespace()).count();
+        let end = value.len()
+            - value[start..]
+                .iter()
+                .rev()
+                .take_while(|c| c.is_whitespace())
+                .count();
+        let value = value[start..end].to_vec();
+        let slavo_germanic = value.contains(&'W')
+            || value.contains(&'K')
+            || value.windows(2).any(|w| w == ['C', 'Z'])
+            || value.windows(4).any(|w| w == ['W', 'I', 'T', 'Z']);
+
+        Self {
+            value,
+            slavo_germanic,
+            primary: Vec::with_capacity(MAX_LEN),
+            alternate: Vec::with_capacity(MAX_LEN),
+        }
+    }
+
+    fn encode(mut self) -> (Vec<char>, Vec<char>) {
+        let mut i = if self.is_silent_start() { 1 } else { 0 };
+        while !self.is_complete() && i < self.len() {
+            i = match self.at(i) {
+                'A' | 'E' | 'I' | 'O' | 'U' | 'Y' => {
+                    if i == 0 {
+                        self.add('A');
+                    }
+                    i + 1
+                }
+                'B' => {
+                    self.add('P');
+                    self.skip_double(i, 'B')
+                }
+                'Ç' => {
+                    self.add('S');
+                    i + 1
+                }
+                'C' => self.c(i),
+                'D' => self.d(i),
+                'F' => {
+                    self.add('F');
+                    self.skip_double(i, 'F')
+                }
+                'G' => self.g(i),
+                'H' => self.h(i),
+                'J' => self.j(i),
+                'K' => {
+                    self.add('K');
+                    self.skip_double(i, 'K')
+                }
+                'L' => self.l(i),
+                'M' => {
+                    self.add('M');
+                    if self.condition_m0(i) {
+                        i + 2
+                    } else {
+                        i + 1
+                    }
+                }
+                'N' => {
+                    self.add('N');
+                    self.skip_double(i, 'N')
+                }
+                'Ñ' => {
+                    self.add('N');
+                    i + 1
+                }
+                'P' => self.p(i),
+                'Q' => {
+                    self.add('K');
+                    self.skip_double(i, 'Q')
+                }
+                'R' => self.r(i),
+                'S' => self.s(i),
+                'T' => self.t(i),
+                'V' => {
+                    self.add('F');
+                    self.skip_double(i, 'V')
+                }
+                'W' => self.w(i),
+                'X' => self.x(i),
+                'Z' => self.z(i),
+                _ => i + 1,
+            };
+        }
+        (self.primary, self.alternate)
+    }
+
+    fn c(&mut self, i: isize) -> isize {
+        if self.condition_c0(i) {
+            self.add('K');
+            i + 2
+        } else if i == 0 && self.contains(i, &["CAESAR"]) {
+            self.add('S');
+            i + 2
+        } else if self.contains(i, &["CH"]) {
+            self.ch(i)
+        } else if self.contains(i, &["CZ"]) && !self.contains(i - 2, &["WICZ"]) {
+            self.add2('S', 'X');
+            i + 2
+        } else if self.contains(i + 1, &["CIA"]) {
+            self.add('X');
+            i + 3
+        } else if self.contains(i, &["CC"]) && !(i == 1 && self.at(0) == 'M') {
+            self.cc(i)
+        } else if self.contains(i, &["CK", "CG", "CQ"]) {
+            self.add('K');
+            i + 2
+        } else if self.contains(i, &["CI", "CE", "CY"]) {
+            if self.contains(i, &["CIO", "CIE", "CIA"]) {
+                self.add2('S', 'X');
+            } else {
+                self.add('S');
+            }
+            i + 2
+        } else {
+            self.add('K');
+            if self.contains(i + 1, &[" C", " Q", " G"]) {
+                i + 3
+            } else if self.contains(i + 1, &["C", "K", "Q"]) && !self.contains(i + 1, &["CE", "CI"])
+            {
+                i + 2
+            } else {
+                i + 1
+            }
+        }
+    }
+
+    fn cc(&mut self, i: isize) -> isize {
+        if self.contains(i + 2, &["I", "E", "H"]) && !self.contains(i + 2, &["HU"]) {
+            if (i == 1 && self.at(i - 1) == 'A') || self.contains(i - 1, &["UCCEE", "UCCES"]) {
+                self.add_str("KS", "KS");
+            } else {
+                self.add('X');
+            }
+            i + 3
+        } else {
+            self.add('K');
+            i + 2
+        }
+    }
+
+    fn ch(&mut self, i: isize) -> isize {
+        if i > 0 && self.contains(i, &["CHAE"]) {
+            self.add2('K', 'X');
+        } else if self.condition_ch0(i) || self.condition_ch1(i) {
+            self.add('K');
+        } else if i > 0 {
+            if self.contains(0, &["MC"]) {
+                self.add('K');
+            } else {
+                self.add2('X', 'K');
+            }
+        } else {
+            self.add('X');
+        }
+        i + 2
+    }
+
+    fn d(&mut self, i: isize) -> isize {
+        if self.contains(i, &["DG"]) {
+            if self.contains(i + 2, &["I", "E", "Y"]) {
+                self.add('J');
+                i + 3
+            } else {
+                self.add_str("TK", "TK");
+                i + 2
+            }
+        } else if self.contains(i, &["DT", "DD"]) {
+            self.add('T');
+            i + 2
+        } else {
+            self.add('T');
+            i + 1
+        }
+    }
+
+    fn g(&mut self, i: isize) -> isize {
+        if self.at(i + 1) == 'H' {
+            self.gh(i)
+        } else if self.at(i + 1) == 'N' {
+            if i == 1 && is_vowel(self.at(0)) && !self.slavo_germanic {
+                self.add_str("KN", "N");
+            } else if !self.contains(i + 2, &["EY"])
+                && self.at(i + 1) != 'Y'
+                && !self.slavo_germanic
+            {
+                self.add_str("N", "KN");
+            } else {
+                self.add_str("KN", "KN");
+            }
+            i + 2
+        } else if self.contains(i + 1, &["LI"]) && !self.slavo_germanic {
+            self.add_str("KL", "L");
+            i + 2
+        } else if self.condition_g0(i) {
+            self.add2('K', 'J');
+            i + 2
+        } else if self.contains(i + 1, &["E", "I", "Y"]) || self.contains(i - 1, &["AGGI", "OGGI"])
+        {
+            if self.contains(0, &["VAN ", "VON "])
+                || self.contains(0, &["SCH"])
+                || self.contains(i + 1, &["ET"])
+            {
+                self.add('K');
+            } else if self.contains(i + 1, &["IER"]) {
+                self.add('J');
+            } else {
+                self.add2('J', 'K');
+            }
+            i + 2
+        } else if self.at(i + 1) == 'G' {
+            self.add('K');
+            i + 2
+        } else {
+            self.add('K');
+            i + 1
+        }
+    }
+
+    fn gh(&mut self, i: isize) -> isize {
+        if i > 0 && !is_vowel(self.at(i - 1)) {
+            self.add('K');
+        } else if i == 0 {
+            if self.at(i + 2) == 'I' {
+                self.add('J');
+            } else {
+                self.add('K');
+            }
+        } else if (i > 1 && self.contains(i - 2, &["B", "H", "D"]))
+            || (i > 2 && self.contains(i - 3, &["B", "H", "D"]))
+            || (i > 3 && self.contains(i - 4, &["B", "H"]))
+        {
+            // Silent, e.g. "hugh", "bough" and "broughton".
+        } else if i > 2 && self.at(i - 1) == 'U' && self.contains(i - 3, &["C", "G", "L", "R", "T"])
+        {
+            self.add('F');
+        } else if i > 0 && self.at(i - 1) != 'I' {
+            self.add('K');
+        }
+        i + 2
+    }
+
+    fn h(&mut self, i: isize) -> isize {
+        if (i == 0 || is_vowel(self.at(i - 1))) && is_vowel(self.at(i + 1)) {
+            self.add('H');
+            i + 2
+        } else {
+            i + 1
+        }
+    }
+
+    fn j(&mut self, i: isize) -> isize {
+        if self.contains(i, &["JOSE"]) || self.contains(0, &["SAN "]) {
+            if (i == 0 && self.at(i + 4) == ' ') || self.len() == 4 || self.contains(0, &["SAN "]) {
+                self.add('H');
+            } else {
+                self.add2('J', 'H');
+            }
+            return i + 1;
+        }
+
+        if i == 0 {
+            self.add2('J', 'A');
+        } else if is_vowel(self.at(i - 1))
+            && !self.slavo_germanic
+            && (self.at(i + 1) == 'A' || self.at(i + 1) == 'O')
+        {
+            self.add2('J', 'H');
+        } else if i == self.len() - 1 {
+            self.add2('J', ' ');
+        } else if !self.contains(i + 1, &["L", "T", "K", "S", "N", "M", "B", "Z"])
+            && !self.contains(i - 1, &["S", "K", "L"])
+        {
+            self.add('J');
+        }
+        self.skip_double(i, 'J')
+    }
+
+    fn l(&mut self, i: isize) -> isize {
+        if self.at(i + 1) == 'L' {
+            if self.condition_l0(i) {
+                self.add_primary("L");
+            } else {
+                self.add('L');
+            }
+            i + 2
+        } else {
+            self.add('L');
+            i + 1
+        }
+    }
+
+    fn p(&mut self, i: isize) -> isize {
+        if self.at(i + 1) == 'H' {
+            self.add('F');
+            i + 2
+        } else {
+            self.add('P');
+            if self.contains(i + 1, &["P", "B"]) {
+                i + 2
+            } else {
+                i + 1
+            }
+        }
+    }
+
+    fn r(&mut self, i: isize) -> isize {
+        if i == self.len() - 1
+            && !self.slavo_germanic
+            && self.contains(i - 2, &["IE"])
+            && !self.contains(i - 4, &["ME", "MA"])
+        {
+            self.add_alternate("R");
+        } else {
+            self.add('R');
+        }
+        self.skip_double(i, 'R')
+    }
+
+    fn s(&mut self, i: isize) -> isize {
+        if self.contains(i - 1, &["ISL", "YSL"]) {
+            i + 1
+        } else if i == 0 && self.contains(i, &["SUGAR"]) {
+            self.add2('X', 'S');
+            i + 1
+        } else if self.contains(i, &["SH"]) {
+            if self.contains(i + 1, &["HEIM", "HOEK", "HOLM", "HOLZ"]) {
+                self.add('S');
+            } else {
+                self.add('X');
+            }
+            i + 2
+        } else if self.contains(i, &["SIO", "SIA"]) || self.contains(i, &["SIAN"]) {
+            if self.slavo_germanic {
+                self.add('S');
+            } else {
+                self.add2('S', 'X');
+            }
+            i + 3
+        } else if (i == 0 && self.contains(i + 1, &["M", "N", "L", "W"]))
+            || self.contains(i + 1, &["Z"])
+        {
+            self.add2('S', 'X');
+            if self.contains(i + 1, &["Z"]) {
+                i + 2
+            } else {
+                i + 1
+            }
+        } else if self.contains(i, &["SC"]) {
+            self.sc(i)
+        } else {
+            if i == self.len() - 1 && self.contains(i - 2, &["AI", "OI"]) {
+                self.add_alternate("S");
+            } else {
+                self.add('S');
+            }
+            if self.contains(i + 1, &["S", "Z"]) {
+                i + 2
+            } else {
+                i + 1
+            }
+        }
+    }
+
+    fn sc(&mut self, i: isize) -> isize {
+        if self.at(i + 2) == 'H' {
+            if self.contains(i + 3, &["OO", "ER", "EN", "UY", "ED", "EM"]) {
+                if self.contains(i + 3, &["ER", "EN"]) {
+                    self.add_str("X", "SK");
+                } else {
+                    self.add_str("SK", "SK");
+                }
+            } else if i == 0 && !is_vowel(self.at(3)) && self.at(3) != 'W' {
+                self.add2('X', 'S');
+            } else {
+                self.add('X');
+            }
+        } else if self.contains(i + 2, &["I", "E", "Y"]) {
+            self.add('S');
+        } else {
+            self.add_str("SK", "SK");
+        }
+        i + 3
+    }
+
+    fn t(&mut self, i: isize) -> isize {
+        if self.contains(i, &["TION"]) || self.contains(i, &["TIA", "TCH"]) {
+            self.add('X');
+            i + 3
+        } else if self.contains(i, &["TH"]) || self.contains(i, &["TTH"]) {
+            if self.contains(i + 2, &["OM", "AM"])
+                || self.contains(0, &["VAN ", "VON "])
+                || self.contains(0, &["SCH"])
+            {
+                self.add('T');
+            } else {
+                self.add2('0', 'T');
+            }
+            i + 2
+        } else {
+            self.add('T');
+            if self.contains(i + 1, &["T", "D"]) {
+                i + 2
+            } else {
+                i + 1
+            }
+        }
+    }
+
+    fn w(&mut self, i: isize) -> isize {
+        if self.contains(i, &["WR"]) {
+            self.add('R');
+            i + 2
+        } else if i == 0 && (is_vowel(self.at(i + 1)) || self.contains(i, &["WH"])) {
+            if is_vowel(self.at(i + 1)) {
+                self.add2('A', 'F');
+            } else {
+                self.add('A');
+            }
+            i + 1
+        } else if (i == self.len() - 1 && is_vowel(self.at(i - 1)))
+            || self.contains(i - 1, &["EWSKI", "EWSKY", "OWSKI", "OWSKY"])
+            || self.contains(0, &["SCH"])
+        {
+            self.add_alternate("F");
+            i + 1
+        } else if self.contains(i, &["WICZ", "WITZ"]) {
+            self.add_str("TS", "FX");
+            i + 4
+        } else {
+            i + 1
+        }
+    }
+
+    fn x(&mut self, i: isize) -> isize {
+        if i == 0 {
+            self.add('S');
+            return i + 1;
+        }
+
+        // Silent at the end of French words, e.g. "breaux".
+        if !(i == self.len() - 1
+            && (self.contains(i - 3, &["IAU", "EAU"]) || self.contains(i - 2, &["AU", "OU"])))
+        {
+            self.add_str("KS", "KS");
+        }
+        if self.contains(i + 1, &["C", "X"]) {
+            i + 2
+        } else {
+            i + 1
+        }
+    }
+
+    fn z(&mut self, i: isize) -> isize {
+        if self.at(i + 1) == 'H' {
+            self.add('J');
+            return i + 2;
+        }
+
+        if self.contains(i + 1, &["ZO", "ZI", "ZA"])
+            || (self.slavo_germanic && i > 0 && self.at(i - 1) != 'T')
+        {
+            self.add_str("S", "TS");
+        } else {
+            self.add('S');
+        }
+        self.skip_double(i, 'Z')
+    }
+
+    fn condition_c0(&self, i: isize) -> bool {
+        if self.contains(i, &["CHIA"]) {
+            true
+        } else if i <= 1 || is_vowel(self.at(i - 2)) || !self.contains(i - 1, &["ACH"]) {
+            false
+        } else {
+            let c = self.at(i + 2);
+            (c != 'I' && c != 'E') || self.contains(i - 2, &["BACHER", "MACHER"])
+        }
+    }
+
+    fn condition_g0(&self, i: isize) -> bool {
+        if i == 0 {
+            let soft = [
+                "ES", "EP", "EB", "EL", "EY", "IB", "IL", "IN", "IE", "EI", "ER",
+            ];
+            if self.at(i + 1) == 'Y' || self.contains(i + 1, &soft) {
+                return true;
+            }
+        }
+
+        (self.contains(i + 1, &["ER"]) || self.at(i + 1) == 'Y')
+            && !self.contains(0, &["DANGER", "RANGER", "MANGER"])
+            && !self.contains(i - 1, &["E", "I"])
+            && !self.contains(i - 1, &["RGY", "OGY"])
+    }
+
+    fn condition_ch0(&self, i: isize) -> bool {
+        i == 0
+            && (self.contains(i + 1, &["HARAC", "HARIS"])
+                || self.contains(i + 1, &["HOR", "HYM", "HIA", "HEM"]))
+            && !self.contains(0, &["CHORE"])
+    }
+
+    fn condition_ch1(&self, i: isize) -> bool {
+        self.contains(0, &["VAN ", "VON "])
+            || self.contains(0, &["SCH"])
+            || self.contains(i - 2, &["ORCHES", "ARCHIT", "ORCHID"])
+            || self.contains(i + 2, &["T", "S"])
+            || ((self.contains(i - 1, &["A", "O", "U", "E"]) || i == 0)
+                && (self.contains(i + 2, &["L", "R", "N", "M", "B", "H", "F", "V", "W", " "])
+                    || i + 1 == self.len() - 1))
+    }
+
+    fn condition_l0(&self, i: isize) -> bool {
+        let len = self.len();
+        (i == len - 3 && self.contains(i - 1, &["ILLO", "ILLA", "ALLE"]))
+            || ((self.contains(len - 2, &["AS", "OS"]) || self.contains(len - 1, &["A", "O"]))
+                && self.contains(i - 1, &["ALLE"]))
+    }
+
+    fn condition_m0(&self, i: isize) -> bool {
+        self.at(i + 1) == 'M'
+            || (self.contains(i - 1, &["UMB"])
+                && (i + 1 == self.len() - 1 || self.contains(i + 2, &["ER"])))
+    }
+
+    fn is_silent_start(&self) -> bool {
+        self.contains(0, &["GN", "KN", "PN", "WR", "PS"])
+    }
+
+    fn is_complete(&self) -> bool {
+        self.primary.len() >= MAX_LEN && self.alternate.len() >= MAX_LEN
+    }
+
+    fn len(&self) -> isize {
+        self.value.len() as isize
+    }
+
+    /// Returns the char at `i`, or NUL if `i` is out of bounds.
+    fn at(&self, i: isize) -> char {
+        usize::try_from(i)
+            .ok()
+            .and_then(|i| self.value.get(i))
+            .copied()
+            .unwrap_or('\0')
+    }
+
+    /// Returns true if the chars starting at `i` are one of `candidates`, which all
+    /// have the same length.
+    fn contains(&self, i: isize, candidates: &[&str]) -> bool {
+        let len = candidates[0].len() as isize;
+        if i < 0 || i + len > self.len() {
+            return false;
+        }
+
+        let target = &self.value[i as usize..(i + len) as usize];
+        candidates
+            .iter()
+            .any(|candidate| candidate.chars().eq(target.iter().copied()))
+    }
+
+    fn skip_double(&self, i: isize, c: char) -> isize {
+        if self.at(i + 1) == c {
+            i + 2
+        } else {
+            i + 1
+        }
+    }
+
+    fn add(&mut self, c: char) {
+        self.add2(c, c);
+    }
+
+    fn add2(&mut self, primary: char, alternate: char) {
+        push(&mut self.primary, primary.encode_utf8(&mut [0; 4]));
+        push(&mut self.alternate, alternate.encode_utf8(&mut [0; 4]));
+    }
+
+    fn add_str(&mut self, primary: &str, alternate: &str) {
+        push(&mut self.primary, primary);
+        push(&mut self.alternate, alternate);
+    }
+
+    fn add_primary(&mut self, primary: &str) {
+        push(&mut self.primary, primary);
+    }
+
+    fn add_alternate(&mut self, alternate: &str) {
+        push(&mut self.alternate, alternate);
+    }
+}
+
+fn push(code: &mut Vec<char>, s: &str) {
+    let room = MAX_LEN.saturating_sub(code.len());
+    code.extend(s.chars().take(room));
+}
+
+fn is_vowel(c: char) -> bool {
+    matches!(c, 'A' | 'E' | 'I' | 'O' | 'U' | 'Y')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{double_metaphone, DoubleMetaphone};
+    use crate::fold::FoldPolicy;
+
+    fn codes(s: &str) -> (String, String) {
+        double_metaphone(s)
+    }
+
+    #[test]
+    fn primary_and_alternate_codes() {
+        for (name, primary, alternate) in [
+            ("Smith", "SM0", "XMT"),
+            ("Smyth", "SM0", "XMT"),
+            ("Schmidt", "XMT", "SMT"),
+            ("Cabrillo", "KPRL", "KPR"),
+            ("Arnow", "ARN", "ARNF"),
+            ("Thomas", "TMS", "TMS"),
+            ("Jose", "HS", "HS"),
+            ("Knight", "NT", "NT"),
+            ("Ghislane", "JLN", "JLN"),
+            ("Thumb", "0M", "TM"),
+            ("Xavier", "SF", "SFR"),
+        ] {
+            assert_eq!(
+                codes(name),
+                (primary.into(), alternate.into()),
+                "{:?}",
+                name
+            );
+        }
+        assert_eq!(codes(""), (String::new(), String::new()));
+        assert_eq!(codes("  "), (String::new(), String::new()));
+    }
+
+    #[test]
+    fn codes_are_truncated() {
+        assert_eq!(codes("Washington"), ("AXNK".into(), "FXNK".into()));
+        assert_eq!(codes("Jankelowicz").0.len(), 4);
+    }
+
+    #[test]
+    fn policy() {
+        let fold = |s: &str| DoubleMetaphone::fold(s.chars()).collect::<String>();
+        assert_eq!(fold("Smith"), "SM0");
+        assert_eq!(fold("smyth"), "SM0");
+        assert_eq!(fold("SMITHE"), "SM0");
+        assert_eq!(fold("Katherine"), fold("Catherine"));
+        assert_ne!(fold("Smith"), fold("Jones"));
+    }
+}
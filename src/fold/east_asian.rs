@@ -0,0 +1,194 @@
+use super::{FoldPolicy, Folded, FullUnicode, InvalidKey};
+use std::marker::PhantomData;
+use unicode_normalization::char::{decompose_canonical, decompose_compatible};
+
+/// East Asian width-insensitive matching.
+///
+/// Fullwidth and halfwidth forms (U+FF00–U+FFEF and the ideographic space U+3000)
+/// are mapped to their normal width before folding the key with `P`, so `ＡＢＣ１２３`
+/// matches `abc123` and halfwidth `ｶﾞｲﾄﾞ` matches `ガイド`. Kana with a voiced or
+/// semi-voiced sound mark are decomposed, since the halfwidth forms spell the mark
+/// as a separate char.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WidthInsensitive<P = FullUnicode>(PhantomData<P>);
+
+impl<P: FoldPolicy> FoldPolicy for WidthInsensitive<P> {
+    fn fold<I: Iterator<Item = char>>(chars: I) -> impl Iterator<Item = char> {
+        P::fold(chars.flat_map(width_fold))
+    }
+
+    fn validate(key: &str) -> Result<(), InvalidKey> {
+        P::validate(key)
+    }
+}
+
+/// Hiragana-katakana-insensitive matching.
+///
+/// Hiragana are mapped to the corresponding katakana before folding the key with `P`,
+/// so `かたかな` matches `カタカナ`. Combine it with [`WidthInsensitive`] to also match
+/// halfwidth katakana, e.g. `KanaInsensitive<WidthInsensitive>`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct KanaInsensitive<P = FullUnicode>(PhantomData<P>);
+
+impl<P: FoldPolicy> FoldPolicy for KanaInsensitive<P> {
+    fn fold<I: Iterator<Item = char>>(chars: I) -> impl Iterator<Item = char> {
+        P::fold(chars.map(to_katakana))
+    }
+
+    fn validate(key: &str) -> Result<(), InvalidKey> {
+        P::validate(key)
+    }
+}
+
+fn width_fold(c: char) -> Folded {
+    let c = if is_width_variant(c) {
+        normal_width(c)
+    } else {
+        c
+    };
+
+    let mut folded = Folded::one(c);
+    if is_kana(c) {
+        folded.len = 0;
+        decompose_canonical(c, |d| {
+            folded.chars[folded.len] = d;
+            folded.len += 1;
+        });
+    }
+    folded
+}
+
+/// Chars with a `<wide>` or `<narrow>` compatibility decomposition.
+fn is_width_variant(c: char) -> bool {
+    matches!(c, '\u{3000}' | '\u{ff01}'..='\u{ffee}')
+}
+
+/// Returns the single char of the `<wide>` or `<narrow>` decomposition of a width
+/// variant, without the further compatibility decompositions of that char.
+fn normal_width(c: char) -> char {
+    let offset = match c {
+        '\u{3000}' => return ' ',
+        '\u{ff01}'..='\u{ff5e}' => 0xfee0,
+        // Halfwidth Hangul, whose compatibility jamo decompose to conjoining jamo.
+        '\u{ffa0}' => return '\u{3164}',
+        '\u{ffa1}'..='\u{ffbe}' => 0xce70,
+        '\u{ffc2}'..='\u{ffc7}' => 0xce73,
+        '\u{ffca}'..='\u{ffcf}' => 0xce75,
+        '\u{ffd2}'..='\u{ffd7}' => 0xce77,
+        '\u{ffda}'..='\u{ffdc}' => 0xce79,
+        // The macron decomposes to a space and a combining macron.
+        '\u{ffe3}' => return '\u{af}',
+        // The other decompositions are a single char that does not decompose further.
+        _ => {
+            let mut normal = c;
+            decompose_compatible(c, |d| normal = d);
+            return normal;
+        }
+    };
+    char::from_u32(c as u32 - offset).unwrap()
+}
+
+fn is_kana(c: char) -> bool {
+    matches!(c, '\u{3041}'..='\u{30ff}')
+}
+
+fn to_katakana(c: char) -> char {
+    match c {
+        // Small a to small ke, and the iteration marks.
+        '\u{3041}'..='\u{3096}' | '\u{309d}' | '\u{309e}' => {
+            char::from_u32(c as u32 + 0x60).unwrap()
+        }
+        _ => c,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_width_variant, normal_width, KanaInsensitive, WidthInsensitive};
+    use crate::fold::{Ascii, FoldPolicy, HttpToken, Identifier, InvalidKey};
+    use unicode_normalization::char::decompose_compatible;
+
+    fn fold<P: FoldPolicy>(s: &str) -> String {
+        P::fold(s.chars()).collect()
+    }
+
+    #[test]
+    fn fullwidth() {
+        assert_eq!(fold::<WidthInsensitive>("ＡＢＣ１２３"), "abc123");
+        assert_eq!(fold::<WidthInsensitive>("ＡＢＣ－１２３"), "abc-123");
+        assert_eq!(fold::<WidthInsensitive>("Ａ\u{3000}Ｂ"), "a b");
+        assert_eq!(fold::<WidthInsensitive<Ascii>>("ＡＢＣ"), "abc");
+        assert_ne!(fold::<Ascii>("ＡＢＣ"), "abc");
+    }
+
+    #[test]
+    fn single_char_decompositions() {
+        assert_eq!(fold::<WidthInsensitive>("\u{ffe3}"), "\u{af}");
+        assert_eq!(fold::<WidthInsensitive>("\u{ffa1}"), "\u{3131}");
+        assert_eq!(fold::<WidthInsensitive>("\u{ffe1}"), "\u{a3}");
+
+        for c in ('\u{ff01}'..='\u{ffee}').chain(['\u{3000}']) {
+            let normal = normal_width(c);
+            let mut expected = String::new();
+            let mut actual = String::new();
+            decompose_compatible(c, |d| expected.push(d));
+            decompose_compatible(normal, |d| actual.push(d));
+            assert_eq!(actual, expected, "{:?}", c);
+            assert!(!is_width_variant(normal) || normal == c, "{:?}", c);
+        }
+    }
+
+    #[test]
+    fn halfwidth_katakana() {
+        assert_eq!(
+            fold::<WidthInsensitive>("ｶﾞｲﾄﾞ"),
+            fold::<WidthInsensitive>("ガイド")
+        );
+        assert_eq!(
+            fold::<WidthInsensitive>("ﾊﾟﾝ"),
+            fold::<WidthInsensitive>("パン")
+        );
+        assert_eq!(
+            fold::<WidthInsensitive>("ガ"),
+            fold::<WidthInsensitive>("カ\u{3099}")
+        );
+        assert_ne!(
+            fold::<WidthInsensitive>("ｶﾞ"),
+            fold::<WidthInsensitive>("カ")
+        );
+        assert_eq!(fold::<WidthInsensitive>("ｰ"), "ー");
+    }
+
+    #[test]
+    fn hiragana_katakana() {
+        assert_eq!(fold::<KanaInsensitive>("かたかな"), "カタカナ");
+        assert_eq!(fold::<KanaInsensitive>("ぎゅうにゅう"), "ギュウニュウ");
+        assert_eq!(fold::<KanaInsensitive>("ゝ"), "ヽ");
+        assert_ne!(
+            fold::<WidthInsensitive>("かな"),
+            fold::<WidthInsensitive>("カナ")
+        );
+    }
+
+    #[test]
+    fn combined() {
+        type Policy = KanaInsensitive<WidthInsensitive>;
+        let expected = fold::<Policy>("ガイドＡ１");
+        assert_eq!(fold::<Policy>("がいどa1"), expected);
+        assert_eq!(fold::<Policy>("ｶﾞｲﾄﾞA1"), expected);
+        assert_eq!(fold::<Policy>("か\u{3099}いと\u{3099}ａ１"), expected);
+    }
+
+    #[test]
+    fn validate_uses_inner_policy() {
+        assert_eq!(WidthInsensitive::<Identifier>::validate("ｆｉｌｅ"), Ok(()));
+        assert_eq!(
+            WidthInsensitive::<Identifier>::validate("a-b"),
+            Err(InvalidKey::at(1, '-'))
+        );
+        assert_eq!(
+            KanaInsensitive::<HttpToken>::validate("かな"),
+            Err(InvalidKey::at(0, 'か'))
+        );
+    }
+}
@@ -0,0 +1,131 @@
+use super::{FoldPolicy, FullUnicode, InvalidKey};
+use unicode_ident::{is_xid_continue, is_xid_start};
+use unicode_normalization::UnicodeNormalization;
+
+/// Identifier matching as recommended by UAX #31, for symbol tables of programming
+/// languages.
+///
+/// Keys are compared by their NFKC_Casefold form: compatibility decomposition, removal
+/// of default ignorable code points, full case folding and NFKC composition. So `ﬁle`,
+/// `FILE` and `ｆｉｌｅ` are the same identifier, and `a\u{200b}b` matches `ab`.
+///
+/// Use the `try_insert` methods of the collections to reject keys that are not
+/// identifiers: a key must start with an `XID_Start` char or `_`, followed by
+/// `XID_Continue` chars. Default ignorable code points are allowed anywhere. The other
+/// insert methods accept any key, while
+/// [`UniCaseSymbolTable`](crate::unicase_symbol_table::UniCaseSymbolTable) only accepts
+/// identifiers.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Identifier;
+
+impl FoldPolicy for Identifier {
+    fn fold<I: Iterator<Item = char>>(chars: I) -> impl Iterator<Item = char> {
+        let chars = chars.nfkd().filter(|&c| !is_default_ignorable(c));
+        FullUnicode::fold(chars).nfkc()
+    }
+
+    fn validate(key: &str) -> Result<(), InvalidKey> {
+        let mut chars = key
+            .char_indices()
+            .filter(|&(_, c)| !is_default_ignorable(c));
+        match chars.next() {
+            Some((_, c)) if is_xid_start(c) || c == '_' => {}
+            Some((i, c)) => return Err(InvalidKey::at(i, c)),
+            None => return Err(InvalidKey::empty()),
+        }
+
+        match chars.find(|&(_, c)| !is_xid_continue(c)) {
+            Some((i, c)) => Err(InvalidKey::at(i, c)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Chars with the Default_Ignorable_Code_Point property.
+fn is_default_ignorable(c: char) -> bool {
+    matches!(
+        c,
+        '\u{00ad}'
+            | '\u{034f}'
+            | '\u{061c}'
+            | '\u{115f}'..='\u{1160}'
+            | '\u{17b4}'..='\u{17b5}'
+            | '\u{180b}'..='\u{180f}'
+            | '\u{200b}'..='\u{200f}'
+            | '\u{202a}'..='\u{202e}'
+            | '\u{2060}'..='\u{206f}'
+            | '\u{3164}'
+            | '\u{fe00}'..='\u{fe0f}'
+            | '\u{feff}'
+            | '\u{ffa0}'
+            | '\u{fff0}'..='\u{fff8}'
+            | '\u{1bca0}'..='\u{1bca3}'
+            | '\u{1d173}'..='\u{1d17a}'
+            | '\u{e0000}'..='\u{e0fff}'
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Identifier;
+    use crate::fold::{FoldPolicy, InvalidKey};
+
+    fn fold<P: FoldPolicy>(s: &str) -> String {
+        P::fold(s.chars()).collect()
+    }
+
+    #[test]
+    fn nfkc_casefold() {
+        assert_eq!(fold::<Identifier>("ﬁle"), "file");
+        assert_eq!(fold::<Identifier>("FILE"), "file");
+        assert_eq!(fold::<Identifier>("ｆｉｌｅ"), "file");
+        assert_eq!(fold::<Identifier>("Straße"), "strasse");
+        assert_eq!(fold::<Identifier>("x²"), "x2");
+        assert_eq!(fold::<Identifier>("Ａ\u{030a}"), "\u{00e5}");
+        assert_eq!(fold::<Identifier>("\u{212b}"), "\u{00e5}");
+    }
+
+    #[test]
+    fn default_ignorables_are_removed() {
+        assert_eq!(fold::<Identifier>("a\u{200b}b"), "ab");
+        assert_eq!(fold::<Identifier>("\u{00ad}ab\u{feff}"), "ab");
+        assert_eq!(fold::<Identifier>("a\u{ffa0}b"), "ab");
+    }
+
+    #[test]
+    fn fold_is_idempotent() {
+        for c in (0..=char::MAX as u32).filter_map(char::from_u32) {
+            let once = fold::<Identifier>(&c.to_string());
+            assert_eq!(fold::<Identifier>(&once), once, "{:?}", c);
+        }
+    }
+
+    #[test]
+    fn validate() {
+        for key in [
+            "x",
+            "_private",
+            "snake_case",
+            "café",
+            "변수",
+            "ｆｉｌｅ",
+            "a\u{200d}b",
+        ] {
+            assert_eq!(Identifier::validate(key), Ok(()), "{:?}", key);
+        }
+        assert_eq!(Identifier::validate(""), Err(InvalidKey::empty()));
+        assert_eq!(Identifier::validate("\u{200b}"), Err(InvalidKey::empty()));
+        assert_eq!(Identifier::validate("1st"), Err(InvalidKey::at(0, '1')));
+        assert_eq!(Identifier::validate("a-b"), Err(InvalidKey::at(1, '-')));
+        assert_eq!(Identifier::validate("ä b"), Err(InvalidKey::at(2, ' ')));
+    }
+
+    #[test]
+    fn error_display() {
+        assert_eq!(
+            InvalidKey::at(1, '-').to_string(),
+            "invalid char '-' at index 1 of key"
+        );
+        assert_eq!(InvalidKey::empty().to_string(), "empty key");
+    }
+}
@@ -0,0 +1,210 @@
+use super::{FoldPolicy, FullUnicode, InvalidKey};
+use std::iter::Peekable;
+use std::marker::PhantomData;
+use unicode_normalization::char::canonical_combining_class;
+use unicode_normalization::UnicodeNormalization;
+
+/// A language whose case folding differs from the default Unicode case folding.
+pub trait Locale {
+    /// Folds `chars` with the tailoring of the language applied around `P`.
+    fn fold<P: FoldPolicy, I: Iterator<Item = char>>(chars: I) -> impl Iterator<Item = char>;
+}
+
+/// Case folding tailored to the language `L`, on top of the policy `P`.
+///
+/// The locale is part of the collection type, e.g. `UniCaseIndexMap<V, String, Tailored<Tr>>`
+/// for a map that folds Turkish keys correctly, while other maps keep the default folding.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Tailored<L, P = FullUnicode>(PhantomData<(L, P)>);
+
+impl<L: Locale, P: FoldPolicy> FoldPolicy for Tailored<L, P> {
+    fn fold<I: Iterator<Item = char>>(chars: I) -> impl Iterator<Item = char> {
+        L::fold::<P, I>(chars)
+    }
+
+    fn validate(key: &str) -> Result<(), InvalidKey> {
+        P::validate(key)
+    }
+}
+
+/// Turkish. Dotted and dotless `i` are different letters: `I` folds to `ı` and `İ`
+/// folds to `i`, using the mappings with status T from CaseFolding.txt.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Tr;
+
+/// Azerbaijani. Uses the same tailoring as [`Tr`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Az;
+
+/// Lithuanian. A lowercase `i` or `j` keeps an explicit dot above when it has another
+/// accent above, so the dot is ignored and e.g. `i̇̀` matches `Ì`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Lt;
+
+impl Locale for Tr {
+    fn fold<P: FoldPolicy, I: Iterator<Item = char>>(chars: I) -> impl Iterator<Item = char> {
+        P::fold(Turkic {
+            chars: chars.peekable(),
+        })
+    }
+}
+
+impl Locale for Az {
+    fn fold<P: FoldPolicy, I: Iterator<Item = char>>(chars: I) -> impl Iterator<Item = char> {
+        Tr::fold::<P, I>(chars)
+    }
+}
+
+impl Locale for Lt {
+    fn fold<P: FoldPolicy, I: Iterator<Item = char>>(chars: I) -> impl Iterator<Item = char> {
+        DotAboveRemover {
+            chars: P::fold(chars.nfd()).nfd(),
+            after_soft_dotted: false,
+        }
+    }
+}
+
+/// Applies the Turkic case folding of `I` and `İ`. `I` followed by a combining dot
+/// above is the decomposed form of `İ`.
+struct Turkic<I: Iterator<Item = char>> {
+    chars: Peekable<I>,
+}
+
+impl<I: Iterator<Item = char>> Iterator for Turkic<I> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        match self.chars.next()? {
+            'I' if self.chars.next_if_eq(&'\u{0307}').is_some() => Some('i'),
+            'I' => Some('\u{0131}'),
+            '\u{0130}' => Some('i'),
+            c => Some(c),
+        }
+    }
+}
+
+/// Removes a combining dot above that follows a soft-dotted letter, unless another
+/// accent above comes in between. Expects NFD input.
+struct DotAboveRemover<I> {
+    chars: I,
+    after_soft_dotted: bool,
+}
+
+impl<I: Iterator<Item = char>> Iterator for DotAboveRemover<I> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        loop {
+            let c = self.chars.next()?;
+            if c == '\u{0307}' && self.after_soft_dotted {
+                self.after_soft_dotted = false;
+                continue;
+            }
+
+            match canonical_combining_class(c) {
+                0 => self.after_soft_dotted = is_soft_dotted(c),
+                230 => self.after_soft_dotted = false,
+                _ => {}
+            }
+            return Some(c);
+        }
+    }
+}
+
+/// Lowercase letters with the Soft_Dotted property in the BMP.
+fn is_soft_dotted(c: char) -> bool {
+    matches!(
+        c,
+        'i' | 'j'
+            | '\u{0249}'
+            | '\u{0268}'
+            | '\u{029d}'
+            | '\u{02b2}'
+            | '\u{03f3}'
+            | '\u{0456}'
+            | '\u{0458}'
+            | '\u{1d62}'
+            | '\u{1d96}'
+            | '\u{1da4}'
+            | '\u{1da8}'
+            | '\u{2071}'
+            | '\u{2148}'
+            | '\u{2149}'
+            | '\u{2c7c}'
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Az, Lt, Tailored, Tr};
+    use crate::fold::{FoldPolicy, FullUnicode, Identifier, InvalidKey, SimpleUnicode};
+
+    fn fold<P: FoldPolicy>(s: &str) -> String {
+        P::fold(s.chars()).collect()
+    }
+
+    #[test]
+    fn turkish() {
+        assert_eq!(fold::<Tailored<Tr>>("İstanbul"), "istanbul");
+        assert_eq!(fold::<Tailored<Tr>>("I\u{0307}stanbul"), "istanbul");
+        assert_eq!(fold::<Tailored<Tr>>("ISPARTA"), "ısparta");
+        assert_eq!(fold::<Tailored<Tr>>("ısparta"), "ısparta");
+        assert_eq!(
+            fold::<Tailored<Tr, SimpleUnicode>>("DİYARBAKIR"),
+            "diyarbakır"
+        );
+        assert_eq!(fold::<Tailored<Az>>("İI"), "iı");
+    }
+
+    #[test]
+    fn turkish_differs_from_default() {
+        assert_ne!(
+            fold::<FullUnicode>("İstanbul"),
+            fold::<FullUnicode>("istanbul")
+        );
+        assert_eq!(
+            fold::<FullUnicode>("ISPARTA"),
+            fold::<FullUnicode>("isparta")
+        );
+        assert_ne!(
+            fold::<Tailored<Tr>>("ISPARTA"),
+            fold::<Tailored<Tr>>("isparta")
+        );
+    }
+
+    #[test]
+    fn lithuanian() {
+        // LATIN CAPITAL LETTER I WITH GRAVE lowercases to i, dot above, grave in Lithuanian.
+        assert_eq!(
+            fold::<Tailored<Lt>>("\u{00cc}"),
+            fold::<Tailored<Lt>>("i\u{0307}\u{0300}")
+        );
+        assert_eq!(
+            fold::<Tailored<Lt>>("\u{012e}\u{0301}"),
+            fold::<Tailored<Lt>>("\u{012f}\u{0307}\u{0301}")
+        );
+        assert_eq!(fold::<Tailored<Lt>>("Jonas"), "jonas");
+        assert_ne!(
+            fold::<FullUnicode>("\u{00cc}"),
+            fold::<FullUnicode>("i\u{0307}\u{0300}")
+        );
+    }
+
+    #[test]
+    fn lithuanian_keeps_other_dots() {
+        assert_eq!(fold::<Tailored<Lt>>("\u{0117}"), "e\u{0307}");
+        assert_eq!(
+            fold::<Tailored<Lt>>("i\u{0300}\u{0307}"),
+            "i\u{0300}\u{0307}"
+        );
+    }
+
+    #[test]
+    fn validate_uses_inner_policy() {
+        assert_eq!(Tailored::<Tr, Identifier>::validate("İstanbul"), Ok(()));
+        assert_eq!(
+            Tailored::<Tr, Identifier>::validate(""),
+            Err(InvalidKey::empty())
+        );
+    }
+}
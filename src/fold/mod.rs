@@ -0,0 +1,372 @@
+//! Folding policies that decide which strings are considered the same key.
+
+mod case_folding;
+mod confusables;
+mod double_metaphone;
+mod east_asian;
+mod identifier;
+mod locale;
+mod normalization;
+mod separators;
+mod soundex;
+mod token;
+
+pub use case_folding::UNICODE_VERSION;
+pub use confusables::ConfusableInsensitive;
+pub use double_metaphone::{double_metaphone, DoubleMetaphone};
+pub use east_asian::{KanaInsensitive, WidthInsensitive};
+pub use identifier::Identifier;
+pub use locale::{Az, Locale, Lt, Tailored, Tr};
+pub use normalization::{AccentInsensitive, Normalized};
+pub use separators::SeparatorInsensitive;
+pub use soundex::{soundex, Soundex};
+pub use token::HttpToken;
+
+use std::error::Error;
+use std::fmt;
+
+/// Decides which strings are considered the same key.
+///
+/// A policy maps a key to the sequence of chars that is compared, ordered and hashed
+/// in its place. Two keys are equal if their folded sequences are equal, and they are
+/// ordered by comparing the folded sequences lexicographically, so every policy gives
+/// a total order that is consistent with hashing.
+///
+/// Policies are used as a type parameter of the collections and are never instantiated.
+pub trait FoldPolicy {
+    /// Folds `chars` into the sequence that is compared, ordered and hashed.
+    fn fold<I: Iterator<Item = char>>(chars: I) -> impl Iterator<Item = char>;
+
+    /// True if every key that consists of ASCII chars only folds to the ASCII lowercase
+    /// of its chars. Keys then compare and hash ASCII keys without calling
+    /// [`fold`](Self::fold), which makes lookups of ASCII keys much faster. False by
+    /// default.
+    const ASCII_FAST_PATH: bool = false;
+
+    /// Checks whether `key` is allowed as a key, used by the `try_insert` methods of
+    /// the collections. Accepts every key by default.
+    ///
+    /// Only the `try_*` methods call this. `insert`, `entry`, `extend`, `from_iter` and
+    /// the literal macros accept any key, so a collection with a validating policy can
+    /// still contain keys the policy rejects. Use
+    /// [`UniCaseSymbolTable`](crate::unicase_symbol_table::UniCaseSymbolTable), which only
+    /// has fallible inserts, when every key must be valid.
+    fn validate(_key: &str) -> Result<(), InvalidKey> {
+        Ok(())
+    }
+}
+
+/// The error returned when a [`FoldPolicy`] rejects a key.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidKey {
+    index: usize,
+    invalid: Option<char>,
+}
+
+impl InvalidKey {
+    /// Creates an error for the char `invalid` at the byte offset `index` of the key.
+    pub fn at(index: usize, invalid: char) -> Self {
+        Self {
+            index,
+            invalid: Some(invalid),
+        }
+    }
+
+    /// Creates an error for an empty key.
+    pub fn empty() -> Self {
+        Self {
+            index: 0,
+            invalid: None,
+        }
+    }
+
+    /// Returns the byte offset of the rejected char in the key.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the rejected char, or `None` if the key was rejected for being empty.
+    pub fn invalid_char(&self) -> Option<char> {
+        self.invalid
+    }
+}
+
+impl fmt::Display for InvalidKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.invalid {
+            Some(c) => write!(f, "invalid char {:?} at index {} of key", c, self.index),
+            None => f.write_str("empty key"),
+        }
+    }
+}
+
+impl Error for InvalidKey {}
+
+/// ASCII-only case folding.
+///
+/// Non-ASCII chars are compared as is, so e.g. the Kelvin sign `K` (U+212A) does not
+/// collide with `K`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Ascii;
+
+impl FoldPolicy for Ascii {
+    const ASCII_FAST_PATH: bool = true;
+
+    fn fold<I: Iterator<Item = char>>(chars: I) -> impl Iterator<Item = char> {
+        chars.map(|c| c.to_ascii_lowercase())
+    }
+}
+
+/// Simple Unicode case folding, using the mappings with status C and S from
+/// CaseFolding.txt. Every char folds to exactly one char.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SimpleUnicode;
+
+impl FoldPolicy for SimpleUnicode {
+    const ASCII_FAST_PATH: bool = true;
+
+    fn fold<I: Iterator<Item = char>>(chars: I) -> impl Iterator<Item = char> {
+        chars.map(simple_fold)
+    }
+}
+
+/// Full Unicode case folding, using the mappings with status C and F from
+/// CaseFolding.txt. Some chars fold to several chars, e.g. `ß` folds to `ss`.
+///
+/// Expansions take part in hashing and ordering like any other folded chars, so
+/// `straße`, `STRASSE` and `STRAẞE` are the same key and sort between `strasr` and
+/// `strast`. This is the default policy.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FullUnicode;
+
+impl FoldPolicy for FullUnicode {
+    const ASCII_FAST_PATH: bool = true;
+
+    fn fold<I: Iterator<Item = char>>(chars: I) -> impl Iterator<Item = char> {
+        chars.flat_map(full_fold)
+    }
+}
+
+/// Returns the simple case folding of `c`.
+#[inline]
+pub const fn simple_fold(c: char) -> char {
+    if c.is_ascii() {
+        return c.to_ascii_lowercase();
+    }
+
+    // The binary searches are written out, so that the folding works in const contexts.
+    let table = case_folding::SIMPLE;
+    let (mut low, mut high) = (0, table.len());
+    while low < high {
+        let mid = low + (high - low) / 2;
+        let (from, to) = table[mid];
+        if from as u32 == c as u32 {
+            return to;
+        } else if (from as u32) < c as u32 {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    c
+}
+
+/// Returns the full case folding of `c`.
+#[inline]
+pub const fn full_fold(c: char) -> Folded {
+    if !c.is_ascii() {
+        let table = case_folding::FULL;
+        let (mut low, mut high) = (0, table.len());
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let (from, chars) = table[mid];
+            if from as u32 == c as u32 {
+                let mut len = 1;
+                while len < chars.len() && chars[len] as u32 != 0 {
+                    len += 1;
+                }
+                return Folded { chars, len, pos: 0 };
+            } else if (from as u32) < c as u32 {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+    }
+
+    Folded::one(simple_fold(c))
+}
+
+/// An iterator over the one to three chars a single char folds to.
+#[derive(Clone, Debug)]
+pub struct Folded {
+    chars: [char; 3],
+    len: usize,
+    pos: usize,
+}
+
+impl Folded {
+    const fn one(c: char) -> Self {
+        Self {
+            chars: [c, '\0', '\0'],
+            len: 1,
+            pos: 0,
+        }
+    }
+
+    /// Like `next`, but usable in const contexts.
+    pub(crate) const fn next_char(&mut self) -> Option<char> {
+        if self.pos == self.len {
+            return None;
+        }
+
+        self.pos += 1;
+        Some(self.chars[self.pos - 1])
+    }
+}
+
+impl Iterator for Folded {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        self.next_char()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.len - self.pos;
+        (remaining, Some(remaining))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        full_fold, simple_fold, Ascii, FoldPolicy, FullUnicode, SimpleUnicode, UNICODE_VERSION,
+    };
+    use std::collections::BTreeMap;
+
+    /// Parses data/CaseFolding.txt into a map from status to mappings.
+    fn case_folding_data() -> BTreeMap<char, BTreeMap<char, Vec<char>>> {
+        let mut data: BTreeMap<char, BTreeMap<char, Vec<char>>> = BTreeMap::new();
+        for line in include_str!("../../data/CaseFolding.txt").lines() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let fields: Vec<_> = line.split(';').map(str::trim).collect();
+            let parse = |s: &str| char::from_u32(u32::from_str_radix(s, 16).unwrap()).unwrap();
+            let status = fields[1].chars().next().unwrap();
+            let mapping = fields[2].split(' ').map(parse).collect();
+            data.entry(status)
+                .or_default()
+                .insert(parse(fields[0]), mapping);
+        }
+        data
+    }
+
+    /// Returns the expected folding of every char, given the statuses to use.
+    fn expected(statuses: &[char]) -> BTreeMap<char, Vec<char>> {
+        let data = case_folding_data();
+        let mut expected = BTreeMap::new();
+        for status in statuses {
+            for (&c, mapping) in &data[status] {
+                expected.insert(c, mapping.clone());
+            }
+        }
+        expected
+    }
+
+    fn all_chars() -> impl Iterator<Item = char> {
+        (0..=char::MAX as u32).filter_map(char::from_u32)
+    }
+
+    fn fold<P: FoldPolicy>(s: &str) -> String {
+        P::fold(s.chars()).collect()
+    }
+
+    #[test]
+    fn full_fold_matches_case_folding_data() {
+        let expected = expected(&['C', 'F']);
+        for c in all_chars() {
+            let folded: Vec<_> = full_fold(c).collect();
+            match expected.get(&c) {
+                Some(mapping) => assert_eq!(&folded, mapping, "{:?}", c),
+                None => assert_eq!(folded, vec![c], "{:?}", c),
+            }
+        }
+    }
+
+    #[test]
+    fn simple_fold_matches_case_folding_data() {
+        let expected = expected(&['C', 'S']);
+        for c in all_chars() {
+            match expected.get(&c) {
+                Some(mapping) => assert_eq!(vec![simple_fold(c)], *mapping, "{:?}", c),
+                None => assert_eq!(simple_fold(c), c, "{:?}", c),
+            }
+        }
+    }
+
+    #[test]
+    fn full_fold_is_idempotent() {
+        for c in all_chars() {
+            let once: String = full_fold(c).collect();
+            let twice: String = FullUnicode::fold(once.chars()).collect();
+            assert_eq!(once, twice, "{:?}", c);
+        }
+    }
+
+    #[test]
+    fn ascii() {
+        assert_eq!(fold::<Ascii>("Content-Type"), "content-type");
+        assert_eq!(fold::<Ascii>("\u{212a}"), "\u{212a}");
+        assert_eq!(fold::<Ascii>("ÅÄÖ"), "ÅÄÖ");
+    }
+
+    #[test]
+    fn simple_unicode() {
+        assert_eq!(fold::<SimpleUnicode>("ÅÄÖ"), "åäö");
+        assert_eq!(fold::<SimpleUnicode>("\u{212a}"), "k");
+        assert_eq!(fold::<SimpleUnicode>("ΣΑΣ"), fold::<SimpleUnicode>("σας"));
+        assert_eq!(fold::<SimpleUnicode>("ß"), "ß");
+        assert_eq!(simple_fold('\u{1e9e}'), 'ß');
+    }
+
+    #[test]
+    fn full_unicode() {
+        assert_eq!(fold::<FullUnicode>("ÅÄÖ"), "åäö");
+        assert_eq!(fold::<FullUnicode>("\u{212a}"), "k");
+        assert_eq!(fold::<FullUnicode>("Maße"), "masse");
+        assert_eq!(full_fold('\u{1e9e}').collect::<String>(), "ss");
+        assert_eq!(full_fold('\u{0390}').count(), 3);
+    }
+
+    #[test]
+    fn full_unicode_expansions_keep_their_order() {
+        // GREEK SMALL LETTER IOTA WITH DIALYTIKA AND TONOS folds to its decomposition.
+        assert_eq!(
+            fold::<FullUnicode>("\u{0390}"),
+            fold::<FullUnicode>("\u{03b9}\u{0308}\u{0301}")
+        );
+        assert_eq!(fold::<FullUnicode>("ﬃ"), "ffi");
+        assert_eq!(fold::<FullUnicode>("ǰ"), fold::<FullUnicode>("J\u{030c}"));
+    }
+
+    #[test]
+    fn unicode_versions_agree() {
+        // Fails when a dependency update brings newer Unicode data than
+        // data/CaseFolding.txt, which then needs to be updated as well.
+        let (major, minor, update) = UNICODE_VERSION;
+        assert_eq!(
+            unicode_normalization::UNICODE_VERSION,
+            (major, minor, update),
+            "unicode-normalization"
+        );
+        assert_eq!(
+            unicode_security::UNICODE_VERSION,
+            (major as u64, minor as u64, update as u64),
+            "unicode-security"
+        );
+    }
+}
@@ -0,0 +1,112 @@
+use super::{FoldPolicy, FullUnicode, InvalidKey};
+use std::marker::PhantomData;
+use unicode_normalization::char::canonical_combining_class;
+use unicode_normalization::UnicodeNormalization;
+
+/// Canonical caseless matching (Unicode definition D145).
+///
+/// Keys are decomposed to NFD before and after folding them with `P`, so canonically
+/// equivalent strings are the same key, e.g. precomposed `Å` (U+00C5), `A` followed by
+/// a combining ring above (U+030A) and the Angstrom sign (U+212B).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Normalized<P = FullUnicode>(PhantomData<P>);
+
+impl<P: FoldPolicy> FoldPolicy for Normalized<P> {
+    fn fold<I: Iterator<Item = char>>(chars: I) -> impl Iterator<Item = char> {
+        P::fold(chars.nfd()).nfd()
+    }
+
+    fn validate(key: &str) -> Result<(), InvalidKey> {
+        P::validate(key)
+    }
+}
+
+/// Accent- and case-insensitive matching.
+///
+/// Keys are decomposed to NFD and every combining mark with a non-zero canonical
+/// combining class is removed before and after folding them with `P`, so `café`,
+/// `Cafe` and `CAFÉ` are the same key. Letters without a decomposition, like `ø`
+/// or `ł`, are kept as is.
+///
+/// Keys are ordered by their stripped, folded form, which gives a total order that
+/// does not depend on the insertion order: `café` sorts next to `cafe`, before `cafes`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AccentInsensitive<P = FullUnicode>(PhantomData<P>);
+
+impl<P: FoldPolicy> FoldPolicy for AccentInsensitive<P> {
+    fn fold<I: Iterator<Item = char>>(chars: I) -> impl Iterator<Item = char> {
+        // Folding can produce new marks, e.g. `İ` folds to `i` followed by U+0307.
+        strip_marks(P::fold(strip_marks(chars.nfd())).nfd())
+    }
+
+    fn validate(key: &str) -> Result<(), InvalidKey> {
+        P::validate(key)
+    }
+}
+
+fn strip_marks<I: Iterator<Item = char>>(chars: I) -> impl Iterator<Item = char> {
+    chars.filter(|&c| canonical_combining_class(c) == 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AccentInsensitive, Normalized};
+    use crate::fold::{Ascii, FoldPolicy, Identifier, InvalidKey};
+
+    fn fold<P: FoldPolicy>(s: &str) -> String {
+        P::fold(s.chars()).collect()
+    }
+
+    #[test]
+    fn canonical_equivalence() {
+        let expected = fold::<Normalized>("a\u{030a}");
+        assert_eq!(fold::<Normalized>("\u{00c5}"), expected);
+        assert_eq!(fold::<Normalized>("\u{212b}"), expected);
+        assert_eq!(fold::<Normalized>("\u{00e5}"), expected);
+        assert_ne!(fold::<Normalized>("a"), expected);
+    }
+
+    #[test]
+    fn combining_mark_order() {
+        // Marks with different combining classes are reordered by NFD.
+        assert_eq!(
+            fold::<Normalized>("q\u{0307}\u{0323}"),
+            fold::<Normalized>("Q\u{0323}\u{0307}")
+        );
+    }
+
+    #[test]
+    fn inner_policy() {
+        // Decomposition exposes ASCII base letters to ASCII folding.
+        assert_eq!(fold::<Normalized<Ascii>>("\u{00c5}"), "a\u{030a}");
+        assert_eq!(fold::<Normalized<Ascii>>("\u{212a}"), "k");
+        assert_ne!(
+            fold::<Normalized<Ascii>>("Ω"),
+            fold::<Normalized<Ascii>>("ω")
+        );
+    }
+
+    #[test]
+    fn accent_insensitive() {
+        let expected = "cafe";
+        assert_eq!(fold::<AccentInsensitive>("café"), expected);
+        assert_eq!(fold::<AccentInsensitive>("Cafe"), expected);
+        assert_eq!(fold::<AccentInsensitive>("CAFÉ"), expected);
+        assert_eq!(fold::<AccentInsensitive>("CAFE\u{0301}"), expected);
+        assert_eq!(fold::<AccentInsensitive>("İstanbul"), "istanbul");
+        assert_eq!(fold::<AccentInsensitive>("Ærøskøbing"), "ærøskøbing");
+    }
+
+    #[test]
+    fn validate_uses_inner_policy() {
+        assert_eq!(
+            Normalized::<Identifier>::validate("a b"),
+            Err(InvalidKey::at(1, ' '))
+        );
+        assert_eq!(AccentInsensitive::<Identifier>::validate("café"), Ok(()));
+        assert_eq!(
+            AccentInsensitive::<Identifier>::validate("1st"),
+            Err(InvalidKey::at(0, '1'))
+        );
+    }
+}
@@ -0,0 +1,71 @@
+use super::{FoldPolicy, FullUnicode, InvalidKey};
+use std::marker::PhantomData;
+
+/// Separator- and whitespace-insensitive matching for identifiers like config keys.
+///
+/// `_`, `-` and all whitespace are ignored before folding the key with `P`, so
+/// `content_type`, `Content-Type`, `contentType` and ` content type ` are the same key.
+/// Since camelCase boundaries only differ in case, they need no special handling.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SeparatorInsensitive<P = FullUnicode>(PhantomData<P>);
+
+impl<P: FoldPolicy> FoldPolicy for SeparatorInsensitive<P> {
+    fn fold<I: Iterator<Item = char>>(chars: I) -> impl Iterator<Item = char> {
+        P::fold(chars.filter(|&c| !is_separator(c)))
+    }
+
+    fn validate(key: &str) -> Result<(), InvalidKey> {
+        P::validate(key)
+    }
+}
+
+fn is_separator(c: char) -> bool {
+    c == '_' || c == '-' || c.is_whitespace()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SeparatorInsensitive;
+    use crate::fold::{Ascii, FoldPolicy, HttpToken, InvalidKey};
+
+    fn fold<P: FoldPolicy>(s: &str) -> String {
+        P::fold(s.chars()).collect()
+    }
+
+    #[test]
+    fn separators() {
+        for s in [
+            "content_type",
+            "Content-Type",
+            "contentType",
+            "ContentType",
+            " content type ",
+            "CONTENT__TYPE",
+            "content\t-\ttype",
+        ] {
+            assert_eq!(fold::<SeparatorInsensitive>(s), "contenttype", "{:?}", s);
+        }
+        assert_ne!(
+            fold::<SeparatorInsensitive>("content.type"),
+            fold::<SeparatorInsensitive>("content_type")
+        );
+    }
+
+    #[test]
+    fn inner_policy() {
+        assert_eq!(fold::<SeparatorInsensitive<Ascii>>("Ä-Ö"), "ÄÖ");
+        assert_eq!(fold::<SeparatorInsensitive>("Ä-Ö"), "äö");
+    }
+
+    #[test]
+    fn validate_uses_inner_policy() {
+        assert_eq!(
+            SeparatorInsensitive::<HttpToken>::validate("Content Type"),
+            Err(InvalidKey::at(7, ' '))
+        );
+        assert_eq!(
+            SeparatorInsensitive::<HttpToken>::validate("Content-Type"),
+            Ok(())
+        );
+    }
+}
@@ -0,0 +1,81 @@
+use super::{Ascii, FoldPolicy, InvalidKey};
+
+/// Matching for HTTP field names and other tokens of RFC 9110.
+///
+/// Keys are compared with ASCII case folding like [`Ascii`]. The `try_insert` methods
+/// of the collections reject keys that are empty or contain chars other than ASCII
+/// letters, digits and ``!#$%&'*+-.^_`|~``.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HttpToken;
+
+impl FoldPolicy for HttpToken {
+    const ASCII_FAST_PATH: bool = true;
+
+    fn fold<I: Iterator<Item = char>>(chars: I) -> impl Iterator<Item = char> {
+        Ascii::fold(chars)
+    }
+
+    fn validate(key: &str) -> Result<(), InvalidKey> {
+        if key.is_empty() {
+            return Err(InvalidKey::empty());
+        }
+
+        match key.char_indices().find(|&(_, c)| !is_tchar(c)) {
+            Some((i, c)) => Err(InvalidKey::at(i, c)),
+            None => Ok(()),
+        }
+    }
+}
+
+fn is_tchar(c: char) -> bool {
+    c.is_ascii_alphanumeric()
+        || matches!(
+            c,
+            '!' | '#'
+                | '$'
+                | '%'
+                | '&'
+                | '\''
+                | '*'
+                | '+'
+                | '-'
+                | '.'
+                | '^'
+                | '_'
+                | '`'
+                | '|'
+                | '~'
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HttpToken;
+    use crate::fold::{FoldPolicy, InvalidKey};
+
+    #[test]
+    fn fold() {
+        let fold = |s: &str| HttpToken::fold(s.chars()).collect::<String>();
+        assert_eq!(fold("Content-Type"), "content-type");
+        assert_eq!(fold("\u{212a}"), "\u{212a}");
+    }
+
+    #[test]
+    fn validate() {
+        for key in [
+            "Content-Type",
+            "x-request-id",
+            "X_Custom.1",
+            "!#$%&'*+-.^_`|~",
+        ] {
+            assert_eq!(HttpToken::validate(key), Ok(()), "{:?}", key);
+        }
+        assert_eq!(HttpToken::validate(""), Err(InvalidKey::empty()));
+        assert_eq!(
+            HttpToken::validate("Content Type"),
+            Err(InvalidKey::at(7, ' '))
+        );
+        assert_eq!(HttpToken::validate("Host:"), Err(InvalidKey::at(4, ':')));
+        assert_eq!(HttpToken::validate("Größe"), Err(InvalidKey::at(2, 'ö')));
+    }
+}
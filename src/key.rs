@@ -1,31 +1,498 @@
+use crate::fold::{FoldPolicy, FullUnicode};
+use std::borrow::{Borrow, Cow};
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::sync::Arc;
 use unicase::UniCase;
 
-pub type Key = UniCase<String>;
+/// The key type stored by the collections.
+///
+/// `S` is the storage for the original spelling of the key. It defaults to `String`,
+/// but any string-like type works, e.g. `Box<str>`, `Arc<str>`, `Cow<'static, str>`
+/// or `&'static str` for static tables that should not allocate at all.
+///
+/// `P` is the [`FoldPolicy`] that decides which keys are equal. Keys are compared,
+/// ordered and hashed by their folded form, but always keep the original spelling.
+pub struct Key<S = String, P = FullUnicode> {
+    inner: S,
+    policy: PhantomData<fn() -> P>,
+}
+
+impl<S, P> Key<S, P> {
+    /// Creates a new key from its storage.
+    pub const fn new(inner: S) -> Self {
+        Self {
+            inner,
+            policy: PhantomData,
+        }
+    }
+
+    /// Consumes the key, returning the storage.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: AsRef<str>, P> Key<S, P> {
+    /// Returns the original spelling of the key.
+    pub fn as_str(&self) -> &str {
+        self.inner.as_ref()
+    }
+}
+
+impl<S, P> Deref for Key<S, P> {
+    type Target = S;
+
+    fn deref(&self) -> &S {
+        &self.inner
+    }
+}
+
+impl<S: AsRef<str>, P> AsRef<str> for Key<S, P> {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<S, P> From<S> for Key<S, P> {
+    fn from(inner: S) -> Self {
+        Self::new(inner)
+    }
+}
+
+impl<S: Clone, P> Clone for Key<S, P> {
+    fn clone(&self) -> Self {
+        Self::new(self.inner.clone())
+    }
+}
+
+impl<S: Copy, P> Copy for Key<S, P> {}
+
+impl<S: fmt::Debug, P> fmt::Debug for Key<S, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+impl<S: fmt::Display, P> fmt::Display for Key<S, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
 
-pub trait ToKey {
-    fn to_key(self) -> Key;
+impl<S1, S2, P> PartialEq<Key<S2, P>> for Key<S1, P>
+where
+    S1: AsRef<str>,
+    S2: AsRef<str>,
+    P: FoldPolicy,
+{
+    fn eq(&self, other: &Key<S2, P>) -> bool {
+        fold_eq::<P>(self.as_str(), other.as_str())
+    }
 }
 
-impl ToKey for String {
-    fn to_key(self) -> Key {
-        UniCase::new(self)
+impl<S: AsRef<str>, P: FoldPolicy> Eq for Key<S, P> {}
+
+impl<S: AsRef<str>, P: FoldPolicy> PartialOrd for Key<S, P> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
     }
 }
 
-impl ToKey for &str {
-    fn to_key(self) -> Key {
-        UniCase::new(self.to_string())
+impl<S: AsRef<str>, P: FoldPolicy> Ord for Key<S, P> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        fold_cmp::<P>(self.as_str(), other.as_str())
     }
 }
 
-impl ToKey for UniCase<String> {
-    fn to_key(self) -> Key {
+impl<S: AsRef<str>, P: FoldPolicy> Hash for Key<S, P> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        fold_hash::<P, H>(self.as_str(), state);
+    }
+}
+
+impl<S: AsRef<str>, P: FoldPolicy> Borrow<KeyStr<P>> for Key<S, P> {
+    fn borrow(&self) -> &KeyStr<P> {
+        KeyStr::new(self.as_str())
+    }
+}
+
+/// Conversion into an owned [`Key`] with storage `S`, used by inserts.
+pub trait ToKey<S = String, P = FullUnicode> {
+    fn to_key(self) -> Key<S, P>;
+}
+
+impl<S, P> ToKey<S, P> for String
+where
+    S: AsRef<str> + From<String>,
+{
+    fn to_key(self) -> Key<S, P> {
+        Key::new(S::from(self))
+    }
+}
+
+impl<'a, S, P> ToKey<S, P> for &'a str
+where
+    S: AsRef<str> + From<&'a str>,
+{
+    fn to_key(self) -> Key<S, P> {
+        Key::new(S::from(self))
+    }
+}
+
+impl<S, P> ToKey<S, P> for Arc<str>
+where
+    S: AsRef<str> + From<Arc<str>>,
+{
+    fn to_key(self) -> Key<S, P> {
+        Key::new(S::from(self))
+    }
+}
+
+impl<S, P> ToKey<S, P> for Box<str>
+where
+    S: AsRef<str> + From<Box<str>>,
+{
+    fn to_key(self) -> Key<S, P> {
+        Key::new(S::from(self))
+    }
+}
+
+impl<S, P> ToKey<S, P> for Cow<'static, str>
+where
+    S: AsRef<str> + From<Cow<'static, str>>,
+{
+    fn to_key(self) -> Key<S, P> {
+        Key::new(S::from(self))
+    }
+}
+
+impl<S, P> ToKey<S, P> for Key<S, P> {
+    fn to_key(self) -> Key<S, P> {
         self
     }
 }
 
-impl ToKey for &UniCase<String> {
-    fn to_key(self) -> Key {
+impl<S: Clone, P> ToKey<S, P> for &Key<S, P> {
+    fn to_key(self) -> Key<S, P> {
         self.clone()
     }
 }
+
+impl<S, P> ToKey<S, P> for UniCase<S> {
+    fn to_key(self) -> Key<S, P> {
+        Key::new(self.into_inner())
+    }
+}
+
+impl<S: Clone, P> ToKey<S, P> for &UniCase<S> {
+    fn to_key(self) -> Key<S, P> {
+        Key::new(S::clone(self))
+    }
+}
+
+/// A borrowed view of a string that is compared, ordered and hashed by the
+/// policy `P`.
+///
+/// `KeyStr` behaves exactly like [`Key`], which makes it possible to look up keys
+/// in the collections without allocating a new `Key`.
+#[repr(transparent)]
+pub struct KeyStr<P = FullUnicode> {
+    policy: PhantomData<fn() -> P>,
+    inner: str,
+}
+
+impl<P> KeyStr<P> {
+    /// Wraps a string slice as a `KeyStr`.
+    pub fn new(s: &str) -> &KeyStr<P> {
+        // SAFETY: `KeyStr` is a `repr(transparent)` wrapper around `str`.
+        unsafe { &*(s as *const str as *const KeyStr<P>) }
+    }
+
+    /// Returns the wrapped string slice.
+    pub fn as_str(&self) -> &str {
+        &self.inner
+    }
+}
+
+impl<P> fmt::Debug for KeyStr<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+impl<P: FoldPolicy> PartialEq for KeyStr<P> {
+    fn eq(&self, other: &KeyStr<P>) -> bool {
+        fold_eq::<P>(self.as_str(), other.as_str())
+    }
+}
+
+impl<P: FoldPolicy> Eq for KeyStr<P> {}
+
+impl<P: FoldPolicy> PartialOrd for KeyStr<P> {
+    fn partial_cmp(&self, other: &KeyStr<P>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<P: FoldPolicy> Ord for KeyStr<P> {
+    fn cmp(&self, other: &KeyStr<P>) -> Ordering {
+        fold_cmp::<P>(self.as_str(), other.as_str())
+    }
+}
+
+impl<P: FoldPolicy> Hash for KeyStr<P> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        fold_hash::<P, H>(self.as_str(), state);
+    }
+}
+
+/// Types that can be used to look up a key without allocating.
+pub trait AsKeyStr {
+    fn as_key_str<P>(&self) -> &KeyStr<P>;
+}
+
+impl<Q> AsKeyStr for KeyStr<Q> {
+    fn as_key_str<P>(&self) -> &KeyStr<P> {
+        KeyStr::new(self.as_str())
+    }
+}
+
+impl AsKeyStr for str {
+    fn as_key_str<P>(&self) -> &KeyStr<P> {
+        KeyStr::new(self)
+    }
+}
+
+impl AsKeyStr for String {
+    fn as_key_str<P>(&self) -> &KeyStr<P> {
+        KeyStr::new(self)
+    }
+}
+
+impl<S: AsRef<str>, Q> AsKeyStr for Key<S, Q> {
+    fn as_key_str<P>(&self) -> &KeyStr<P> {
+        KeyStr::new(self.as_str())
+    }
+}
+
+impl<S: AsRef<str>> AsKeyStr for UniCase<S> {
+    fn as_key_str<P>(&self) -> &KeyStr<P> {
+        KeyStr::new(self.as_ref())
+    }
+}
+
+impl<T: AsKeyStr + ?Sized> AsKeyStr for &T {
+    fn as_key_str<P>(&self) -> &KeyStr<P> {
+        (**self).as_key_str()
+    }
+}
+
+/// The size of the chunks in which the UTF-8 of the folded keys is hashed.
+const HASH_CHUNK: usize = 64;
+
+#[inline]
+fn fold_eq<P: FoldPolicy>(a: &str, b: &str) -> bool {
+    if a == b {
+        return true;
+    }
+    if P::ASCII_FAST_PATH && a.is_ascii() && b.is_ascii() {
+        return a.eq_ignore_ascii_case(b);
+    }
+    P::fold(a.chars()).eq(P::fold(b.chars()))
+}
+
+#[inline]
+fn fold_cmp<P: FoldPolicy>(a: &str, b: &str) -> Ordering {
+    if P::ASCII_FAST_PATH && a.is_ascii() && b.is_ascii() {
+        let a = a.bytes().map(|b| b.to_ascii_lowercase());
+        return a.cmp(b.bytes().map(|b| b.to_ascii_lowercase()));
+    }
+    P::fold(a.chars()).cmp(P::fold(b.chars()))
+}
+
+#[inline]
+fn fold_hash<P: FoldPolicy, H: Hasher>(s: &str, state: &mut H) {
+    // Both paths write the same chunks, so that an ASCII key hashes like the non-ASCII
+    // keys that fold to it, e.g. `K` and the Kelvin sign.
+    let mut buf = [0; HASH_CHUNK];
+    if P::ASCII_FAST_PATH && s.is_ascii() {
+        for chunk in s.as_bytes().chunks(HASH_CHUNK) {
+            let buf = &mut buf[..chunk.len()];
+            buf.copy_from_slice(chunk);
+            buf.make_ascii_lowercase();
+            state.write(buf);
+        }
+    } else {
+        let mut len = 0;
+        for c in P::fold(s.chars()) {
+            for &byte in c.encode_utf8(&mut [0; 4]).as_bytes() {
+                if len == HASH_CHUNK {
+                    state.write(&buf);
+                    len = 0;
+                }
+                buf[len] = byte;
+                len += 1;
+            }
+        }
+        if len > 0 {
+            state.write(&buf[..len]);
+        }
+    }
+    // Terminate the sequence like `str` does, so that hashing keys in sequence is prefix-free.
+    state.write_u8(0xff);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Key, KeyStr, ToKey};
+    use crate::fold::{Ascii, FoldPolicy, FullUnicode, SimpleUnicode};
+    use crate::unicase_btree_map::UniCaseBTreeMap;
+    use std::borrow::{Borrow, Cow};
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+    use std::sync::Arc;
+
+    fn hash<T: Hash + ?Sized>(t: &T) -> u64 {
+        let mut s = DefaultHasher::new();
+        t.hash(&mut s);
+        s.finish()
+    }
+
+    fn key_str_matches_key<P: FoldPolicy>() {
+        for (a, b) in [("abc", "ABC"), ("Maße", "MASSE"), ("Å", "å"), ("a", "b")] {
+            let key = Key::<String, P>::new(a.to_string());
+            let other = Key::<String, P>::new(b.to_string());
+            let borrowed: &KeyStr<P> = key.borrow();
+            assert_eq!(hash(&key), hash(borrowed));
+            assert_eq!(key == other, borrowed == KeyStr::new(b));
+            assert_eq!(key.cmp(&other), borrowed.cmp(KeyStr::new(b)));
+            if key == other {
+                assert_eq!(hash(&key), hash(&other));
+            }
+        }
+    }
+
+    #[test]
+    fn key_str_matches_key_ascii() {
+        key_str_matches_key::<Ascii>();
+    }
+
+    #[test]
+    fn key_str_matches_key_simple_unicode() {
+        key_str_matches_key::<SimpleUnicode>();
+    }
+
+    #[test]
+    fn key_str_matches_key_full_unicode() {
+        key_str_matches_key::<FullUnicode>();
+    }
+
+    /// Records the writes, so that the chunks of the paths can be compared.
+    #[derive(Default)]
+    struct Writes(Vec<Vec<u8>>);
+
+    impl Hasher for Writes {
+        fn finish(&self) -> u64 {
+            0
+        }
+
+        fn write(&mut self, bytes: &[u8]) {
+            self.0.push(bytes.to_vec());
+        }
+    }
+
+    /// Full Unicode case folding without the ASCII fast path.
+    struct NoFastPath;
+
+    impl FoldPolicy for NoFastPath {
+        fn fold<I: Iterator<Item = char>>(chars: I) -> impl Iterator<Item = char> {
+            FullUnicode::fold(chars)
+        }
+    }
+
+    #[test]
+    fn ascii_fast_path_matches_fold() {
+        // The Kelvin sign straddles the end of the first chunk.
+        let long = format!("{}K{}", "x".repeat(63), "Keep-Alive".repeat(10));
+        let long_kelvin = long.replacen('K', "\u{212a}", 1);
+        let keys = [
+            "",
+            "a",
+            "A",
+            "b",
+            "ab",
+            "Content-Type",
+            "content-type",
+            "CONTENT",
+            "\u{212a}",
+            "K",
+            "k",
+            "Maße",
+            "MASSE",
+            "_",
+            "[",
+            &long,
+            &long_kelvin,
+        ];
+        for a in keys {
+            for b in keys {
+                let fast = (Key::<&str, FullUnicode>::new(a), Key::new(b));
+                let slow = (Key::<&str, NoFastPath>::new(a), Key::new(b));
+                assert_eq!(fast.0 == fast.1, slow.0 == slow.1, "{:?} == {:?}", a, b);
+                assert_eq!(
+                    fast.0.cmp(&fast.1),
+                    slow.0.cmp(&slow.1),
+                    "{:?} <=> {:?}",
+                    a,
+                    b
+                );
+            }
+            let (mut fast, mut slow) = (Writes::default(), Writes::default());
+            Key::<&str, FullUnicode>::new(a).hash(&mut fast);
+            Key::<&str, NoFastPath>::new(a).hash(&mut slow);
+            assert_eq!(fast.0, slow.0, "{:?}", a);
+        }
+        assert_eq!(
+            hash(&Key::<&str, FullUnicode>::new(&long)),
+            hash(&Key::<&str, FullUnicode>::new(&long_kelvin))
+        );
+    }
+
+    #[test]
+    fn shared_keys() {
+        let key: Arc<str> = Arc::from("Content-Type");
+        let mut map1 = UniCaseBTreeMap::<u8, Arc<str>>::default();
+        let mut map2 = UniCaseBTreeMap::<u8, Arc<str>>::default();
+        map1.insert(key.clone(), 1);
+        map2.insert(key.clone(), 2);
+        assert_eq!(Arc::strong_count(&key), 3);
+        assert_eq!(map1.get("content-type"), Some(&1));
+        assert_eq!(map2.get("CONTENT-TYPE"), Some(&2));
+
+        let boxed: Key<Box<str>> = Box::<str>::from("ACCEPT").to_key();
+        assert_eq!(boxed, Key::new("Accept"));
+        let cow: Key<Cow<'static, str>> = Cow::Borrowed("Accept").to_key();
+        assert_eq!(cow.as_str(), "Accept");
+    }
+
+    #[test]
+    fn key_str_as_str() {
+        let key: Key = Key::new("Abc".to_string());
+        let borrowed: &KeyStr = key.borrow();
+        assert_eq!(borrowed.as_str(), "Abc");
+    }
+
+    #[test]
+    fn policies() {
+        let kelvin = Key::<&str, Ascii>::new("\u{212a}");
+        assert_ne!(kelvin, Key::new("K"));
+        let kelvin = Key::<&str, SimpleUnicode>::new("\u{212a}");
+        assert_eq!(kelvin, Key::new("K"));
+        assert_ne!(Key::<&str, SimpleUnicode>::new("Maße"), Key::new("MASSE"));
+        assert_eq!(Key::<&str, FullUnicode>::new("Maße"), Key::new("MASSE"));
+    }
+}
@@ -1,5 +1,29 @@
+pub mod fold;
 pub mod key;
+#[doc(hidden)]
+pub mod literal;
+mod phf;
+pub mod unicase_abbrev_resolver;
 pub mod unicase_btree_map;
 pub mod unicase_btree_set;
+#[cfg(feature = "concurrent")]
+pub mod unicase_concurrent_map;
+pub mod unicase_hash_map;
+pub mod unicase_hash_set;
+#[cfg(feature = "http")]
+pub mod unicase_header_map;
 pub mod unicase_index_map;
 pub mod unicase_index_set;
+pub mod unicase_multi_map;
+#[cfg(feature = "persistent")]
+pub mod unicase_persistent_map;
+#[cfg(feature = "persistent")]
+pub mod unicase_persistent_set;
+pub mod unicase_phf_map;
+pub mod unicase_phf_set;
+#[cfg(feature = "snapshot")]
+pub mod unicase_snapshot_map;
+pub mod unicase_symbol_table;
+pub mod unicase_trie;
+pub mod unicase_vec_map;
+pub mod unicase_vec_set;
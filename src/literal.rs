@@ -0,0 +1,136 @@
+//! Compile-time checks for the keys of the collection literal macros like
+//! [`unicase_btree_map!`](crate::unicase_btree_map!).
+//!
+//! ```
+//! let map = unicase_collections::unicase_index_map! {
+//!     "Content-Type" => 1,
+//!     "Content-Length" => 2,
+//! };
+//! assert_eq!(map["content-type"], 1);
+//! ```
+//!
+//! Keys that only differ by case fail to compile:
+//!
+//! ```compile_fail,E0080
+//! let map = unicase_collections::unicase_index_map! {
+//!     "Content-Type" => 1,
+//!     "content-type" => 2,
+//! };
+//! ```
+
+use crate::fold::{full_fold, Folded};
+
+/// Panics if two of the keys are equal under full Unicode case folding. The macros call
+/// this in a `const` item, so duplicate keys fail the build instead of silently
+/// overwriting each other.
+#[doc(hidden)]
+pub const fn assert_unique_keys(keys: &[&str]) {
+    let mut i = 0;
+    while i < keys.len() {
+        let mut j = i + 1;
+        while j < keys.len() {
+            if full_fold_eq(keys[i], keys[j]) {
+                panic!("two keys of the literal are equal under case folding");
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+}
+
+/// Returns true if `a` and `b` fold to the same chars with [`FullUnicode`](crate::fold::FullUnicode).
+const fn full_fold_eq(a: &str, b: &str) -> bool {
+    let mut a = ConstFolder::new(a);
+    let mut b = ConstFolder::new(b);
+    loop {
+        match (a.next_char(), b.next_char()) {
+            (None, None) => return true,
+            (Some(x), Some(y)) if x as u32 == y as u32 => {}
+            _ => return false,
+        }
+    }
+}
+
+/// Full case folding over the chars of a str, without the iterator adapters that are not
+/// usable in const contexts.
+struct ConstFolder<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    folded: Option<Folded>,
+}
+
+impl<'a> ConstFolder<'a> {
+    const fn new(s: &'a str) -> Self {
+        Self {
+            bytes: s.as_bytes(),
+            pos: 0,
+            folded: None,
+        }
+    }
+
+    const fn next_char(&mut self) -> Option<char> {
+        loop {
+            if let Some(folded) = &mut self.folded {
+                if let Some(c) = folded.next_char() {
+                    return Some(c);
+                }
+            }
+            if self.pos == self.bytes.len() {
+                return None;
+            }
+            let c = self.decode();
+            self.folded = Some(full_fold(c));
+        }
+    }
+
+    /// Decodes the char at `pos` and advances past it. The bytes are valid UTF-8.
+    const fn decode(&mut self) -> char {
+        let bytes = self.bytes;
+        let first = bytes[self.pos] as u32;
+        let (mut c, len) = match first {
+            0x00..=0x7f => (first, 1),
+            0xc0..=0xdf => (first & 0x1f, 2),
+            0xe0..=0xef => (first & 0x0f, 3),
+            _ => (first & 0x07, 4),
+        };
+        let mut i = 1;
+        while i < len {
+            c = (c << 6) | (bytes[self.pos + i] as u32 & 0x3f);
+            i += 1;
+        }
+        self.pos += len;
+        match char::from_u32(c) {
+            Some(c) => c,
+            None => panic!("invalid UTF-8"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{assert_unique_keys, full_fold_eq};
+
+    #[test]
+    fn full_fold_eq_matches_policy() {
+        assert!(full_fold_eq("Content-Type", "content-TYPE"));
+        assert!(full_fold_eq("Maße", "MASSE"));
+        assert!(full_fold_eq("\u{212a}elvin", "kelvin"));
+        assert!(full_fold_eq("ǰ", "J\u{030c}"));
+        assert!(full_fold_eq("", ""));
+        assert!(!full_fold_eq("Maß", "MASSE"));
+        assert!(!full_fold_eq("Å", "A"));
+        assert!(!full_fold_eq("a", ""));
+    }
+
+    #[test]
+    fn unique_keys() {
+        const _: () = assert_unique_keys(&["GET", "PUT", "get-all", "Straße", "straßen"]);
+        assert_unique_keys(&[]);
+    }
+
+    #[test]
+    #[should_panic(expected = "equal under case folding")]
+    fn duplicate_keys() {
+        assert_unique_keys(&["Accept", "STRASSE", "Straße"]);
+    }
+}
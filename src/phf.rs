@@ -0,0 +1,239 @@
+//! Perfect hash tables for the static collections, generated with the hash and displace
+//! algorithm of the phf crates.
+//!
+//! The hash is computed from the folded key, so it must stay the same between the build
+//! script that generates a table and the program that uses it.
+
+use crate::fold::FoldPolicy;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// The average number of keys in a bucket.
+const LAMBDA: usize = 5;
+
+/// The hashes of a key, `g` picks the bucket and `f1` and `f2` the slot in the table.
+pub(crate) struct Hashes {
+    g: u32,
+    f1: u32,
+    f2: u32,
+}
+
+pub(crate) fn hash<P: FoldPolicy>(key: &str, seed: u64) -> Hashes {
+    // FNV-1a over the folded chars, with a SplitMix64 finalizer for the low bits.
+    let mut h = 0xcbf2_9ce4_8422_2325 ^ seed;
+    for c in P::fold(key.chars()) {
+        h = (h ^ c as u64).wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    let h1 = mix(h);
+    let h2 = mix(h1 ^ 0x9e37_79b9_7f4a_7c15);
+    Hashes {
+        g: (h1 >> 32) as u32,
+        f1: h1 as u32,
+        f2: h2 as u32,
+    }
+}
+
+fn mix(mut h: u64) -> u64 {
+    h = (h ^ (h >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    h = (h ^ (h >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    h ^ (h >> 31)
+}
+
+fn displace(f1: u32, f2: u32, d1: u32, d2: u32) -> u32 {
+    d2.wrapping_add(f1.wrapping_mul(d1)).wrapping_add(f2)
+}
+
+/// Returns the slot of the key in a table with `len` slots, which is only the right one
+/// if the key is in the table.
+pub(crate) fn index<P: FoldPolicy>(
+    key: &str,
+    seed: u64,
+    disps: &[(u32, u32)],
+    len: usize,
+) -> Option<usize> {
+    if disps.is_empty() {
+        return None;
+    }
+    let hashes = hash::<P>(key, seed);
+    let (d1, d2) = disps[hashes.g as usize % disps.len()];
+    Some(displace(hashes.f1, hashes.f2, d1, d2) as usize % len)
+}
+
+/// A generated table, where `order[slot]` is the index of the key in that slot.
+pub(crate) struct Table {
+    pub(crate) seed: u64,
+    pub(crate) disps: Vec<(u32, u32)>,
+    pub(crate) order: Vec<usize>,
+}
+
+impl Table {
+    /// Writes the seed and displacements as the first arguments of `from_parts`.
+    pub(crate) fn write_parts(&self, out: &mut String) {
+        write!(out, "{}, &[", self.seed).unwrap();
+        for (d1, d2) in &self.disps {
+            write!(out, "({}, {}), ", d1, d2).unwrap();
+        }
+        out.push(']');
+    }
+}
+
+/// Returns the path of the policy type `P` in the generated code, with the paths into
+/// this crate starting with `crate_path`. The policies of this crate are named through
+/// their re-exports in `fold`, since the modules that define them are private.
+pub(crate) fn policy_path<P>(crate_path: &str) -> String {
+    const CRATE: &str = "unicase_collections::";
+
+    let name = std::any::type_name::<P>();
+    let mut out = String::with_capacity(name.len());
+    let mut rest = name;
+    while let Some(i) = rest.find(CRATE) {
+        let (before, after) = rest.split_at(i);
+        out.push_str(before);
+        let after = &after[CRATE.len()..];
+        if before.ends_with(|c: char| c.is_alphanumeric() || c == '_' || c == ':') {
+            out.push_str(CRATE);
+            rest = after;
+            continue;
+        }
+
+        let len = after
+            .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == ':'))
+            .unwrap_or(after.len());
+        let (path, after) = after.split_at(len);
+        out.push_str(crate_path);
+        match path.strip_prefix("fold::") {
+            Some(path) => {
+                out.push_str("::fold::");
+                out.push_str(path.rsplit("::").next().unwrap());
+            }
+            None => {
+                out.push_str("::");
+                out.push_str(path);
+            }
+        }
+        rest = after;
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Generates a table for the keys.
+///
+/// # Panics
+///
+/// Panics if two keys are equal under the policy.
+pub(crate) fn generate<P: FoldPolicy>(keys: &[&str]) -> Table {
+    let mut folded = HashMap::with_capacity(keys.len());
+    for key in keys {
+        if let Some(other) = folded.insert(P::fold(key.chars()).collect::<String>(), key) {
+            panic!("duplicate key {:?}, which is equal to {:?}", key, other);
+        }
+    }
+
+    (0..)
+        .find_map(|seed| try_generate::<P>(keys, seed))
+        .expect("no seed found")
+}
+
+fn try_generate<P: FoldPolicy>(keys: &[&str], seed: u64) -> Option<Table> {
+    if keys.is_empty() {
+        return Some(Table {
+            seed,
+            disps: Vec::new(),
+            order: Vec::new(),
+        });
+    }
+
+    let hashes: Vec<_> = keys.iter().map(|key| hash::<P>(key, seed)).collect();
+    let buckets_len = keys.len().div_ceil(LAMBDA);
+    let mut buckets: Vec<(usize, Vec<usize>)> = (0..buckets_len).map(|i| (i, Vec::new())).collect();
+    for (i, h) in hashes.iter().enumerate() {
+        buckets[h.g as usize % buckets_len].1.push(i);
+    }
+    // Place the largest buckets first, while most slots are still free.
+    buckets.sort_by_key(|(_, keys)| Reverse(keys.len()));
+
+    let len = keys.len();
+    let mut slots: Vec<Option<usize>> = vec![None; len];
+    let mut disps = vec![(0, 0); buckets_len];
+    // The generation in which a slot was last tried, to find collisions within a bucket.
+    let mut tried = vec![0u64; len];
+    let mut generation = 0;
+    let mut placed = Vec::new();
+
+    'buckets: for (bucket, bucket_keys) in &buckets {
+        for d1 in 0..len as u32 {
+            'disps: for d2 in 0..len as u32 {
+                placed.clear();
+                generation += 1;
+                for &key in bucket_keys {
+                    let h = &hashes[key];
+                    let slot = displace(h.f1, h.f2, d1, d2) as usize % len;
+                    if slots[slot].is_some() || tried[slot] == generation {
+                        continue 'disps;
+                    }
+                    tried[slot] = generation;
+                    placed.push((slot, key));
+                }
+
+                disps[*bucket] = (d1, d2);
+                for &(slot, key) in &placed {
+                    slots[slot] = Some(key);
+                }
+                continue 'buckets;
+            }
+        }
+        return None;
+    }
+
+    Some(Table {
+        seed,
+        disps,
+        order: slots.into_iter().map(|key| key.unwrap()).collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{generate, index, policy_path};
+    use crate::fold::{Ascii, FullUnicode, WidthInsensitive};
+
+    #[test]
+    fn every_key_has_its_own_slot() {
+        let keys: Vec<String> = (0..500).map(|i| format!("Key{}", i)).collect();
+        let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+        let table = generate::<FullUnicode>(&keys);
+        assert_eq!(table.order.len(), keys.len());
+        for (i, key) in keys.iter().enumerate() {
+            let upper = key.to_uppercase();
+            let slot = index::<FullUnicode>(&upper, table.seed, &table.disps, keys.len());
+            assert_eq!(table.order[slot.unwrap()], i);
+        }
+    }
+
+    #[test]
+    fn empty() {
+        let table = generate::<Ascii>(&[]);
+        assert!(table.disps.is_empty());
+        assert_eq!(index::<Ascii>("a", table.seed, &table.disps, 0), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "duplicate key")]
+    fn duplicate_keys() {
+        generate::<FullUnicode>(&["Straße", "STRASSE"]);
+    }
+
+    #[test]
+    fn policy_paths() {
+        assert_eq!(
+            policy_path::<Ascii>("::unicase_collections"),
+            "::unicase_collections::fold::Ascii"
+        );
+        assert_eq!(
+            policy_path::<WidthInsensitive<Ascii>>("crate"),
+            "crate::fold::WidthInsensitive<crate::fold::Ascii>"
+        );
+    }
+}
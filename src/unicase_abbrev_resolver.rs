@@ -0,0 +1,331 @@
+use crate::fold::{FoldPolicy, FullUnicode, InvalidKey};
+use crate::key::{AsKeyStr, Key, ToKey};
+use crate::unicase_index_map::UniCaseIndexMap;
+use crate::unicase_index_set::UniCaseIndexSet;
+use crate::unicase_trie::UniCaseTrie;
+use std::iter::FromIterator;
+
+/// Resolves case-insensitive abbreviations of commands or options, like `stat` for
+/// `status` in VMS/DCL or Cisco IOS style command lines.
+///
+/// Every entry has a minimum abbreviation length, counted in chars of the input, which
+/// defaults to 1. An input shorter than that only matches the entry if it is the whole
+/// key. Build a resolver without values from a [`UniCaseIndexSet`], or with values from
+/// a [`UniCaseIndexMap`].
+#[derive(Debug, Clone)]
+pub struct UniCaseAbbrevResolver<V = (), S = String, P = FullUnicode> {
+    /// The entries with their minimum abbreviation length.
+    entries: UniCaseTrie<(V, usize), S, P>,
+}
+
+/// The result of resolving an input with a [`UniCaseAbbrevResolver`].
+#[derive(Debug, Clone)]
+pub enum Resolution<'a, V, S, P> {
+    /// The input is a whole key.
+    Exact(&'a Key<S, P>, &'a V),
+    /// The input abbreviates exactly one key.
+    UniquePrefix(&'a Key<S, P>, &'a V),
+    /// The input abbreviates several keys, which are given in folded key order.
+    Ambiguous(Vec<(&'a Key<S, P>, &'a V)>),
+    /// The input does not abbreviate any key.
+    NotFound,
+}
+
+impl<'a, V, S, P> Resolution<'a, V, S, P> {
+    /// Returns the matched key and value of an exact or unique match.
+    pub fn matched(&self) -> Option<(&'a Key<S, P>, &'a V)> {
+        match *self {
+            Resolution::Exact(k, v) | Resolution::UniquePrefix(k, v) => Some((k, v)),
+            Resolution::Ambiguous(_) | Resolution::NotFound => None,
+        }
+    }
+}
+
+impl<V, S, P> Default for UniCaseAbbrevResolver<V, S, P> {
+    fn default() -> Self {
+        Self {
+            entries: Default::default(),
+        }
+    }
+}
+
+impl<K, V, S, P> Extend<(K, V)> for UniCaseAbbrevResolver<V, S, P>
+where
+    K: ToKey<S, P>,
+    S: AsRef<str>,
+    P: FoldPolicy,
+{
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl<K, V, S, P> FromIterator<(K, V)> for UniCaseAbbrevResolver<V, S, P>
+where
+    K: ToKey<S, P>,
+    S: AsRef<str>,
+    P: FoldPolicy,
+{
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut resolver = Self::default();
+        resolver.extend(iter);
+        resolver
+    }
+}
+
+impl<V, S, P, H> From<UniCaseIndexMap<V, S, P, H>> for UniCaseAbbrevResolver<V, S, P>
+where
+    S: AsRef<str>,
+    P: FoldPolicy,
+{
+    fn from(map: UniCaseIndexMap<V, S, P, H>) -> Self {
+        map.into_iter().collect()
+    }
+}
+
+impl<S, P, H> From<UniCaseIndexSet<S, P, H>> for UniCaseAbbrevResolver<(), S, P>
+where
+    S: AsRef<str>,
+    P: FoldPolicy,
+{
+    fn from(set: UniCaseIndexSet<S, P, H>) -> Self {
+        set.into_iter().map(|k| (k, ())).collect()
+    }
+}
+
+impl<V> UniCaseAbbrevResolver<V> {
+    /// Creates a new UniCaseAbbrevResolver.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<V, S, P> UniCaseAbbrevResolver<V, S, P>
+where
+    S: AsRef<str>,
+    P: FoldPolicy,
+{
+    /// Returns true if the resolver contains the whole key.
+    /// The key may be a String, str or UniCase value.
+    pub fn contains_key<K: AsKeyStr>(&self, k: K) -> bool {
+        self.entries.contains_key(k)
+    }
+
+    /// Returns a reference to the value corresponding to the whole key.
+    /// The key may be a String, str or UniCase value.
+    pub fn get<K: AsKeyStr>(&self, k: K) -> Option<&V> {
+        self.entries.get(k).map(|(v, _)| v)
+    }
+
+    /// Inserts a key that can be abbreviated down to a single char.
+    /// If the resolver did have this key present, the value is updated, and the old value
+    /// is returned. The minimum abbreviation length is kept.
+    pub fn insert<K: ToKey<S, P>>(&mut self, k: K, v: V) -> Option<V> {
+        let key = k.to_key();
+        if let Some((old, _)) = self.entries.get_mut(&key) {
+            return Some(std::mem::replace(old, v));
+        }
+        self.entries.insert(key, (v, 1));
+        None
+    }
+
+    /// Inserts a key that can only be abbreviated down to `min_len` chars, e.g. 4 for
+    /// `STATus`. If the resolver did have this key present, the value and the minimum
+    /// length are updated, and the old value is returned.
+    pub fn insert_with_min_len<K: ToKey<S, P>>(&mut self, k: K, v: V, min_len: usize) -> Option<V> {
+        self.entries.insert(k, (v, min_len)).map(|(v, _)| v)
+    }
+
+    /// Returns true if the resolver contains no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the number of entries in the resolver.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns the minimum abbreviation length of the key.
+    /// The key may be a String, str or UniCase value.
+    pub fn min_len<K: AsKeyStr>(&self, k: K) -> Option<usize> {
+        self.entries.get(k).map(|&(_, min_len)| min_len)
+    }
+
+    /// Removes a key from the resolver, returning its value if the key was present.
+    /// The key may be a String, str or UniCase value.
+    pub fn remove<K: AsKeyStr>(&mut self, k: K) -> Option<V> {
+        self.entries.remove(k).map(|(v, _)| v)
+    }
+
+    /// Resolves the input to the key it names. A whole key always wins over longer keys
+    /// it abbreviates, and an empty input only matches an empty key.
+    /// The input may be a String, str or UniCase value.
+    pub fn resolve<K: AsKeyStr>(&self, input: K) -> Resolution<'_, V, S, P> {
+        let input = input.as_key_str::<P>();
+        if let Some((k, (v, _))) = self.entries.get_key_value(input) {
+            return Resolution::Exact(k, v);
+        }
+        let len = input.as_str().chars().count();
+        if len == 0 {
+            return Resolution::NotFound;
+        }
+
+        let mut candidates: Vec<_> = self
+            .entries
+            .iter_prefix(input)
+            .filter(|(_, (_, min_len))| len >= *min_len)
+            .map(|(k, (v, _))| (k, v))
+            .collect();
+        match candidates.len() {
+            0 => Resolution::NotFound,
+            1 => {
+                let (k, v) = candidates.remove(0);
+                Resolution::UniquePrefix(k, v)
+            }
+            _ => Resolution::Ambiguous(candidates),
+        }
+    }
+
+    /// Sets the minimum abbreviation length of the key. Returns false if the key is not
+    /// present.
+    /// The key may be a String, str or UniCase value.
+    pub fn set_min_len<K: AsKeyStr>(&mut self, k: K, min_len: usize) -> bool {
+        match self.entries.get_mut(k) {
+            Some((_, old)) => {
+                *old = min_len;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Inserts a key like [`insert`](Self::insert), but first checks the key with
+    /// [`FoldPolicy::validate`] and returns an error if the policy rejects it.
+    pub fn try_insert<K: ToKey<S, P>>(&mut self, k: K, v: V) -> Result<Option<V>, InvalidKey> {
+        let key = k.to_key();
+        P::validate(key.as_str())?;
+        Ok(self.insert(key, v))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Resolution, UniCaseAbbrevResolver};
+    use crate::fold::FullUnicode;
+    use crate::unicase_index_map::UniCaseIndexMap;
+    use crate::unicase_index_set::UniCaseIndexSet;
+
+    fn commands() -> UniCaseAbbrevResolver {
+        let set: UniCaseIndexSet = ["status", "stash", "Start", "show", "help"]
+            .into_iter()
+            .collect();
+        UniCaseAbbrevResolver::from(set)
+    }
+
+    fn resolved(resolution: Resolution<'_, (), String, FullUnicode>) -> String {
+        match resolution {
+            Resolution::Exact(k, _) => format!("exact {}", k),
+            Resolution::UniquePrefix(k, _) => format!("prefix {}", k),
+            Resolution::Ambiguous(candidates) => {
+                let keys: Vec<_> = candidates.iter().map(|(k, _)| k.as_str()).collect();
+                format!("ambiguous {}", keys.join(","))
+            }
+            Resolution::NotFound => "not found".to_string(),
+        }
+    }
+
+    #[test]
+    fn resolve() {
+        let resolver = commands();
+        assert_eq!(resolved(resolver.resolve("status")), "exact status");
+        assert_eq!(resolved(resolver.resolve("STAT")), "prefix status");
+        assert_eq!(resolved(resolver.resolve("stas")), "prefix stash");
+        assert_eq!(resolved(resolver.resolve("H")), "prefix help");
+        assert_eq!(
+            resolved(resolver.resolve("sta")),
+            "ambiguous Start,stash,status"
+        );
+        assert_eq!(
+            resolved(resolver.resolve("s")),
+            "ambiguous show,Start,stash,status"
+        );
+        assert_eq!(resolved(resolver.resolve("statuses")), "not found");
+        assert_eq!(resolved(resolver.resolve("x")), "not found");
+        assert_eq!(resolved(resolver.resolve("")), "not found");
+    }
+
+    #[test]
+    fn exact_wins_over_longer_keys() {
+        let mut resolver = commands();
+        resolver.insert("Stat", ());
+        assert_eq!(resolved(resolver.resolve("stat")), "exact Stat");
+        assert_eq!(resolved(resolver.resolve("statu")), "prefix status");
+    }
+
+    #[test]
+    fn min_len() {
+        let mut resolver = commands();
+        assert!(resolver.set_min_len("status", 4));
+        assert!(resolver.set_min_len("SHOW", 2));
+        assert!(!resolver.set_min_len("list", 2));
+        assert_eq!(resolver.min_len("Status"), Some(4));
+        assert_eq!(resolver.min_len("help"), Some(1));
+
+        assert_eq!(resolved(resolver.resolve("sta")), "ambiguous Start,stash");
+        assert_eq!(resolved(resolver.resolve("stat")), "prefix status");
+        assert_eq!(resolved(resolver.resolve("s")), "ambiguous Start,stash");
+        assert_eq!(resolved(resolver.resolve("sh")), "prefix show");
+        assert!(resolver.set_min_len("start", 2));
+        assert!(resolver.set_min_len("stash", 2));
+        assert_eq!(resolved(resolver.resolve("s")), "not found");
+    }
+
+    #[test]
+    fn min_len_counts_chars() {
+        let mut resolver = UniCaseAbbrevResolver::new();
+        resolver.insert_with_min_len("Äpfel", 1, 2);
+        assert_eq!(resolver.resolve("ä").matched(), None);
+        assert_eq!(resolver.resolve("äp").matched().map(|(_, v)| *v), Some(1));
+    }
+
+    #[test]
+    fn from_index_map() {
+        let mut map = UniCaseIndexMap::new();
+        map.insert("Delete", 1);
+        map.insert("Describe", 2);
+        let resolver = UniCaseAbbrevResolver::from(map);
+        assert_eq!(resolver.len(), 2);
+        assert_eq!(resolver.resolve("del").matched().map(|(_, v)| *v), Some(1));
+        assert_eq!(resolver.resolve("DES").matched().map(|(_, v)| *v), Some(2));
+        assert_eq!(resolver.resolve("de").matched(), None);
+        assert!(matches!(resolver.resolve("de"), Resolution::Ambiguous(c) if c.len() == 2));
+    }
+
+    #[test]
+    fn insert_and_remove() {
+        let mut resolver = UniCaseAbbrevResolver::new();
+        assert_eq!(resolver.insert_with_min_len("Quit", 1, 2), None);
+        assert_eq!(resolver.insert("QUIT", 2), Some(1));
+        assert_eq!(resolver.min_len("quit"), Some(2));
+        assert_eq!(resolver.get("quit"), Some(&2));
+        assert!(resolver.contains_key("Quit"));
+        assert_eq!(resolver.remove("quit"), Some(2));
+        assert!(resolver.is_empty());
+    }
+
+    #[test]
+    fn try_insert() {
+        use crate::fold::{Identifier, InvalidKey};
+
+        let mut resolver = UniCaseAbbrevResolver::<u8, String, Identifier>::default();
+        assert_eq!(resolver.try_insert("list", 1), Ok(None));
+        assert_eq!(
+            resolver.try_insert("list-all", 2),
+            Err(InvalidKey::at(4, '-'))
+        );
+        assert_eq!(resolver.len(), 1);
+    }
+}
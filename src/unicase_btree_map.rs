@@ -1,106 +1,137 @@
-use crate::key::{Key, ToKey};
+use crate::fold::{FoldPolicy, FullUnicode, InvalidKey};
+use crate::key::{AsKeyStr, Key, ToKey};
 use std::collections::btree_map::{Entry, IntoIter, Iter, IterMut, Keys, Values, ValuesMut};
 use std::collections::BTreeMap;
 use std::iter::FromIterator;
 use std::ops::Index;
 
-#[derive(Debug, Default, Clone)]
-pub struct UniCaseBTreeMap<V> {
-    inner: BTreeMap<Key, V>,
+#[derive(Debug, Clone)]
+pub struct UniCaseBTreeMap<V, S = String, P = FullUnicode> {
+    inner: BTreeMap<Key<S, P>, V>,
 }
 
-impl<V> Eq for UniCaseBTreeMap<V> where V: Eq {}
+impl<V, S, P> Default for UniCaseBTreeMap<V, S, P> {
+    fn default() -> Self {
+        Self {
+            inner: Default::default(),
+        }
+    }
+}
+
+impl<V, S, P> Eq for UniCaseBTreeMap<V, S, P>
+where
+    V: Eq,
+    S: AsRef<str>,
+    P: FoldPolicy,
+{
+}
 
-impl<V> PartialEq for UniCaseBTreeMap<V>
+impl<V, S, P> PartialEq for UniCaseBTreeMap<V, S, P>
 where
     V: PartialEq,
+    S: AsRef<str>,
+    P: FoldPolicy,
 {
-    fn eq(&self, other: &UniCaseBTreeMap<V>) -> bool {
+    fn eq(&self, other: &UniCaseBTreeMap<V, S, P>) -> bool {
         if self.len() != other.len() {
             return false;
         }
 
         self.iter()
-            .all(|(key, value)| other.get(&***key).map_or(false, |v| *value == *v))
+            .all(|(key, value)| other.get(key) == Some(value))
     }
 }
 
-impl<K, V> Extend<(K, V)> for UniCaseBTreeMap<V>
+impl<K, V, S, P> Extend<(K, V)> for UniCaseBTreeMap<V, S, P>
 where
-    K: ToKey,
+    K: ToKey<S, P>,
+    S: AsRef<str>,
+    P: FoldPolicy,
 {
     fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
-        // Transform the keys into UniCases.
+        // Transform the keys into `Key`s.
         let iter = iter.into_iter().map(|(k, v)| (k.to_key(), v));
         self.inner.extend(iter);
     }
 }
 
-impl<'a, K, V> Extend<(K, &'a V)> for UniCaseBTreeMap<V>
+impl<'a, K, V, S, P> Extend<(K, &'a V)> for UniCaseBTreeMap<V, S, P>
 where
-    K: ToKey,
+    K: ToKey<S, P>,
+    S: AsRef<str>,
+    P: FoldPolicy,
     V: Copy,
 {
     fn extend<T: IntoIterator<Item = (K, &'a V)>>(&mut self, iter: T) {
-        // Transform the keys into UniCases and copy the values.
+        // Transform the keys into `Key`s and copy the values.
         let iter = iter.into_iter().map(|(k, v)| (k.to_key(), *v));
         self.inner.extend(iter);
     }
 }
 
-impl<K, V> FromIterator<(K, V)> for UniCaseBTreeMap<V>
+impl<K, V, S, P> FromIterator<(K, V)> for UniCaseBTreeMap<V, S, P>
 where
-    K: ToKey,
+    K: ToKey<S, P>,
+    S: AsRef<str>,
+    P: FoldPolicy,
 {
     fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
-        let mut map = Self::new();
+        let mut map = Self::default();
         map.extend(iter);
         map
     }
 }
 
-impl<'a, V> IntoIterator for &'a UniCaseBTreeMap<V> {
-    type Item = (&'a Key, &'a V);
-    type IntoIter = Iter<'a, Key, V>;
+impl<'a, V, S, P> IntoIterator for &'a UniCaseBTreeMap<V, S, P>
+where
+    S: AsRef<str>,
+    P: FoldPolicy,
+{
+    type Item = (&'a Key<S, P>, &'a V);
+    type IntoIter = Iter<'a, Key<S, P>, V>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.iter()
     }
 }
 
-impl<'a, V> IntoIterator for &'a mut UniCaseBTreeMap<V> {
-    type Item = (&'a Key, &'a mut V);
-    type IntoIter = IterMut<'a, Key, V>;
+impl<'a, V, S, P> IntoIterator for &'a mut UniCaseBTreeMap<V, S, P>
+where
+    S: AsRef<str>,
+    P: FoldPolicy,
+{
+    type Item = (&'a Key<S, P>, &'a mut V);
+    type IntoIter = IterMut<'a, Key<S, P>, V>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.iter_mut()
     }
 }
 
-impl<V> IntoIterator for UniCaseBTreeMap<V> {
-    type Item = (Key, V);
-    type IntoIter = IntoIter<Key, V>;
+impl<V, S, P> IntoIterator for UniCaseBTreeMap<V, S, P> {
+    type Item = (Key<S, P>, V);
+    type IntoIter = IntoIter<Key<S, P>, V>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.inner.into_iter()
     }
 }
 
-impl<'a, K, V> Index<K> for UniCaseBTreeMap<V>
+impl<K, V, S, P> Index<K> for UniCaseBTreeMap<V, S, P>
 where
-    K: ToKey,
+    K: AsKeyStr,
+    S: AsRef<str>,
+    P: FoldPolicy,
 {
     type Output = V;
 
     fn index(&self, index: K) -> &Self::Output {
-        let key = index.to_key();
-        self.inner.index(&key)
+        self.inner.index(index.as_key_str::<P>())
     }
 }
 
 impl<V> UniCaseBTreeMap<V> {
-    /// Creates a new UniCaseBTreeMap with the default
-    /// hasher and capacity.
+    /// Creates a new, empty UniCaseBTreeMap.
     pub fn new() -> Self {
         Self {
             inner: Default::default(),
@@ -108,7 +139,11 @@ impl<V> UniCaseBTreeMap<V> {
     }
 }
 
-impl<V> UniCaseBTreeMap<V> {
+impl<V, S, P> UniCaseBTreeMap<V, S, P>
+where
+    S: AsRef<str>,
+    P: FoldPolicy,
+{
     /// Clears the map, removing all key-value pairs. Keeps the allocated memory for reuse.
     pub fn clear(&mut self) {
         self.inner.clear();
@@ -116,35 +151,31 @@ impl<V> UniCaseBTreeMap<V> {
 
     /// Returns true if the map contains a value for the specified key.
     /// The key may be a String, str or UniCase value.
-    pub fn contains_key<K: ToKey>(&self, k: K) -> bool {
-        let key = k.to_key();
-        self.inner.contains_key(&key)
+    pub fn contains_key<K: AsKeyStr>(&self, k: K) -> bool {
+        self.inner.contains_key(k.as_key_str::<P>())
     }
 
     /// Gets the given key's corresponding entry in the map for in-place manipulation.
-    pub fn entry<K: ToKey>(&mut self, k: K) -> Entry<'_, Key, V> {
+    pub fn entry<K: ToKey<S, P>>(&mut self, k: K) -> Entry<'_, Key<S, P>, V> {
         let key = k.to_key();
         self.inner.entry(key)
     }
 
     /// Returns a reference to the value corresponding to the key.
-    pub fn get<K: ToKey>(&self, k: K) -> Option<&V> {
-        let key = k.to_key();
-        self.inner.get(&key)
+    pub fn get<K: AsKeyStr>(&self, k: K) -> Option<&V> {
+        self.inner.get(k.as_key_str::<P>())
     }
 
     /// Returns the key-value pair corresponding to the supplied key.
     /// The key may be a String, str or UniCase value.
-    pub fn get_key_value<K: ToKey>(&self, k: K) -> Option<(&Key, &V)> {
-        let key = k.to_key();
-        self.inner.get_key_value(&key)
+    pub fn get_key_value<K: AsKeyStr>(&self, k: K) -> Option<(&Key<S, P>, &V)> {
+        self.inner.get_key_value(k.as_key_str::<P>())
     }
 
     /// Returns a mutable reference to the value corresponding to the key.
     /// The key may be a String, str or UniCase value.
-    pub fn get_mut<K: ToKey>(&mut self, k: K) -> Option<&mut V> {
-        let key = k.to_key();
-        self.inner.get_mut(&key)
+    pub fn get_mut<K: AsKeyStr>(&mut self, k: K) -> Option<&mut V> {
+        self.inner.get_mut(k.as_key_str::<P>())
     }
 
     /// Inserts a key-value pair into the map.
@@ -153,7 +184,7 @@ impl<V> UniCaseBTreeMap<V> {
     /// The key is not updated, though; this matters for types that can be == without being identical.
     /// See the module-level documentation of [BTreeMap](https://doc.rust-lang.org/std/collections/index.html#insert-and-complex-keys)
     // for more.
-    pub fn insert<K: ToKey>(&mut self, k: K, v: V) -> Option<V> {
+    pub fn insert<K: ToKey<S, P>>(&mut self, k: K, v: V) -> Option<V> {
         let key = k.to_key();
         self.inner.insert(key, v)
     }
@@ -163,21 +194,21 @@ impl<V> UniCaseBTreeMap<V> {
         self.inner.is_empty()
     }
 
-    /// An iterator visiting all key-value pairs in arbitrary order.
-    /// The iterator element type is (&'a UniCase<String>, &'a V).
-    pub fn iter(&self) -> Iter<Key, V> {
+    /// An iterator visiting all key-value pairs in folded key order.
+    /// The iterator element type is (&'a Key<S, P>, &'a V).
+    pub fn iter(&self) -> Iter<'_, Key<S, P>, V> {
         self.inner.iter()
     }
 
-    /// An iterator visiting all key-value pairs in arbitrary order,
+    /// An iterator visiting all key-value pairs in folded key order,
     /// with mutable references to the values. The iterator element type is
-    /// (&'a UniCase<String>, &'a mut V).
-    pub fn iter_mut(&mut self) -> IterMut<Key, V> {
+    /// (&'a Key<S, P>, &'a mut V).
+    pub fn iter_mut(&mut self) -> IterMut<'_, Key<S, P>, V> {
         self.inner.iter_mut()
     }
 
-    /// An iterator visiting all keys in arbitrary order. The iterator element type is &'a UniCase<String>.
-    pub fn keys(&self) -> Keys<Key, V> {
+    /// An iterator visiting all keys in folded key order. The iterator element type is &'a Key<S, P>.
+    pub fn keys(&self) -> Keys<'_, Key<S, P>, V> {
         self.inner.keys()
     }
 
@@ -188,47 +219,76 @@ impl<V> UniCaseBTreeMap<V> {
 
     /// Removes a key from the map, returning the value at the key if the key was previously in the map.
     /// The key may be a String, str or UniCase value.
-    pub fn remove<K: ToKey>(&mut self, k: K) -> Option<V> {
-        let key = k.to_key();
-        self.inner.remove(&key)
+    pub fn remove<K: AsKeyStr>(&mut self, k: K) -> Option<V> {
+        self.inner.remove(k.as_key_str::<P>())
     }
 
     /// Removes a key from the map, returning the stored key and value if the key was previously in the map.
     /// The key may be a String, str or UniCase value.
-    pub fn remove_entry<K: ToKey>(&mut self, k: K) -> Option<(Key, V)> {
-        let key = k.to_key();
-        self.inner.remove_entry(&key)
+    pub fn remove_entry<K: AsKeyStr>(&mut self, k: K) -> Option<(Key<S, P>, V)> {
+        self.inner.remove_entry(k.as_key_str::<P>())
     }
 
     /// Retains only the elements specified by the predicate.
     /// In other words, remove all pairs (k, v) such that f(&k,&mut v) returns false.
     pub fn retain<F>(&mut self, f: F)
     where
-        F: FnMut(&Key, &mut V) -> bool,
+        F: FnMut(&Key<S, P>, &mut V) -> bool,
     {
         self.inner.retain(f);
     }
 
-    /// An iterator visiting all values in arbitrary order. The iterator element type is &'a V.
-    pub fn values(&self) -> Values<Key, V> {
+    /// Inserts a key-value pair into the map like [`insert`](Self::insert), but first
+    /// checks the key with [`FoldPolicy::validate`] and returns an error if the policy
+    /// rejects it.
+    pub fn try_insert<K: ToKey<S, P>>(&mut self, k: K, v: V) -> Result<Option<V>, InvalidKey> {
+        let key = k.to_key();
+        P::validate(key.as_str())?;
+        Ok(self.inner.insert(key, v))
+    }
+
+    /// An iterator visiting all values in folded key order. The iterator element type is &'a V.
+    pub fn values(&self) -> Values<'_, Key<S, P>, V> {
         self.inner.values()
     }
 
-    /// An iterator visiting all values mutably in arbitrary order. The iterator element type is &'a mut V.
-    pub fn values_mut(&mut self) -> ValuesMut<Key, V> {
+    /// An iterator visiting all values mutably in folded key order. The iterator element type is &'a mut V.
+    pub fn values_mut(&mut self) -> ValuesMut<'_, Key<S, P>, V> {
         self.inner.values_mut()
     }
 }
 
+/// Creates a [`UniCaseBTreeMap`] with the default key type and policy from `key => value` pairs.
+///
+/// The keys must be string literals. Two keys that are equal under case folding, like
+/// `"Foo"` and `"FOO"`, are a compile error instead of silently overwriting each other.
+#[macro_export]
+macro_rules! unicase_btree_map {
+    ($($key:literal => $value:expr),* $(,)?) => {{
+        const KEYS: &[&str] = &[$($key),*];
+        const _: () = $crate::literal::assert_unique_keys(KEYS);
+        #[allow(unused_mut)]
+        let mut map = $crate::unicase_btree_map::UniCaseBTreeMap::new();
+        $(map.insert($key, $value);)*
+        map
+    }};
+}
+
 #[cfg(test)]
 mod tests {
     use super::UniCaseBTreeMap;
+    use crate::fold::Ascii;
+    use crate::key::Key;
     use unicase::UniCase;
 
     #[test]
     fn new() {
         let map = UniCaseBTreeMap::<u8>::new();
         assert_eq!(map.len(), 0);
+
+        let mut map = UniCaseBTreeMap::<u8, &str, Ascii>::default();
+        map.insert("\u{212a}", 1);
+        assert_eq!(map.get("k"), None);
     }
 
     #[test]
@@ -269,7 +329,7 @@ mod tests {
         let mut map = UniCaseBTreeMap::<u8>::new();
         map.insert("A", 1);
         let entry = map.entry("A");
-        assert_eq!(entry.key(), &UniCase::new("A".to_string()));
+        assert_eq!(entry.key(), &Key::new("A".to_string()));
     }
 
     #[test]
@@ -293,7 +353,6 @@ mod tests {
     fn get_unicase() {
         let mut map = UniCaseBTreeMap::<u8>::new();
         map.insert("A", 1);
-        // Won't work with plain &str, which is annoying.
         let uc = UniCase::new("a".to_string());
         assert_eq!(map.get(uc).unwrap(), &1);
     }
@@ -302,7 +361,6 @@ mod tests {
     fn get_unicase_ref() {
         let mut map = UniCaseBTreeMap::<u8>::new();
         map.insert("A", 1);
-        // Won't work with plain &str, which is annoying.
         let uc = UniCase::new("a".to_string());
         assert_eq!(map.get(&uc).unwrap(), &1);
     }
@@ -312,7 +370,7 @@ mod tests {
         let mut map = UniCaseBTreeMap::<u8>::new();
         map.insert("A", 1);
         let result = map.get_key_value("a");
-        assert_eq!(result.unwrap().0, &UniCase::new("a".to_string()));
+        assert_eq!(result.unwrap().0, &Key::new("a".to_string()));
         assert_eq!(result.unwrap().1, &1);
     }
 
@@ -359,7 +417,7 @@ mod tests {
         map.insert("A".to_string(), 1);
         map.insert("B".to_string(), 2);
 
-        let mut elems: Vec<_> = map.iter().map(|(_, v)| v.clone()).collect();
+        let mut elems: Vec<_> = map.iter().map(|(_, v)| *v).collect();
         elems.sort();
         assert_eq!(elems, vec![1, 2]);
     }
@@ -374,7 +432,7 @@ mod tests {
             *v += 10;
         }
 
-        let mut elems: Vec<_> = map.iter().map(|(_, v)| v.clone()).collect();
+        let mut elems: Vec<_> = map.iter().map(|(_, v)| *v).collect();
         elems.sort();
         assert_eq!(elems, vec![11, 12]);
     }
@@ -389,10 +447,7 @@ mod tests {
         keys.sort();
         assert_eq!(
             keys,
-            vec![
-                &UniCase::new("A".to_string()),
-                &UniCase::new("B".to_string())
-            ]
+            vec![&Key::new("A".to_string()), &Key::new("B".to_string())]
         );
     }
 
@@ -525,4 +580,122 @@ mod tests {
 
         let _map: UniCaseBTreeMap<u8> = v.into_iter().collect();
     }
+
+    #[test]
+    fn static_str_keys() {
+        let mut map = UniCaseBTreeMap::<u8, &'static str>::default();
+        map.insert("Content-Type", 1);
+        map.insert("CONTENT-TYPE", 2);
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.get("content-type"), Some(&2));
+        assert_eq!(map.keys().next().map(|k| **k), Some("Content-Type"));
+    }
+
+    #[test]
+    fn arc_str_keys() {
+        let shared: std::sync::Arc<str> = "Shared".into();
+        let mut map = UniCaseBTreeMap::<u8, std::sync::Arc<str>>::default();
+        map.insert(UniCase::new(shared.clone()), 1);
+        map.insert("Other".to_string(), 2);
+        assert_eq!(map.get("SHARED"), Some(&1));
+        assert_eq!(std::sync::Arc::strong_count(&shared), 2);
+    }
+
+    #[test]
+    fn ascii_policy() {
+        use crate::fold::Ascii;
+
+        let mut map = UniCaseBTreeMap::<u8, String, Ascii>::default();
+        map.insert("K", 1);
+        map.insert("\u{212a}", 2);
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get("k"), Some(&1));
+    }
+
+    #[test]
+    fn full_folding_expansions() {
+        let mut map = UniCaseBTreeMap::<u8>::new();
+        map.insert("straße", 1);
+        map.insert("STRASSE", 2);
+        map.insert("strasz", 3);
+        map.insert("strasr", 4);
+        map.insert("ﬁx", 5);
+        map.insert("FIX", 6);
+        assert_eq!(map.len(), 4);
+        assert_eq!(map.get("Strasse"), Some(&2));
+        assert_eq!(map.get("fix"), Some(&6));
+
+        let keys: Vec<_> = map.keys().map(|k| k.as_str()).collect();
+        assert_eq!(keys, vec!["ﬁx", "strasr", "straße", "strasz"]);
+    }
+
+    #[test]
+    fn normalized_policy() {
+        use crate::fold::Normalized;
+
+        let mut map = UniCaseBTreeMap::<u8, String, Normalized>::default();
+        map.insert("\u{00c5}ngström", 1);
+        assert_eq!(map.get("A\u{030a}NGSTRO\u{0308}M"), Some(&1));
+        assert_eq!(map.get("\u{212b}ngström"), Some(&1));
+        assert_eq!(map.keys().next().unwrap().as_str(), "\u{00c5}ngström");
+    }
+
+    #[test]
+    fn accent_insensitive_policy() {
+        use crate::fold::AccentInsensitive;
+
+        let words = ["cafés", "CAFE", "Cafeteria", "café", "cafe", "Čaj"];
+        let mut forward = UniCaseBTreeMap::<usize, String, AccentInsensitive>::default();
+        let mut backward = UniCaseBTreeMap::<usize, String, AccentInsensitive>::default();
+        for (i, word) in words.iter().enumerate() {
+            forward.insert(*word, i);
+        }
+        for (i, word) in words.iter().enumerate().rev() {
+            backward.insert(*word, i);
+        }
+
+        assert_eq!(forward.len(), 4);
+        assert_eq!(forward.get("CAFÉ"), Some(&4));
+        assert_eq!(backward.get("CAFÉ"), Some(&1));
+
+        let order = |map: &UniCaseBTreeMap<usize, String, AccentInsensitive>| {
+            map.keys()
+                .map(|k| k.as_str().to_lowercase())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(order(&forward), vec!["cafe", "cafés", "cafeteria", "čaj"]);
+        assert_eq!(order(&forward), order(&backward));
+    }
+
+    #[test]
+    fn tailored_policy() {
+        use crate::fold::{Lt, Tailored};
+
+        let mut map = UniCaseBTreeMap::<u8, String, Tailored<Lt>>::default();
+        map.insert("\u{00cc}", 1);
+        assert_eq!(map.get("i\u{0307}\u{0300}"), Some(&1));
+    }
+
+    #[test]
+    fn try_insert() {
+        let mut map = UniCaseBTreeMap::new();
+        assert_eq!(map.try_insert("a-b", 1), Ok(None));
+        assert_eq!(map.try_insert("A-B", 2), Ok(Some(1)));
+        assert_eq!(map.get("a-b"), Some(&2));
+    }
+
+    #[test]
+    fn literal_macro() {
+        let map = crate::unicase_btree_map! {
+            "b" => 2,
+            "A" => 1,
+        };
+        assert_eq!(map.get("a"), Some(&1));
+        assert_eq!(map.get("B"), Some(&2));
+        let keys: Vec<_> = map.keys().map(|k| k.as_str()).collect();
+        assert_eq!(keys, vec!["A", "b"]);
+
+        let empty: UniCaseBTreeMap<u8> = crate::unicase_btree_map! {};
+        assert!(empty.is_empty());
+    }
 }
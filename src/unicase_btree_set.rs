@@ -1,57 +1,78 @@
-use crate::key::{Key, ToKey};
+use crate::fold::{FoldPolicy, FullUnicode, InvalidKey};
+use crate::key::{AsKeyStr, Key, ToKey};
 use std::collections::btree_set::{IntoIter, Iter};
 use std::collections::BTreeSet;
 use std::iter::FromIterator;
 
-#[derive(Debug, Default, Clone)]
-pub struct UniCaseBTreeSet {
-    inner: BTreeSet<Key>,
+#[derive(Debug, Clone)]
+pub struct UniCaseBTreeSet<S = String, P = FullUnicode> {
+    inner: BTreeSet<Key<S, P>>,
 }
 
-impl PartialEq for UniCaseBTreeSet {
-    fn eq(&self, other: &UniCaseBTreeSet) -> bool {
+impl<S, P> Default for UniCaseBTreeSet<S, P> {
+    fn default() -> Self {
+        Self {
+            inner: Default::default(),
+        }
+    }
+}
+
+impl<S, P> PartialEq for UniCaseBTreeSet<S, P>
+where
+    S: AsRef<str>,
+    P: FoldPolicy,
+{
+    fn eq(&self, other: &UniCaseBTreeSet<S, P>) -> bool {
         if self.len() != other.len() {
             return false;
         }
 
-        self.iter().all(|key| other.contains(key.as_ref()))
+        self.iter().all(|key| other.contains(key))
     }
 }
 
-impl<K> Extend<K> for UniCaseBTreeSet
+impl<K, S, P> Extend<K> for UniCaseBTreeSet<S, P>
 where
-    K: ToKey,
+    K: ToKey<S, P>,
+    S: AsRef<str>,
+    P: FoldPolicy,
 {
     fn extend<T: IntoIterator<Item = K>>(&mut self, iter: T) {
-        // Transform the keys into UniCases.
+        // Transform the keys into `Key`s.
         let iter = iter.into_iter().map(|k| k.to_key());
         self.inner.extend(iter);
     }
 }
 
-impl<K> FromIterator<K> for UniCaseBTreeSet
+impl<K, S, P> FromIterator<K> for UniCaseBTreeSet<S, P>
 where
-    K: ToKey,
+    K: ToKey<S, P>,
+    S: AsRef<str>,
+    P: FoldPolicy,
 {
     fn from_iter<T: IntoIterator<Item = K>>(iter: T) -> Self {
-        let mut map = Self::new();
+        let mut map = Self::default();
         map.extend(iter);
         map
     }
 }
 
-impl<'a> IntoIterator for &'a UniCaseBTreeSet {
-    type Item = &'a Key;
-    type IntoIter = Iter<'a, Key>;
+impl<'a, S, P> IntoIterator for &'a UniCaseBTreeSet<S, P>
+where
+    S: AsRef<str>,
+    P: FoldPolicy,
+{
+    type Item = &'a Key<S, P>;
+    type IntoIter = Iter<'a, Key<S, P>>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.iter()
     }
 }
 
-impl IntoIterator for UniCaseBTreeSet {
-    type Item = Key;
-    type IntoIter = IntoIter<Key>;
+impl<S, P> IntoIterator for UniCaseBTreeSet<S, P> {
+    type Item = Key<S, P>;
+    type IntoIter = IntoIter<Key<S, P>>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.inner.into_iter()
@@ -59,8 +80,7 @@ impl IntoIterator for UniCaseBTreeSet {
 }
 
 impl UniCaseBTreeSet {
-    /// Creates a new UniCaseBTreeSet with the default
-    /// hasher and capacity.
+    /// Creates a new, empty UniCaseBTreeSet.
     pub fn new() -> Self {
         Self {
             inner: Default::default(),
@@ -68,7 +88,11 @@ impl UniCaseBTreeSet {
     }
 }
 
-impl UniCaseBTreeSet {
+impl<S, P> UniCaseBTreeSet<S, P>
+where
+    S: AsRef<str>,
+    P: FoldPolicy,
+{
     /// Clears the map, removing all key-value pairs. Keeps the allocated memory for reuse.
     pub fn clear(&mut self) {
         self.inner.clear();
@@ -76,23 +100,21 @@ impl UniCaseBTreeSet {
 
     /// Returns true if the map contains a value for the specified key.
     /// The key may be a String, str or UniCase value.
-    pub fn contains<K: ToKey>(&self, k: K) -> bool {
-        let key = k.to_key();
-        self.inner.contains(&key)
+    pub fn contains<K: AsKeyStr>(&self, k: K) -> bool {
+        self.inner.contains(k.as_key_str::<P>())
     }
 
     /// Returns a reference to the value corresponding to the key.
     /// The key may be a String, str or UniCase value.
-    pub fn get<K: ToKey>(&self, k: K) -> Option<&Key> {
-        let key = k.to_key();
-        self.inner.get(&key)
+    pub fn get<K: AsKeyStr>(&self, k: K) -> Option<&Key<S, P>> {
+        self.inner.get(k.as_key_str::<P>())
     }
 
     // Adds a value to the set.
     // Returns whether the value was newly inserted. That is:
     // If the set did not previously contain an equal value, true is returned.
     // If the set already contained an equal value, false is returned, and the entry is not updated.
-    pub fn insert<K: ToKey>(&mut self, k: K) -> bool {
+    pub fn insert<K: ToKey<S, P>>(&mut self, k: K) -> bool {
         let key = k.to_key();
         self.inner.insert(key)
     }
@@ -102,9 +124,9 @@ impl UniCaseBTreeSet {
         self.inner.is_empty()
     }
 
-    /// An iterator visiting all key-value pairs in arbitrary order.
-    /// The iterator element type is (&'a UniCase<String>, &'a V).
-    pub fn iter(&self) -> Iter<Key> {
+    /// An iterator visiting all keys in folded key order.
+    /// The iterator element type is &'a Key<S, P>.
+    pub fn iter(&self) -> Iter<'_, Key<S, P>> {
         self.inner.iter()
     }
 
@@ -115,24 +137,48 @@ impl UniCaseBTreeSet {
 
     /// Removes a key from the map, returning the value at the key if the key was previously in the map.
     /// The key may be a String, str or UniCase value.
-    pub fn remove<K: ToKey>(&mut self, k: K) -> bool {
-        let key = k.to_key();
-        self.inner.remove(&key)
+    pub fn remove<K: AsKeyStr>(&mut self, k: K) -> bool {
+        self.inner.remove(k.as_key_str::<P>())
     }
 
     /// Retains only the elements specified by the predicate.
     /// In other words, remove all pairs (k, v) such that f(&k,&mut v) returns false.
     pub fn retain<F>(&mut self, f: F)
     where
-        F: FnMut(&Key) -> bool,
+        F: FnMut(&Key<S, P>) -> bool,
     {
         self.inner.retain(f);
     }
+
+    /// Adds a value to the set like [`insert`](Self::insert), but first checks the key
+    /// with [`FoldPolicy::validate`] and returns an error if the policy rejects it.
+    pub fn try_insert<K: ToKey<S, P>>(&mut self, k: K) -> Result<bool, InvalidKey> {
+        let key = k.to_key();
+        P::validate(key.as_str())?;
+        Ok(self.inner.insert(key))
+    }
+}
+
+/// Creates a [`UniCaseBTreeSet`] with the default key type and policy from keys.
+///
+/// The keys must be string literals. Two keys that are equal under case folding, like
+/// `"Foo"` and `"FOO"`, are a compile error instead of silently overwriting each other.
+#[macro_export]
+macro_rules! unicase_btree_set {
+    ($($key:literal),* $(,)?) => {{
+        const KEYS: &[&str] = &[$($key),*];
+        const _: () = $crate::literal::assert_unique_keys(KEYS);
+        #[allow(unused_mut)]
+        let mut set = $crate::unicase_btree_set::UniCaseBTreeSet::new();
+        $(set.insert($key);)*
+        set
+    }};
 }
 
 #[cfg(test)]
 mod tests {
     use super::UniCaseBTreeSet;
+    use crate::key::Key;
     use unicase::UniCase;
 
     #[test]
@@ -178,8 +224,8 @@ mod tests {
     fn get_str() {
         let mut map = UniCaseBTreeSet::new();
         map.insert("A");
-        assert_eq!(map.get("A"), Some(&UniCase::new("A".to_string())));
-        assert_eq!(map.get("a"), Some(&UniCase::new("a".to_string())));
+        assert_eq!(map.get("A"), Some(&Key::new("A".to_string())));
+        assert_eq!(map.get("a"), Some(&Key::new("a".to_string())));
         assert!(map.get("B").is_none());
         assert!(map.get("Å").is_none());
     }
@@ -190,7 +236,7 @@ mod tests {
         map.insert("A");
         assert_eq!(
             map.get("A".to_string()).unwrap(),
-            &UniCase::new("a".to_string())
+            &Key::new("a".to_string())
         );
     }
 
@@ -198,9 +244,8 @@ mod tests {
     fn get_unicase() {
         let mut map = UniCaseBTreeSet::new();
         map.insert("A");
-        // Won't work with plain &str, which is annoying.
         let uc = UniCase::new("a".to_string());
-        assert_eq!(map.get(uc).unwrap(), &UniCase::new("A".to_string()));
+        assert_eq!(map.get(uc).unwrap(), &Key::new("A".to_string()));
     }
 
     #[test]
@@ -208,18 +253,18 @@ mod tests {
         let mut map = UniCaseBTreeSet::new();
         map.insert("A");
         let result = map.get("a");
-        assert_eq!(result, Some(&UniCase::new("a".to_string())));
+        assert_eq!(result, Some(&Key::new("a".to_string())));
     }
 
     #[test]
     fn insert_str() {
         let mut map = UniCaseBTreeSet::new();
         let result = map.insert("A");
-        assert_eq!(result, true);
+        assert!(result);
         let result = map.insert("B");
-        assert_eq!(result, true);
+        assert!(result);
         let result = map.insert("A");
-        assert_eq!(result, false);
+        assert!(!result);
 
         assert_eq!(map.len(), 2);
     }
@@ -246,11 +291,11 @@ mod tests {
         map.insert("A".to_string());
         map.insert("B".to_string());
 
-        let mut elems: Vec<_> = map.iter().map(|v| v.clone()).collect();
+        let mut elems: Vec<_> = map.iter().cloned().collect();
         elems.sort();
         assert_eq!(
             elems,
-            vec![UniCase::new("a".to_string()), UniCase::new("b".to_string())]
+            vec![Key::new("a".to_string()), Key::new("b".to_string())]
         );
     }
 
@@ -264,10 +309,7 @@ mod tests {
         keys.sort();
         assert_eq!(
             keys,
-            vec![
-                &UniCase::new("A".to_string()),
-                &UniCase::new("B".to_string())
-            ]
+            vec![&Key::new("A".to_string()), &Key::new("B".to_string())]
         );
     }
 
@@ -288,8 +330,8 @@ mod tests {
         let mut map = UniCaseBTreeSet::new();
         map.insert("A".to_string());
         map.insert("B".to_string());
-        assert_eq!(map.remove("b"), true);
-        assert_eq!(map.remove("b"), false);
+        assert!(map.remove("b"));
+        assert!(!map.remove("b"));
     }
 
     #[test]
@@ -297,8 +339,8 @@ mod tests {
         let mut map = UniCaseBTreeSet::new();
         map.insert("A".to_string());
         map.insert("B".to_string());
-        assert_eq!(map.remove("b"), true);
-        assert_eq!(map.remove("b"), false);
+        assert!(map.remove("b"));
+        assert!(!map.remove("b"));
     }
 
     #[test]
@@ -326,7 +368,7 @@ mod tests {
         map.extend(v);
 
         assert_eq!(map.len(), 3);
-        assert_eq!(map.get("a"), Some(&UniCase::new("a".to_string())));
+        assert_eq!(map.get("a"), Some(&Key::new("a".to_string())));
     }
 
     #[test]
@@ -346,4 +388,88 @@ mod tests {
 
         let _map: UniCaseBTreeSet = v.into_iter().collect();
     }
+
+    #[test]
+    fn static_str_keys() {
+        let set: UniCaseBTreeSet<&'static str> = vec!["GET", "PUT", "get"].into_iter().collect();
+        assert_eq!(set.len(), 2);
+        assert!(set.contains("Get"));
+    }
+
+    #[test]
+    fn ascii_policy() {
+        use crate::fold::Ascii;
+
+        let set: UniCaseBTreeSet<String, Ascii> = vec!["Å", "å", "A", "a"].into_iter().collect();
+        assert_eq!(set.len(), 3);
+    }
+
+    #[test]
+    fn normalized_policy() {
+        use crate::fold::Normalized;
+
+        let mut set = UniCaseBTreeSet::<String, Normalized>::default();
+        set.insert("\u{00c5}");
+        assert!(set.contains("a\u{030a}"));
+
+        let mut plain = UniCaseBTreeSet::new();
+        plain.insert("\u{00c5}");
+        assert!(!plain.contains("a\u{030a}"));
+    }
+
+    #[test]
+    fn tailored_policy() {
+        use crate::fold::{Az, Tailored};
+
+        let set: UniCaseBTreeSet<String, Tailored<Az>> =
+            vec!["bir", "BİR", "BIR"].into_iter().collect();
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn width_insensitive_policy() {
+        use crate::fold::WidthInsensitive;
+
+        let set: UniCaseBTreeSet<String, WidthInsensitive> =
+            vec!["ＡＢＣ", "abc", "ｶﾅ", "カナ", "かな"]
+                .into_iter()
+                .collect();
+        assert_eq!(set.len(), 3);
+        assert!(set.contains("Abc"));
+    }
+
+    #[test]
+    fn identifier_policy() {
+        use crate::fold::{Identifier, InvalidKey};
+
+        let mut set = UniCaseBTreeSet::<String, Identifier>::default();
+        assert_eq!(set.try_insert("Ⅸ"), Ok(true));
+        assert_eq!(set.try_insert("ix"), Ok(false));
+        assert_eq!(set.try_insert("a b"), Err(InvalidKey::at(1, ' ')));
+        assert_eq!(set.len(), 1);
+    }
+
+    #[test]
+    fn confusable_insensitive_policy() {
+        use crate::fold::ConfusableInsensitive;
+
+        let mut tenants = UniCaseBTreeSet::<String, ConfusableInsensitive>::default();
+        assert!(tenants.insert("paypal"));
+        assert!(!tenants.insert("p\u{0430}ypal"));
+        assert!(!tenants.insert("PAYPA1"));
+        assert!(!tenants.insert("paypaI"));
+        assert!(tenants.insert("paypay"));
+        assert_eq!(
+            tenants.get("p\u{0430}yp\u{0430}l").unwrap().as_str(),
+            "paypal"
+        );
+    }
+
+    #[test]
+    fn literal_macro() {
+        let set = crate::unicase_btree_set!["b", "A", "Straße"];
+        assert_eq!(set.len(), 3);
+        assert!(set.contains("STRASSE"));
+        assert_eq!(set.iter().next().unwrap().as_str(), "A");
+    }
 }
@@ -0,0 +1,390 @@
+use crate::fold::{FoldPolicy, FullUnicode, InvalidKey};
+use crate::key::{AsKeyStr, Key, ToKey};
+use dashmap::iter::{Iter, IterMut, OwningIter};
+use dashmap::mapref::entry::Entry;
+use dashmap::mapref::one::{Ref, RefMut};
+use dashmap::DashMap;
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::BuildHasher;
+use std::iter::FromIterator;
+
+/// A map for sharing between threads, split into shards by the hash of the folded key,
+/// each behind its own lock. Threads that use keys in different shards do not contend.
+///
+/// All methods take `&self`, so the map is usually shared in an `Arc`. The guards
+/// returned by [`get`](Self::get), [`get_mut`](Self::get_mut) and
+/// [`upsert`](Self::upsert) hold the lock of their shard, so holding one while
+/// modifying the map from the same thread may deadlock.
+pub struct UniCaseConcurrentMap<V, S = String, P = FullUnicode, H = RandomState> {
+    inner: DashMap<Key<S, P>, V, H>,
+}
+
+impl<V, S, P, H> Clone for UniCaseConcurrentMap<V, S, P, H>
+where
+    V: Clone,
+    S: AsRef<str> + Clone,
+    P: FoldPolicy,
+    H: BuildHasher + Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<V, S, P, H> fmt::Debug for UniCaseConcurrentMap<V, S, P, H>
+where
+    V: fmt::Debug,
+    S: AsRef<str> + fmt::Debug,
+    P: FoldPolicy,
+    H: BuildHasher + Clone,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+impl<V, S, P, H> Default for UniCaseConcurrentMap<V, S, P, H>
+where
+    S: AsRef<str>,
+    P: FoldPolicy,
+    H: BuildHasher + Clone + Default,
+{
+    fn default() -> Self {
+        Self {
+            inner: Default::default(),
+        }
+    }
+}
+
+impl<K, V, S, P, H> Extend<(K, V)> for UniCaseConcurrentMap<V, S, P, H>
+where
+    K: ToKey<S, P>,
+    S: AsRef<str>,
+    P: FoldPolicy,
+    H: BuildHasher + Clone,
+{
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        // Transform the keys into `Key`s.
+        let iter = iter.into_iter().map(|(k, v)| (k.to_key(), v));
+        self.inner.extend(iter);
+    }
+}
+
+impl<K, V, S, P, H> FromIterator<(K, V)> for UniCaseConcurrentMap<V, S, P, H>
+where
+    K: ToKey<S, P>,
+    S: AsRef<str>,
+    P: FoldPolicy,
+    H: BuildHasher + Clone + Default,
+{
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut map = Self::default();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<'a, V, S, P, H> IntoIterator for &'a UniCaseConcurrentMap<V, S, P, H>
+where
+    S: AsRef<str>,
+    P: FoldPolicy,
+    H: BuildHasher + Clone,
+{
+    type Item = dashmap::mapref::multiple::RefMulti<'a, Key<S, P>, V>;
+    type IntoIter = Iter<'a, Key<S, P>, V, H>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<V, S, P, H> IntoIterator for UniCaseConcurrentMap<V, S, P, H>
+where
+    S: AsRef<str>,
+    P: FoldPolicy,
+    H: BuildHasher + Clone,
+{
+    type Item = (Key<S, P>, V);
+    type IntoIter = OwningIter<Key<S, P>, V, H>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.inner.into_iter()
+    }
+}
+
+impl<V> UniCaseConcurrentMap<V> {
+    /// Creates a new UniCaseConcurrentMap with the default
+    /// hasher, capacity and number of shards.
+    pub fn new() -> Self {
+        Self {
+            inner: DashMap::new(),
+        }
+    }
+
+    /// Creates an empty map with space for at least `capacity` elements.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            inner: DashMap::with_capacity(capacity),
+        }
+    }
+
+    /// Creates an empty map with `shard_amount` shards.
+    /// Panics if `shard_amount` is not a power of two greater than one.
+    pub fn with_shard_amount(shard_amount: usize) -> Self {
+        Self {
+            inner: DashMap::with_shard_amount(shard_amount),
+        }
+    }
+}
+
+impl<V, S, P, H> UniCaseConcurrentMap<V, S, P, H>
+where
+    S: AsRef<str>,
+    P: FoldPolicy,
+    H: BuildHasher + Clone,
+{
+    /// Creates an empty map that uses `hash_builder` to hash the folded keys.
+    pub fn with_hasher(hash_builder: H) -> Self {
+        Self {
+            inner: DashMap::with_hasher(hash_builder),
+        }
+    }
+
+    /// Creates an empty map with space for at least `capacity` elements, using
+    /// `hash_builder` to hash the folded keys.
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: H) -> Self {
+        Self {
+            inner: DashMap::with_capacity_and_hasher(capacity, hash_builder),
+        }
+    }
+
+    /// Returns the number of elements the map can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.inner.capacity()
+    }
+
+    /// Clears the map, removing all key-value pairs.
+    pub fn clear(&self) {
+        self.inner.clear();
+    }
+
+    /// Returns true if the map contains a value for the specified key.
+    /// The key may be a String, str or UniCase value.
+    pub fn contains_key<K: AsKeyStr>(&self, k: K) -> bool {
+        self.inner.contains_key(k.as_key_str::<P>())
+    }
+
+    /// Gets the given key's corresponding entry in the map for in-place manipulation.
+    /// The entry holds the write lock of the key's shard.
+    pub fn entry<K: ToKey<S, P>>(&self, k: K) -> Entry<'_, Key<S, P>, V> {
+        self.inner.entry(k.to_key())
+    }
+
+    /// Returns a guard of the value corresponding to the key, which holds the read lock
+    /// of the key's shard. The key may be a String, str or UniCase value.
+    pub fn get<K: AsKeyStr>(&self, k: K) -> Option<Ref<'_, Key<S, P>, V>> {
+        self.inner.get(k.as_key_str::<P>())
+    }
+
+    /// Returns a mutable guard of the value corresponding to the key, which holds the
+    /// write lock of the key's shard. The key may be a String, str or UniCase value.
+    pub fn get_mut<K: AsKeyStr>(&self, k: K) -> Option<RefMut<'_, Key<S, P>, V>> {
+        self.inner.get_mut(k.as_key_str::<P>())
+    }
+
+    /// Returns a reference to the hasher of the map.
+    pub fn hasher(&self) -> &H {
+        self.inner.hasher()
+    }
+
+    /// Inserts a key-value pair into the map.
+    /// If the map did not have this key present, None is returned.
+    /// If the map did have this key present, the value is updated, and the old value is returned.
+    /// The key is not updated, though; this matters for types that can be == without being identical.
+    pub fn insert<K: ToKey<S, P>>(&self, k: K, v: V) -> Option<V> {
+        self.inner.insert(k.to_key(), v)
+    }
+
+    /// Returns true if the map contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// An iterator visiting all key-value pairs in arbitrary order.
+    /// Every shard is read locked while it is visited, so the pairs of a shard are a
+    /// consistent snapshot, while the shards that are not being visited may change.
+    pub fn iter(&self) -> Iter<'_, Key<S, P>, V, H> {
+        self.inner.iter()
+    }
+
+    /// An iterator visiting all key-value pairs in arbitrary order, with mutable
+    /// references to the values. Every shard is write locked while it is visited.
+    pub fn iter_mut(&self) -> IterMut<'_, Key<S, P>, V, H> {
+        self.inner.iter_mut()
+    }
+
+    /// Returns the number of elements in the map. The shards are counted one by one, so
+    /// concurrent modifications may or may not be counted.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Removes a key from the map, returning the value at the key if the key was previously in the map.
+    /// The key may be a String, str or UniCase value.
+    pub fn remove<K: AsKeyStr>(&self, k: K) -> Option<V> {
+        self.remove_entry(k).map(|(_, v)| v)
+    }
+
+    /// Removes a key from the map, returning the stored key and value if the key was previously in the map.
+    /// The key may be a String, str or UniCase value.
+    pub fn remove_entry<K: AsKeyStr>(&self, k: K) -> Option<(Key<S, P>, V)> {
+        self.inner.remove(k.as_key_str::<P>())
+    }
+
+    /// Retains only the elements specified by the predicate.
+    /// In other words, remove all pairs (k, v) such that f(&k,&mut v) returns false.
+    pub fn retain<F>(&self, f: F)
+    where
+        F: FnMut(&Key<S, P>, &mut V) -> bool,
+    {
+        self.inner.retain(f);
+    }
+
+    /// Shrinks the capacity of the map as much as possible.
+    pub fn shrink_to_fit(&self) {
+        self.inner.shrink_to_fit();
+    }
+
+    /// Inserts a key-value pair into the map like [`insert`](Self::insert), but first
+    /// checks the key with [`FoldPolicy::validate`] and returns an error if the policy
+    /// rejects it.
+    pub fn try_insert<K: ToKey<S, P>>(&self, k: K, v: V) -> Result<Option<V>, InvalidKey> {
+        let key = k.to_key();
+        P::validate(key.as_str())?;
+        Ok(self.inner.insert(key, v))
+    }
+
+    /// Calls `update` with the value of the key, or inserts the value returned by
+    /// `insert` if the map does not contain the key. Both happen under the write lock of
+    /// the key's shard, so concurrent upserts of the same key do not lose updates.
+    /// Returns a mutable guard of the value.
+    pub fn upsert<K, F, U>(&self, k: K, insert: F, update: U) -> RefMut<'_, Key<S, P>, V>
+    where
+        K: ToKey<S, P>,
+        F: FnOnce() -> V,
+        U: FnOnce(&mut V),
+    {
+        match self.inner.entry(k.to_key()) {
+            Entry::Occupied(mut entry) => {
+                update(entry.get_mut());
+                entry.into_ref()
+            }
+            Entry::Vacant(entry) => entry.insert(insert()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UniCaseConcurrentMap;
+    use std::thread;
+    use unicase::UniCase;
+
+    #[test]
+    fn new() {
+        let map = UniCaseConcurrentMap::<u8>::new();
+        assert_eq!(map.len(), 0);
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn insert_and_get() {
+        let map = UniCaseConcurrentMap::new();
+        assert_eq!(map.insert("Tenant-A", 1), None);
+        assert_eq!(map.insert("TENANT-a", 2), Some(1));
+        assert_eq!(*map.get("tenant-a").unwrap(), 2);
+        assert_eq!(map.get("Tenant-A").unwrap().key().as_str(), "Tenant-A");
+        assert_eq!(*map.get(UniCase::new("TENANT-A")).unwrap(), 2);
+        assert!(map.contains_key("tenant-A".to_string()));
+        assert!(map.get("tenant-b").is_none());
+
+        *map.get_mut("tenant-a").unwrap() += 1;
+        assert_eq!(*map.get("tenant-a").unwrap(), 3);
+    }
+
+    #[test]
+    fn remove_and_retain() {
+        let map: UniCaseConcurrentMap<i32> =
+            vec![("A", 1), ("B", 2), ("C", 3)].into_iter().collect();
+        assert_eq!(map.remove("a"), Some(1));
+        assert_eq!(map.remove("a"), None);
+        assert_eq!(map.remove_entry("B").unwrap().0.as_str(), "B");
+        map.retain(|_, v| *v != 3);
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn upsert() {
+        let map = UniCaseConcurrentMap::new();
+        assert_eq!(*map.upsert("Hits", || 1, |v| *v += 1), 1);
+        assert_eq!(*map.upsert("HITS", || 1, |v| *v += 1), 2);
+        assert_eq!(map.get("hits").unwrap().key().as_str(), "Hits");
+        *map.entry("hits").or_insert(0) += 10;
+        assert_eq!(*map.get("hits").unwrap(), 12);
+    }
+
+    #[test]
+    fn concurrent_upserts() {
+        let map = UniCaseConcurrentMap::with_shard_amount(4);
+        thread::scope(|scope| {
+            for t in 0..4 {
+                let map = &map;
+                scope.spawn(move || {
+                    for i in 0..100 {
+                        let key = if t % 2 == 0 {
+                            format!("key{}", i % 10)
+                        } else {
+                            format!("KEY{}", i % 10)
+                        };
+                        map.upsert(key, || 1, |v| *v += 1);
+                    }
+                });
+            }
+        });
+        assert_eq!(map.len(), 10);
+        assert!(map.iter().all(|entry| *entry.value() == 40));
+    }
+
+    #[test]
+    fn iter() {
+        let map: UniCaseConcurrentMap<i32> = vec![("A", 1), ("B", 2)].into_iter().collect();
+        for mut entry in map.iter_mut() {
+            *entry.value_mut() *= 10;
+        }
+        let mut pairs: Vec<_> = map
+            .iter()
+            .map(|entry| (entry.key().to_string(), *entry.value()))
+            .collect();
+        pairs.sort();
+        assert_eq!(pairs, vec![("A".to_string(), 10), ("B".to_string(), 20)]);
+
+        for _ in &map {}
+        let mut owned: Vec<_> = map.into_iter().map(|(_, v)| v).collect();
+        owned.sort();
+        assert_eq!(owned, vec![10, 20]);
+    }
+
+    #[test]
+    fn try_insert() {
+        use crate::fold::{HttpToken, InvalidKey};
+
+        let map = UniCaseConcurrentMap::<u8, String, HttpToken>::default();
+        assert_eq!(map.try_insert("X-Id", 1), Ok(None));
+        assert_eq!(map.try_insert("x-id", 2), Ok(Some(1)));
+        assert_eq!(map.try_insert("X Id", 3), Err(InvalidKey::at(1, ' ')));
+        assert_eq!(map.len(), 1);
+    }
+}
@@ -0,0 +1,744 @@
+use crate::fold::{FoldPolicy, FullUnicode, InvalidKey};
+use crate::key::{AsKeyStr, Key, ToKey};
+use std::collections::hash_map::RandomState;
+use std::collections::hash_map::{Entry, IntoIter, Iter, IterMut, Keys, Values, ValuesMut};
+use std::collections::{HashMap, TryReserveError};
+use std::hash::BuildHasher;
+use std::iter::FromIterator;
+use std::ops::Index;
+
+#[derive(Debug, Clone)]
+pub struct UniCaseHashMap<V, S = String, P = FullUnicode, H = RandomState> {
+    inner: HashMap<Key<S, P>, V, H>,
+}
+
+impl<V, S, P, H: Default> Default for UniCaseHashMap<V, S, P, H> {
+    fn default() -> Self {
+        Self {
+            inner: Default::default(),
+        }
+    }
+}
+
+impl<V, S, P, H> Eq for UniCaseHashMap<V, S, P, H>
+where
+    V: Eq,
+    S: AsRef<str>,
+    P: FoldPolicy,
+    H: BuildHasher,
+{
+}
+
+impl<V, S, P, H> PartialEq for UniCaseHashMap<V, S, P, H>
+where
+    V: PartialEq,
+    S: AsRef<str>,
+    P: FoldPolicy,
+    H: BuildHasher,
+{
+    fn eq(&self, other: &UniCaseHashMap<V, S, P, H>) -> bool {
+        if self.len() != other.len() {
+            return false;
+        }
+
+        self.iter()
+            .all(|(key, value)| other.get(key) == Some(value))
+    }
+}
+
+impl<K, V, S, P, H> Extend<(K, V)> for UniCaseHashMap<V, S, P, H>
+where
+    K: ToKey<S, P>,
+    S: AsRef<str>,
+    P: FoldPolicy,
+    H: BuildHasher,
+{
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        // Transform the keys into `Key`s.
+        let iter = iter.into_iter().map(|(k, v)| (k.to_key(), v));
+        self.inner.extend(iter);
+    }
+}
+
+impl<'a, K, V, S, P, H> Extend<(K, &'a V)> for UniCaseHashMap<V, S, P, H>
+where
+    K: ToKey<S, P>,
+    S: AsRef<str>,
+    P: FoldPolicy,
+    H: BuildHasher,
+    V: Copy,
+{
+    fn extend<T: IntoIterator<Item = (K, &'a V)>>(&mut self, iter: T) {
+        // Transform the keys into `Key`s and copy the values.
+        let iter = iter.into_iter().map(|(k, v)| (k.to_key(), *v));
+        self.inner.extend(iter);
+    }
+}
+
+impl<K, V, S, P, H> FromIterator<(K, V)> for UniCaseHashMap<V, S, P, H>
+where
+    K: ToKey<S, P>,
+    S: AsRef<str>,
+    P: FoldPolicy,
+    H: BuildHasher + Default,
+{
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut map = Self::default();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<'a, V, S, P, H> IntoIterator for &'a UniCaseHashMap<V, S, P, H>
+where
+    S: AsRef<str>,
+    P: FoldPolicy,
+    H: BuildHasher,
+{
+    type Item = (&'a Key<S, P>, &'a V);
+    type IntoIter = Iter<'a, Key<S, P>, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, V, S, P, H> IntoIterator for &'a mut UniCaseHashMap<V, S, P, H>
+where
+    S: AsRef<str>,
+    P: FoldPolicy,
+    H: BuildHasher,
+{
+    type Item = (&'a Key<S, P>, &'a mut V);
+    type IntoIter = IterMut<'a, Key<S, P>, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<V, S, P, H> IntoIterator for UniCaseHashMap<V, S, P, H> {
+    type Item = (Key<S, P>, V);
+    type IntoIter = IntoIter<Key<S, P>, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.inner.into_iter()
+    }
+}
+
+impl<K, V, S, P, H> Index<K> for UniCaseHashMap<V, S, P, H>
+where
+    K: AsKeyStr,
+    S: AsRef<str>,
+    P: FoldPolicy,
+    H: BuildHasher,
+{
+    type Output = V;
+
+    fn index(&self, index: K) -> &Self::Output {
+        &self.inner[index.as_key_str::<P>()]
+    }
+}
+
+impl<V> UniCaseHashMap<V> {
+    /// Creates a new UniCaseHashMap with the default
+    /// hasher and capacity.
+    pub fn new() -> Self {
+        Self {
+            inner: Default::default(),
+        }
+    }
+
+    /// Creates an empty map with space for at least `capacity` elements.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            inner: HashMap::with_capacity(capacity),
+        }
+    }
+}
+
+impl<V, S, P, H> UniCaseHashMap<V, S, P, H> {
+    /// Creates an empty map that uses `hash_builder` to hash the folded keys.
+    pub fn with_hasher(hash_builder: H) -> Self {
+        Self {
+            inner: HashMap::with_hasher(hash_builder),
+        }
+    }
+
+    /// Creates an empty map with space for at least `capacity` elements, using
+    /// `hash_builder` to hash the folded keys.
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: H) -> Self {
+        Self {
+            inner: HashMap::with_capacity_and_hasher(capacity, hash_builder),
+        }
+    }
+
+    /// Returns a reference to the hasher of the map.
+    pub fn hasher(&self) -> &H {
+        self.inner.hasher()
+    }
+}
+
+impl<V, S, P, H> UniCaseHashMap<V, S, P, H>
+where
+    S: AsRef<str>,
+    P: FoldPolicy,
+    H: BuildHasher,
+{
+    /// Returns the number of elements the map can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.inner.capacity()
+    }
+
+    /// Clears the map, removing all key-value pairs. Keeps the allocated memory for reuse.
+    pub fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    /// Returns true if the map contains a value for the specified key.
+    /// The key may be a String, str or UniCase value.
+    pub fn contains_key<K: AsKeyStr>(&self, k: K) -> bool {
+        self.inner.contains_key(k.as_key_str::<P>())
+    }
+
+    /// Gets the given key's corresponding entry in the map for in-place manipulation.
+    pub fn entry<K: ToKey<S, P>>(&mut self, k: K) -> Entry<'_, Key<S, P>, V> {
+        let key = k.to_key();
+        self.inner.entry(key)
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    /// The key may be a String, str or UniCase value.
+    pub fn get<K: AsKeyStr>(&self, k: K) -> Option<&V> {
+        self.inner.get(k.as_key_str::<P>())
+    }
+
+    /// Returns the key-value pair corresponding to the supplied key.
+    /// The key may be a String, str or UniCase value.
+    pub fn get_key_value<K: AsKeyStr>(&self, k: K) -> Option<(&Key<S, P>, &V)> {
+        self.inner.get_key_value(k.as_key_str::<P>())
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    /// The key may be a String, str or UniCase value.
+    pub fn get_mut<K: AsKeyStr>(&mut self, k: K) -> Option<&mut V> {
+        self.inner.get_mut(k.as_key_str::<P>())
+    }
+
+    /// Inserts a key-value pair into the map.
+    /// If the map did not have this key present, None is returned.
+    /// If the map did have this key present, the value is updated, and the old value is returned.
+    /// The key is not updated, though; this matters for types that can be == without being identical.
+    /// See the module-level documentation of [BTreeMap](https://doc.rust-lang.org/std/collections/index.html#insert-and-complex-keys)
+    // for more.
+    pub fn insert<K: ToKey<S, P>>(&mut self, k: K, v: V) -> Option<V> {
+        let key = k.to_key();
+        self.inner.insert(key, v)
+    }
+
+    /// Returns true if the map contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// An iterator visiting all key-value pairs in arbitrary order.
+    /// The iterator element type is (&'a Key<S, P>, &'a V).
+    pub fn iter(&self) -> Iter<'_, Key<S, P>, V> {
+        self.inner.iter()
+    }
+
+    /// An iterator visiting all key-value pairs in arbitrary order,
+    /// with mutable references to the values. The iterator element type is
+    /// (&'a Key<S, P>, &'a mut V).
+    pub fn iter_mut(&mut self) -> IterMut<'_, Key<S, P>, V> {
+        self.inner.iter_mut()
+    }
+
+    /// An iterator visiting all keys in arbitrary order. The iterator element type is &'a Key<S, P>.
+    pub fn keys(&self) -> Keys<'_, Key<S, P>, V> {
+        self.inner.keys()
+    }
+
+    /// Returns the number of elements in the map.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Removes a key from the map, returning the value at the key if the key was previously in the map.
+    /// The key may be a String, str or UniCase value.
+    pub fn remove<K: AsKeyStr>(&mut self, k: K) -> Option<V> {
+        self.inner.remove(k.as_key_str::<P>())
+    }
+
+    /// Removes a key from the map, returning the stored key and value if the key was previously in the map.
+    /// The key may be a String, str or UniCase value.
+    pub fn remove_entry<K: AsKeyStr>(&mut self, k: K) -> Option<(Key<S, P>, V)> {
+        self.inner.remove_entry(k.as_key_str::<P>())
+    }
+
+    /// Reserves capacity for at least `additional` more elements.
+    /// Panics if the new capacity overflows `usize`.
+    pub fn reserve(&mut self, additional: usize) {
+        self.inner.reserve(additional);
+    }
+
+    /// Retains only the elements specified by the predicate.
+    /// In other words, remove all pairs (k, v) such that f(&k,&mut v) returns false.
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&Key<S, P>, &mut V) -> bool,
+    {
+        self.inner.retain(f);
+    }
+
+    /// Shrinks the capacity of the map as much as possible.
+    pub fn shrink_to_fit(&mut self) {
+        self.inner.shrink_to_fit();
+    }
+
+    /// Inserts a key-value pair into the map like [`insert`](Self::insert), but first
+    /// checks the key with [`FoldPolicy::validate`] and returns an error if the policy
+    /// rejects it.
+    pub fn try_insert<K: ToKey<S, P>>(&mut self, k: K, v: V) -> Result<Option<V>, InvalidKey> {
+        let key = k.to_key();
+        P::validate(key.as_str())?;
+        Ok(self.inner.insert(key, v))
+    }
+
+    /// Tries to reserve capacity for at least `additional` more elements, returning an
+    /// error instead of panicking or aborting if the allocation fails.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.inner.try_reserve(additional)
+    }
+
+    /// An iterator visiting all values in arbitrary order. The iterator element type is &'a V.
+    pub fn values(&self) -> Values<'_, Key<S, P>, V> {
+        self.inner.values()
+    }
+
+    /// An iterator visiting all values mutably in arbitrary order. The iterator element type is &'a mut V.
+    pub fn values_mut(&mut self) -> ValuesMut<'_, Key<S, P>, V> {
+        self.inner.values_mut()
+    }
+}
+
+/// Creates a [`UniCaseHashMap`] with the default key type and policy from `key => value` pairs.
+///
+/// The keys must be string literals. Two keys that are equal under case folding, like
+/// `"Foo"` and `"FOO"`, are a compile error instead of silently overwriting each other.
+#[macro_export]
+macro_rules! unicase_hash_map {
+    ($($key:literal => $value:expr),* $(,)?) => {{
+        const KEYS: &[&str] = &[$($key),*];
+        const _: () = $crate::literal::assert_unique_keys(KEYS);
+        #[allow(unused_mut)]
+        let mut map = $crate::unicase_hash_map::UniCaseHashMap::with_capacity(KEYS.len());
+        $(map.insert($key, $value);)*
+        map
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UniCaseHashMap;
+    use crate::fold::SimpleUnicode;
+    use crate::key::Key;
+    use std::collections::hash_map::RandomState;
+    use unicase::UniCase;
+
+    #[test]
+    fn new() {
+        let map = UniCaseHashMap::<u8>::new();
+        assert_eq!(map.len(), 0);
+
+        let map = UniCaseHashMap::<u8, Box<str>, SimpleUnicode>::with_capacity_and_hasher(
+            10,
+            RandomState::new(),
+        );
+        assert!(map.capacity() >= 10);
+    }
+
+    #[test]
+    fn clear() {
+        let mut map = UniCaseHashMap::<u8>::new();
+        assert_eq!(map.len(), 0);
+        map.insert("A", 1);
+        assert_eq!(map.len(), 1);
+        assert!(!map.is_empty());
+
+        map.clear();
+        assert_eq!(map.len(), 0);
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn contains_key_str() {
+        let mut map = UniCaseHashMap::<u8>::new();
+        map.insert("A", 1);
+        assert!(map.contains_key("A"));
+        assert!(map.contains_key("a"));
+        assert!(!map.contains_key("B"));
+        assert!(!map.contains_key("Å"));
+    }
+
+    #[test]
+    fn contains_key_string() {
+        let mut map = UniCaseHashMap::<u8>::new();
+        map.insert("A", 1);
+        assert!(map.contains_key("A".to_string()));
+        assert!(map.contains_key("a".to_string()));
+        assert!(!map.contains_key("B".to_string()));
+        assert!(!map.contains_key("Å".to_string()));
+    }
+
+    #[test]
+    fn entry() {
+        let mut map = UniCaseHashMap::<u8>::new();
+        map.insert("A", 1);
+        let entry = map.entry("A");
+        assert_eq!(entry.key(), &Key::new("A".to_string()));
+    }
+
+    #[test]
+    fn get_str() {
+        let mut map = UniCaseHashMap::<u8>::new();
+        map.insert("A", 1);
+        assert_eq!(map.get("A").unwrap(), &1);
+        assert_eq!(map.get("a").unwrap(), &1);
+        assert!(map.get("B").is_none());
+        assert!(map.get("Å").is_none());
+    }
+
+    #[test]
+    fn get_string() {
+        let mut map = UniCaseHashMap::<u8>::new();
+        map.insert("A", 1);
+        assert_eq!(map.get("A".to_string()).unwrap(), &1);
+    }
+
+    #[test]
+    fn get_unicase() {
+        let mut map = UniCaseHashMap::<u8>::new();
+        map.insert("A", 1);
+        let uc = UniCase::new("a".to_string());
+        assert_eq!(map.get(uc).unwrap(), &1);
+    }
+
+    #[test]
+    fn get_key_value() {
+        let mut map = UniCaseHashMap::<u8>::new();
+        map.insert("A", 1);
+        let result = map.get_key_value("a");
+        assert_eq!(result.unwrap().0, &Key::new("a".to_string()));
+        assert_eq!(result.unwrap().1, &1);
+    }
+
+    #[test]
+    fn get_mut() {
+        let mut map = UniCaseHashMap::<u8>::new();
+        map.insert("A", 1);
+        assert_eq!(map.get_mut("a"), Some(&mut 1));
+        assert!(map.get_mut("C").is_none());
+    }
+
+    #[test]
+    fn insert_str() {
+        let mut map = UniCaseHashMap::<u8>::new();
+        let result = map.insert("A", 1);
+        assert!(result.is_none());
+        let result = map.insert("B", 2);
+        assert!(result.is_none());
+        let result = map.insert("A", 20);
+        assert_eq!(result, Some(1));
+
+        assert_eq!(map.len(), 2);
+    }
+
+    #[test]
+    fn insert_string() {
+        let mut map = UniCaseHashMap::<u8>::new();
+        map.insert("A".to_string(), 1);
+        map.insert("B".to_string(), 2);
+        assert_eq!(map.len(), 2);
+    }
+
+    #[test]
+    fn is_empty() {
+        let mut map = UniCaseHashMap::<u8>::new();
+        assert!(map.is_empty());
+        map.insert("A", 1);
+        assert!(!map.is_empty());
+    }
+
+    #[test]
+    fn iter() {
+        let mut map = UniCaseHashMap::<u8>::new();
+        map.insert("A".to_string(), 1);
+        map.insert("B".to_string(), 2);
+
+        let mut elems: Vec<_> = map.iter().map(|(_, v)| *v).collect();
+        elems.sort();
+        assert_eq!(elems, vec![1, 2]);
+    }
+
+    #[test]
+    fn iter_mut() {
+        let mut map = UniCaseHashMap::<u8>::new();
+        map.insert("A".to_string(), 1);
+        map.insert("B".to_string(), 2);
+
+        for (_, v) in map.iter_mut() {
+            *v += 10;
+        }
+
+        let mut elems: Vec<_> = map.iter().map(|(_, v)| *v).collect();
+        elems.sort();
+        assert_eq!(elems, vec![11, 12]);
+    }
+
+    #[test]
+    fn keys() {
+        let mut map = UniCaseHashMap::<u8>::new();
+        map.insert("A".to_string(), 1);
+        map.insert("B".to_string(), 2);
+
+        let mut keys: Vec<_> = map.keys().collect();
+        keys.sort();
+        assert_eq!(
+            keys,
+            vec![&Key::new("A".to_string()), &Key::new("B".to_string())]
+        );
+    }
+
+    #[test]
+    fn len() {
+        let mut map = UniCaseHashMap::<u8>::new();
+        assert_eq!(map.len(), 0);
+        map.insert("A".to_string(), 1);
+        assert_eq!(map.len(), 1);
+        map.insert("B".to_string(), 2);
+        assert_eq!(map.len(), 2);
+        map.clear();
+        assert_eq!(map.len(), 0);
+    }
+
+    #[test]
+    fn remove() {
+        let mut map = UniCaseHashMap::<u8>::new();
+        map.insert("A".to_string(), 1);
+        map.insert("B".to_string(), 2);
+        assert_eq!(map.remove("b"), Some(2));
+        assert_eq!(map.remove("b"), None);
+    }
+
+    #[test]
+    fn remove_entry() {
+        let mut map = UniCaseHashMap::<u8>::new();
+        map.insert("A".to_string(), 1);
+        map.insert("B".to_string(), 2);
+        assert_eq!(map.remove("b"), Some(2));
+        assert_eq!(map.remove("b"), None);
+    }
+
+    #[test]
+    fn retain() {
+        let mut map = UniCaseHashMap::<u8>::new();
+        map.insert("A".to_string(), 1);
+        map.insert("B".to_string(), 2);
+        map.insert("C".to_string(), 1);
+
+        map.retain(|_, v| v == &1);
+
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get("A"), Some(&1));
+        assert_eq!(map.get("B"), None);
+        assert_eq!(map.get("C"), Some(&1));
+    }
+
+    #[test]
+    fn values() {
+        let mut map = UniCaseHashMap::<u8>::new();
+        map.insert("A".to_string(), 1);
+        map.insert("B".to_string(), 2);
+        map.insert("C".to_string(), 1);
+
+        let mut values: Vec<_> = map.values().cloned().collect();
+        values.sort();
+        assert_eq!(values, vec![1, 1, 2]);
+    }
+
+    #[test]
+    fn values_mut() {
+        let mut map = UniCaseHashMap::<u8>::new();
+        map.insert("A".to_string(), 1);
+        map.insert("B".to_string(), 2);
+        map.insert("C".to_string(), 1);
+
+        for v in map.values_mut() {
+            *v += 10;
+        }
+
+        let mut values: Vec<_> = map.values().cloned().collect();
+        values.sort();
+        assert_eq!(values, vec![11, 11, 12]);
+    }
+
+    #[test]
+    fn partial_eq() {
+        let mut map1 = UniCaseHashMap::<u8>::new();
+        map1.insert("A".to_string(), 1);
+        map1.insert("B".to_string(), 2);
+        map1.insert("C".to_string(), 3);
+
+        let mut map2 = UniCaseHashMap::<u8>::new();
+        map2.insert("C".to_string(), 3);
+        map2.insert("B".to_string(), 2);
+        map2.insert("A".to_string(), 1);
+
+        assert_eq!(map1, map2);
+    }
+
+    #[test]
+    fn extend() {
+        let mut map = UniCaseHashMap::<u8>::new();
+        map.insert("A".to_string(), 1);
+
+        let v = vec![("A", 2), ("B", 3), ("C", 4)];
+
+        map.extend(v);
+
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.get("a"), Some(&2));
+    }
+
+    #[test]
+    fn index() {
+        let mut map = UniCaseHashMap::<u8>::new();
+        map.insert("A", 1);
+        map.insert("B", 2);
+
+        assert_eq!(map["a"], 1);
+        assert_eq!(map["b"], 2);
+    }
+
+    #[test]
+    fn into_iterator_impls() {
+        let mut map = UniCaseHashMap::<u8>::new();
+        map.insert("A", 1);
+        map.insert("B", 2);
+
+        // These should all compile.
+        for _ in &map {}
+        for _ in &mut map {}
+        for _ in map {}
+    }
+
+    #[test]
+    fn from_iterator() {
+        let v = vec![("A", 2), ("B", 3), ("C", 4)];
+
+        let _map: UniCaseHashMap<u8> = v.into_iter().collect();
+    }
+
+    #[test]
+    fn entry_api() {
+        let mut map = UniCaseHashMap::<u32>::new();
+        for word in ["a", "B", "A", "b", "A"] {
+            *map.entry(word).or_insert(0) += 1;
+        }
+        assert_eq!(map["a"], 3);
+        assert_eq!(map["b"], 2);
+        assert_eq!(map.get_key_value("A").unwrap().0.as_str(), "a");
+    }
+
+    #[test]
+    fn box_str_keys() {
+        let mut map = UniCaseHashMap::<u8, Box<str>>::default();
+        map.insert("A", 1);
+        map.insert("b".to_string(), 2);
+        assert_eq!(map["a"], 1);
+        assert_eq!(map["B"], 2);
+    }
+
+    #[test]
+    fn full_folding_expansions() {
+        let mut map = UniCaseHashMap::<u8>::new();
+        map.insert("straße", 1);
+        map.insert("STRASSE", 2);
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.get("STRAẞE"), Some(&2));
+    }
+
+    #[test]
+    fn ascii_policy() {
+        use crate::fold::Ascii;
+
+        let mut map = UniCaseHashMap::<u8, &str, Ascii>::default();
+        map.insert("K", 1);
+        map.insert("\u{212a}", 2);
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get("k"), Some(&1));
+    }
+
+    #[test]
+    fn try_insert() {
+        use crate::fold::{Identifier, InvalidKey};
+
+        let mut map = UniCaseHashMap::<u8, String, Identifier>::default();
+        assert_eq!(map.try_insert("Name", 1), Ok(None));
+        assert_eq!(map.try_insert("na-me", 2), Err(InvalidKey::at(2, '-')));
+        assert_eq!(map.get("NAME"), Some(&1));
+    }
+
+    #[test]
+    fn custom_hasher() {
+        use crate::fold::FullUnicode;
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::BuildHasherDefault;
+
+        type FixedState = BuildHasherDefault<DefaultHasher>;
+
+        let mut map = UniCaseHashMap::<u8, String, FullUnicode, FixedState>::with_hasher(
+            FixedState::default(),
+        );
+        map.insert("A", 1);
+        assert_eq!(map.get("a"), Some(&1));
+
+        let map = UniCaseHashMap::<u8, String, FullUnicode, _>::with_capacity_and_hasher(
+            8,
+            FixedState::default(),
+        );
+        assert!(map.is_empty());
+
+        let map: UniCaseHashMap<u8, String, FullUnicode, FixedState> =
+            vec![("A", 1), ("a", 2)].into_iter().collect();
+        assert_eq!(map.len(), 1);
+        assert_eq!(map, map.clone());
+    }
+
+    #[test]
+    fn capacity() {
+        let mut map = UniCaseHashMap::<u8>::with_capacity(10);
+        assert!(map.capacity() >= 10);
+        map.reserve(100);
+        assert!(map.capacity() >= 100);
+        map.insert("A", 1);
+        map.shrink_to_fit();
+        assert!(map.capacity() >= 1 && map.capacity() < 100);
+        assert!(map.try_reserve(1000).is_ok());
+        assert!(map.capacity() >= 1001);
+        assert!(map.try_reserve(usize::MAX).is_err());
+        assert_eq!(map["a"], 1);
+    }
+
+    #[test]
+    fn literal_macro() {
+        let map = crate::unicase_hash_map! {
+            "Content-Type" => "text/plain",
+            "Accept" => "*/*",
+        };
+        assert_eq!(map.len(), 2);
+        assert_eq!(map["content-type"], "text/plain");
+        assert_eq!(map.get("ACCEPT"), Some(&"*/*"));
+    }
+}
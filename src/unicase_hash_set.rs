@@ -0,0 +1,522 @@
+use crate::fold::{FoldPolicy, FullUnicode, InvalidKey};
+use crate::key::{AsKeyStr, Key, ToKey};
+use std::collections::hash_map::RandomState;
+use std::collections::hash_set::{IntoIter, Iter};
+use std::collections::{HashSet, TryReserveError};
+use std::hash::BuildHasher;
+use std::iter::FromIterator;
+
+#[derive(Debug, Clone)]
+pub struct UniCaseHashSet<S = String, P = FullUnicode, H = RandomState> {
+    inner: HashSet<Key<S, P>, H>,
+}
+
+impl<S, P, H: Default> Default for UniCaseHashSet<S, P, H> {
+    fn default() -> Self {
+        Self {
+            inner: Default::default(),
+        }
+    }
+}
+
+impl<S, P, H> PartialEq for UniCaseHashSet<S, P, H>
+where
+    S: AsRef<str>,
+    P: FoldPolicy,
+    H: BuildHasher,
+{
+    fn eq(&self, other: &UniCaseHashSet<S, P, H>) -> bool {
+        if self.len() != other.len() {
+            return false;
+        }
+
+        self.iter().all(|key| other.contains(key))
+    }
+}
+
+impl<K, S, P, H> Extend<K> for UniCaseHashSet<S, P, H>
+where
+    K: ToKey<S, P>,
+    S: AsRef<str>,
+    P: FoldPolicy,
+    H: BuildHasher,
+{
+    fn extend<T: IntoIterator<Item = K>>(&mut self, iter: T) {
+        // Transform the keys into `Key`s.
+        let iter = iter.into_iter().map(|k| k.to_key());
+        self.inner.extend(iter);
+    }
+}
+
+impl<K, S, P, H> FromIterator<K> for UniCaseHashSet<S, P, H>
+where
+    K: ToKey<S, P>,
+    S: AsRef<str>,
+    P: FoldPolicy,
+    H: BuildHasher + Default,
+{
+    fn from_iter<T: IntoIterator<Item = K>>(iter: T) -> Self {
+        let mut map = Self::default();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<'a, S, P, H> IntoIterator for &'a UniCaseHashSet<S, P, H>
+where
+    S: AsRef<str>,
+    P: FoldPolicy,
+    H: BuildHasher,
+{
+    type Item = &'a Key<S, P>;
+    type IntoIter = Iter<'a, Key<S, P>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<S, P, H> IntoIterator for UniCaseHashSet<S, P, H> {
+    type Item = Key<S, P>;
+    type IntoIter = IntoIter<Key<S, P>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.inner.into_iter()
+    }
+}
+
+impl UniCaseHashSet {
+    /// Creates a new UniCaseHashSet with the default
+    /// hasher and capacity.
+    pub fn new() -> Self {
+        Self {
+            inner: Default::default(),
+        }
+    }
+
+    /// Creates an empty set with space for at least `capacity` elements.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            inner: HashSet::with_capacity(capacity),
+        }
+    }
+}
+
+impl<S, P, H> UniCaseHashSet<S, P, H> {
+    /// Creates an empty set that uses `hash_builder` to hash the folded keys.
+    pub fn with_hasher(hash_builder: H) -> Self {
+        Self {
+            inner: HashSet::with_hasher(hash_builder),
+        }
+    }
+
+    /// Creates an empty set with space for at least `capacity` elements, using
+    /// `hash_builder` to hash the folded keys.
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: H) -> Self {
+        Self {
+            inner: HashSet::with_capacity_and_hasher(capacity, hash_builder),
+        }
+    }
+
+    /// Returns a reference to the hasher of the set.
+    pub fn hasher(&self) -> &H {
+        self.inner.hasher()
+    }
+}
+
+impl<S, P, H> UniCaseHashSet<S, P, H>
+where
+    S: AsRef<str>,
+    P: FoldPolicy,
+    H: BuildHasher,
+{
+    /// Returns the number of elements the set can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.inner.capacity()
+    }
+
+    /// Clears the map, removing all key-value pairs. Keeps the allocated memory for reuse.
+    pub fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    /// Returns true if the map contains a value for the specified key.
+    /// The key may be a String, str or UniCase value.
+    pub fn contains<K: AsKeyStr>(&self, k: K) -> bool {
+        self.inner.contains(k.as_key_str::<P>())
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    /// The key may be a String, str or UniCase value.
+    pub fn get<K: AsKeyStr>(&self, k: K) -> Option<&Key<S, P>> {
+        self.inner.get(k.as_key_str::<P>())
+    }
+
+    // Adds a value to the set.
+    // Returns whether the value was newly inserted. That is:
+    // If the set did not previously contain an equal value, true is returned.
+    // If the set already contained an equal value, false is returned, and the entry is not updated.
+    pub fn insert<K: ToKey<S, P>>(&mut self, k: K) -> bool {
+        let key = k.to_key();
+        self.inner.insert(key)
+    }
+
+    /// Returns true if the map contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// An iterator visiting all keys in arbitrary order.
+    /// The iterator element type is &'a Key<S, P>.
+    pub fn iter(&self) -> Iter<'_, Key<S, P>> {
+        self.inner.iter()
+    }
+
+    /// Returns the number of elements in the map.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Removes a key from the map, returning the value at the key if the key was previously in the map.
+    /// The key may be a String, str or UniCase value.
+    pub fn remove<K: AsKeyStr>(&mut self, k: K) -> bool {
+        self.inner.remove(k.as_key_str::<P>())
+    }
+
+    /// Reserves capacity for at least `additional` more elements.
+    /// Panics if the new capacity overflows `usize`.
+    pub fn reserve(&mut self, additional: usize) {
+        self.inner.reserve(additional);
+    }
+
+    /// Retains only the elements specified by the predicate.
+    /// In other words, remove all pairs (k, v) such that f(&k,&mut v) returns false.
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&Key<S, P>) -> bool,
+    {
+        self.inner.retain(f);
+    }
+
+    /// Shrinks the capacity of the set as much as possible.
+    pub fn shrink_to_fit(&mut self) {
+        self.inner.shrink_to_fit();
+    }
+
+    /// Adds a value to the set like [`insert`](Self::insert), but first checks the key
+    /// with [`FoldPolicy::validate`] and returns an error if the policy rejects it.
+    pub fn try_insert<K: ToKey<S, P>>(&mut self, k: K) -> Result<bool, InvalidKey> {
+        let key = k.to_key();
+        P::validate(key.as_str())?;
+        Ok(self.inner.insert(key))
+    }
+
+    /// Tries to reserve capacity for at least `additional` more elements, returning an
+    /// error instead of panicking or aborting if the allocation fails.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.inner.try_reserve(additional)
+    }
+}
+
+/// Creates a [`UniCaseHashSet`] with the default key type and policy from keys.
+///
+/// The keys must be string literals. Two keys that are equal under case folding, like
+/// `"Foo"` and `"FOO"`, are a compile error instead of silently overwriting each other.
+#[macro_export]
+macro_rules! unicase_hash_set {
+    ($($key:literal),* $(,)?) => {{
+        const KEYS: &[&str] = &[$($key),*];
+        const _: () = $crate::literal::assert_unique_keys(KEYS);
+        #[allow(unused_mut)]
+        let mut set = $crate::unicase_hash_set::UniCaseHashSet::with_capacity(KEYS.len());
+        $(set.insert($key);)*
+        set
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UniCaseHashSet;
+    use crate::key::Key;
+    use unicase::UniCase;
+
+    #[test]
+    fn new() {
+        let map = UniCaseHashSet::new();
+        assert_eq!(map.len(), 0);
+    }
+
+    #[test]
+    fn clear() {
+        let mut map = UniCaseHashSet::new();
+        assert_eq!(map.len(), 0);
+        map.insert("A");
+        assert_eq!(map.len(), 1);
+        assert!(!map.is_empty());
+
+        map.clear();
+        assert_eq!(map.len(), 0);
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn contains_str() {
+        let mut map = UniCaseHashSet::new();
+        map.insert("A");
+        assert!(map.contains("A"));
+        assert!(map.contains("a"));
+        assert!(!map.contains("B"));
+        assert!(!map.contains("Å"));
+    }
+
+    #[test]
+    fn contains_string() {
+        let mut map = UniCaseHashSet::new();
+        map.insert("A");
+        assert!(map.contains("A".to_string()));
+        assert!(map.contains("a".to_string()));
+        assert!(!map.contains("B".to_string()));
+        assert!(!map.contains("Å".to_string()));
+    }
+
+    #[test]
+    fn get_str() {
+        let mut map = UniCaseHashSet::new();
+        map.insert("A");
+        assert_eq!(map.get("A"), Some(&Key::new("A".to_string())));
+        assert_eq!(map.get("a"), Some(&Key::new("a".to_string())));
+        assert!(map.get("B").is_none());
+        assert!(map.get("Å").is_none());
+    }
+
+    #[test]
+    fn get_string() {
+        let mut map = UniCaseHashSet::new();
+        map.insert("A");
+        assert_eq!(
+            map.get("A".to_string()).unwrap(),
+            &Key::new("a".to_string())
+        );
+    }
+
+    #[test]
+    fn get_unicase() {
+        let mut map = UniCaseHashSet::new();
+        map.insert("A");
+        let uc = UniCase::new("a".to_string());
+        assert_eq!(map.get(uc).unwrap(), &Key::new("A".to_string()));
+    }
+
+    #[test]
+    fn get_key_value() {
+        let mut map = UniCaseHashSet::new();
+        map.insert("A");
+        let result = map.get("a");
+        assert_eq!(result, Some(&Key::new("a".to_string())));
+    }
+
+    #[test]
+    fn insert_str() {
+        let mut map = UniCaseHashSet::new();
+        let result = map.insert("A");
+        assert!(result);
+        let result = map.insert("B");
+        assert!(result);
+        let result = map.insert("A");
+        assert!(!result);
+
+        assert_eq!(map.len(), 2);
+    }
+
+    #[test]
+    fn insert_string() {
+        let mut map = UniCaseHashSet::new();
+        map.insert("A".to_string());
+        map.insert("B".to_string());
+        assert_eq!(map.len(), 2);
+    }
+
+    #[test]
+    fn is_empty() {
+        let mut map = UniCaseHashSet::new();
+        assert!(map.is_empty());
+        map.insert("A");
+        assert!(!map.is_empty());
+    }
+
+    #[test]
+    fn iter() {
+        let mut map = UniCaseHashSet::new();
+        map.insert("A".to_string());
+        map.insert("B".to_string());
+
+        let mut elems: Vec<_> = map.iter().cloned().collect();
+        elems.sort();
+        assert_eq!(
+            elems,
+            vec![Key::new("a".to_string()), Key::new("b".to_string())]
+        );
+    }
+
+    #[test]
+    fn keys() {
+        let mut map = UniCaseHashSet::new();
+        map.insert("A".to_string());
+        map.insert("B".to_string());
+
+        let mut keys: Vec<_> = map.iter().collect();
+        keys.sort();
+        assert_eq!(
+            keys,
+            vec![&Key::new("A".to_string()), &Key::new("B".to_string())]
+        );
+    }
+
+    #[test]
+    fn len() {
+        let mut map = UniCaseHashSet::new();
+        assert_eq!(map.len(), 0);
+        map.insert("A".to_string());
+        assert_eq!(map.len(), 1);
+        map.insert("B".to_string());
+        assert_eq!(map.len(), 2);
+        map.clear();
+        assert_eq!(map.len(), 0);
+    }
+
+    #[test]
+    fn remove() {
+        let mut map = UniCaseHashSet::new();
+        map.insert("A".to_string());
+        map.insert("B".to_string());
+        assert!(map.remove("b"));
+        assert!(!map.remove("b"));
+    }
+
+    #[test]
+    fn remove_entry() {
+        let mut map = UniCaseHashSet::new();
+        map.insert("A".to_string());
+        map.insert("B".to_string());
+        assert!(map.remove("b"));
+        assert!(!map.remove("b"));
+    }
+
+    #[test]
+    fn partial_eq() {
+        let mut map1 = UniCaseHashSet::new();
+        map1.insert("A".to_string());
+        map1.insert("B".to_string());
+        map1.insert("C".to_string());
+
+        let mut map2 = UniCaseHashSet::new();
+        map2.insert("C".to_string());
+        map2.insert("B".to_string());
+        map2.insert("A".to_string());
+
+        assert_eq!(map1, map2);
+    }
+
+    #[test]
+    fn extend() {
+        let mut map = UniCaseHashSet::new();
+        map.insert("A".to_string());
+
+        let v = vec!["A", "B", "C"];
+
+        map.extend(v);
+
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.get("a"), Some(&Key::new("a".to_string())));
+    }
+
+    #[test]
+    fn into_iterator_impls() {
+        let mut map = UniCaseHashSet::new();
+        map.insert("A");
+        map.insert("B");
+
+        // These should all compile.
+        for _ in &map {}
+        for _ in map {}
+    }
+
+    #[test]
+    fn from_iterator() {
+        let v = vec!["A", "B", "C"];
+
+        let _map: UniCaseHashSet = v.into_iter().collect();
+    }
+
+    #[test]
+    fn arc_str_keys() {
+        let mut set = UniCaseHashSet::<std::sync::Arc<str>>::default();
+        set.insert("A");
+        set.insert("a".to_string());
+        assert_eq!(set.len(), 1);
+        assert_eq!(set.get("A").map(|k| &***k), Some("A"));
+    }
+
+    #[test]
+    fn normalized_policy() {
+        use crate::fold::Normalized;
+
+        let mut set = UniCaseHashSet::<String, Normalized>::default();
+        set.insert("\u{00c5}");
+        assert!(!set.insert("a\u{030a}"));
+        assert!(set.contains("\u{212b}"));
+    }
+
+    #[test]
+    fn try_insert() {
+        let mut set = UniCaseHashSet::new();
+        assert_eq!(set.try_insert("a b"), Ok(true));
+        assert_eq!(set.try_insert("A B"), Ok(false));
+    }
+
+    #[test]
+    fn custom_hasher() {
+        use crate::fold::FullUnicode;
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::BuildHasherDefault;
+
+        type FixedState = BuildHasherDefault<DefaultHasher>;
+
+        let mut set =
+            UniCaseHashSet::<String, FullUnicode, FixedState>::with_hasher(FixedState::default());
+        set.insert("A");
+        assert!(set.contains("a"));
+
+        let set = UniCaseHashSet::<String, FullUnicode, _>::with_capacity_and_hasher(
+            8,
+            FixedState::default(),
+        );
+        assert!(set.is_empty());
+
+        let set: UniCaseHashSet<String, FullUnicode, FixedState> =
+            vec!["A", "a"].into_iter().collect();
+        assert_eq!(set.len(), 1);
+    }
+
+    #[test]
+    fn capacity() {
+        let mut set = UniCaseHashSet::with_capacity(10);
+        assert!(set.capacity() >= 10);
+        set.reserve(100);
+        assert!(set.capacity() >= 100);
+        set.insert("A");
+        set.shrink_to_fit();
+        assert!(set.capacity() >= 1 && set.capacity() < 100);
+        assert!(set.try_reserve(1000).is_ok());
+        assert!(set.try_reserve(usize::MAX).is_err());
+        assert!(set.contains("a"));
+    }
+
+    #[test]
+    fn literal_macro() {
+        let set = crate::unicase_hash_set!["GET", "Put",];
+        assert_eq!(set.len(), 2);
+        assert!(set.contains("get"));
+        assert!(set.contains("PUT"));
+    }
+}
@@ -0,0 +1,309 @@
+use crate::fold::{HttpToken, InvalidKey};
+use crate::key::{AsKeyStr, Key, ToKey};
+use crate::unicase_multi_map::{GetAll, Iter, UniCaseMultiMap};
+use http::header::{HeaderMap, HeaderName, HeaderValue, InvalidHeaderName};
+use std::convert::TryFrom;
+
+/// A map of HTTP header fields that keeps every value of repeated fields.
+///
+/// Field names are compared with ASCII case folding and must be RFC 9110 tokens, which
+/// is checked on insert. Values are kept as [`HeaderValue`]s, so converting from and to
+/// [`http::HeaderMap`] is lossless. Names keep the spelling they were first inserted
+/// with; [`iter_canonical`](Self::iter_canonical) returns them in `Content-Type` casing.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UniCaseHeaderMap {
+    inner: UniCaseMultiMap<HeaderValue, String, HttpToken>,
+}
+
+impl From<HeaderMap> for UniCaseHeaderMap {
+    fn from(headers: HeaderMap) -> Self {
+        let mut map = Self::new();
+        let mut last = None;
+        for (name, value) in headers {
+            // Further values of the same field come without a name.
+            if let Some(name) = name {
+                last = Some(name);
+            }
+            if let Some(name) = &last {
+                map.inner.append(name.as_str(), value);
+            }
+        }
+        map
+    }
+}
+
+impl From<&HeaderMap> for UniCaseHeaderMap {
+    fn from(headers: &HeaderMap) -> Self {
+        let mut map = Self::new();
+        for (name, value) in headers {
+            map.inner.append(name.as_str(), value.clone());
+        }
+        map
+    }
+}
+
+/// Converts the map into an [`http::HeaderMap`] with the names in lowercase.
+///
+/// The names are checked to be tokens on insert, and `http` accepts every token as a
+/// [`HeaderName`] up to a length of 65535 bytes. So the conversion fails only if the map
+/// contains a longer name.
+impl TryFrom<&UniCaseHeaderMap> for HeaderMap {
+    type Error = InvalidHeaderName;
+
+    fn try_from(map: &UniCaseHeaderMap) -> Result<Self, Self::Error> {
+        let mut headers = HeaderMap::with_capacity(map.keys_len());
+        for (key, values) in map.inner.groups() {
+            let name = HeaderName::from_bytes(key.as_str().as_bytes())?;
+            for value in values {
+                headers.append(&name, value.clone());
+            }
+        }
+        Ok(headers)
+    }
+}
+
+/// Converts the map into an [`http::HeaderMap`] like the conversion from
+/// `&UniCaseHeaderMap`, which fails only for names longer than 65535 bytes.
+impl TryFrom<UniCaseHeaderMap> for HeaderMap {
+    type Error = InvalidHeaderName;
+
+    fn try_from(map: UniCaseHeaderMap) -> Result<Self, Self::Error> {
+        HeaderMap::try_from(&map)
+    }
+}
+
+impl<'a> IntoIterator for &'a UniCaseHeaderMap {
+    type Item = (&'a Key<String, HttpToken>, &'a HeaderValue);
+    type IntoIter = Iter<'a, HeaderValue, String, HttpToken>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl UniCaseHeaderMap {
+    /// Creates a new UniCaseHeaderMap.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a value to the field, keeping the values that are already there. Returns
+    /// an error if the name is not a valid token.
+    pub fn append<K: ToKey<String, HttpToken>>(
+        &mut self,
+        name: K,
+        value: HeaderValue,
+    ) -> Result<(), InvalidKey> {
+        self.inner.try_append(name, value)
+    }
+
+    /// Clears the map, removing all fields.
+    pub fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    /// Returns true if the map contains at least one value for the field.
+    pub fn contains_key<K: AsKeyStr>(&self, name: K) -> bool {
+        self.inner.contains_key(name)
+    }
+
+    /// Returns the first value of the field.
+    pub fn get<K: AsKeyStr>(&self, name: K) -> Option<&HeaderValue> {
+        self.inner.get(name)
+    }
+
+    /// Returns an iterator over all values of the field in arrival order.
+    pub fn get_all<K: AsKeyStr>(&self, name: K) -> GetAll<'_, HeaderValue> {
+        self.inner.get_all(name)
+    }
+
+    /// Replaces all values of the field with a single value, returning the removed values.
+    /// Returns an error if the name is not a valid token.
+    pub fn insert<K: ToKey<String, HttpToken>>(
+        &mut self,
+        name: K,
+        value: HeaderValue,
+    ) -> Result<Vec<HeaderValue>, InvalidKey> {
+        self.inner.try_insert(name, value)
+    }
+
+    /// Returns true if the map contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// An iterator visiting all fields in arrival order, with the names as inserted.
+    pub fn iter(&self) -> Iter<'_, HeaderValue, String, HttpToken> {
+        self.inner.iter()
+    }
+
+    /// An iterator visiting all fields in arrival order, with the names in canonical
+    /// casing, e.g. `Content-Type`.
+    pub fn iter_canonical(&self) -> impl Iterator<Item = (String, &HeaderValue)> {
+        self.iter()
+            .map(|(name, value)| (canonical_name(name.as_str()), value))
+    }
+
+    /// An iterator visiting every field name once, in the order they were first inserted.
+    pub fn keys(&self) -> impl Iterator<Item = &Key<String, HttpToken>> {
+        self.inner.keys()
+    }
+
+    /// Returns the number of distinct fields in the map.
+    pub fn keys_len(&self) -> usize {
+        self.inner.keys_len()
+    }
+
+    /// Returns the number of values in the map.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Removes a field from the map, returning all of its values in arrival order.
+    pub fn remove_all<K: AsKeyStr>(&mut self, name: K) -> Vec<HeaderValue> {
+        self.inner.remove_all(name)
+    }
+}
+
+/// Returns `name` in canonical header casing, where every letter that starts the name or
+/// follows a `-` is uppercase and all other letters are lowercase, e.g. `Content-Type`.
+pub fn canonical_name(name: &str) -> String {
+    let mut upper = true;
+    name.chars()
+        .map(|c| {
+            let c = if upper {
+                c.to_ascii_uppercase()
+            } else {
+                c.to_ascii_lowercase()
+            };
+            upper = c == '-';
+            c
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{canonical_name, UniCaseHeaderMap};
+    use crate::fold::InvalidKey;
+    use http::header::{HeaderMap, HeaderValue, CONTENT_TYPE, SET_COOKIE};
+    use std::convert::TryFrom;
+
+    fn value(s: &'static str) -> HeaderValue {
+        HeaderValue::from_static(s)
+    }
+
+    #[test]
+    fn append_and_get_all() {
+        let mut map = UniCaseHeaderMap::new();
+        map.append("Set-Cookie", value("a=1")).unwrap();
+        map.append("content-type", value("text/html")).unwrap();
+        map.append("SET-COOKIE", value("b=2")).unwrap();
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.keys_len(), 2);
+        assert_eq!(map.get("Content-Type"), Some(&value("text/html")));
+        assert_eq!(
+            map.get_all("set-cookie").collect::<Vec<_>>(),
+            vec![&value("a=1"), &value("b=2")]
+        );
+        assert_eq!(
+            map.insert("set-cookie", value("c=3")),
+            Ok(vec![value("a=1"), value("b=2")])
+        );
+        assert_eq!(map.remove_all("SET-cookie"), vec![value("c=3")]);
+        assert!(!map.contains_key("set-cookie"));
+    }
+
+    #[test]
+    fn names_must_be_tokens() {
+        let mut map = UniCaseHeaderMap::new();
+        assert_eq!(
+            map.append("Content Type", value("text/html")),
+            Err(InvalidKey::at(7, ' '))
+        );
+        assert_eq!(map.insert("", value("x")), Err(InvalidKey::empty()));
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn ascii_folding_only() {
+        let mut map = UniCaseHeaderMap::new();
+        map.append("x-kelvin", value("1")).unwrap();
+        assert!(map.contains_key("X-KELVIN"));
+        assert!(!map.contains_key("x-\u{212a}elvin"));
+    }
+
+    #[test]
+    fn canonical_casing() {
+        assert_eq!(canonical_name("content-type"), "Content-Type");
+        assert_eq!(canonical_name("X-REQUEST-ID"), "X-Request-Id");
+        assert_eq!(canonical_name("etag"), "Etag");
+        assert_eq!(canonical_name("x--y"), "X--Y");
+
+        let mut map = UniCaseHeaderMap::new();
+        map.append("content-type", value("text/html")).unwrap();
+        map.append("x-forwarded-for", value("1.2.3.4")).unwrap();
+        let names: Vec<_> = map.iter_canonical().map(|(name, _)| name).collect();
+        assert_eq!(names, vec!["Content-Type", "X-Forwarded-For"]);
+        assert_eq!(map.keys().next().unwrap().as_str(), "content-type");
+    }
+
+    #[test]
+    fn from_header_map() {
+        let mut headers = HeaderMap::new();
+        headers.append(SET_COOKIE, value("a=1"));
+        headers.append(CONTENT_TYPE, value("text/html"));
+        headers.append(SET_COOKIE, value("b=2"));
+        headers.append("x-binary", HeaderValue::from_bytes(b"\xff\xfe").unwrap());
+
+        let map = UniCaseHeaderMap::from(&headers);
+        assert_eq!(map.len(), 4);
+        assert_eq!(map.get_all("Set-Cookie").count(), 2);
+        assert_eq!(map.get("X-Binary").unwrap().as_bytes(), b"\xff\xfe");
+        assert_eq!(map, UniCaseHeaderMap::from(headers));
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut headers = HeaderMap::new();
+        headers.append(SET_COOKIE, value("a=1"));
+        headers.append(SET_COOKIE, value("b=2"));
+        headers.append(CONTENT_TYPE, value("text/html"));
+        headers.append("x-binary", HeaderValue::from_bytes(b"\xff").unwrap());
+
+        let map = UniCaseHeaderMap::from(headers.clone());
+        assert_eq!(HeaderMap::try_from(&map).unwrap(), headers);
+        assert_eq!(HeaderMap::try_from(map).unwrap(), headers);
+    }
+
+    #[test]
+    fn into_header_map() {
+        let mut map = UniCaseHeaderMap::new();
+        map.append("Set-Cookie", value("a=1")).unwrap();
+        map.append("Content-Type", value("text/html")).unwrap();
+        map.append("set-cookie", value("b=2")).unwrap();
+
+        let headers = HeaderMap::try_from(&map).unwrap();
+        assert_eq!(headers.len(), 3);
+        assert_eq!(
+            headers.get_all(SET_COOKIE).iter().collect::<Vec<_>>(),
+            vec![&value("a=1"), &value("b=2")]
+        );
+        assert_eq!(headers[CONTENT_TYPE], value("text/html"));
+    }
+
+    #[test]
+    fn into_header_map_fails_only_for_long_names() {
+        let mut map = UniCaseHeaderMap::new();
+        let tchars =
+            "!#$%&'*+-.^_`|~0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+        map.append(tchars, value("1")).unwrap();
+        map.append("x".repeat(65535), value("2")).unwrap();
+        let headers = HeaderMap::try_from(&map).unwrap();
+        assert_eq!(headers[tchars.to_ascii_lowercase().as_str()], value("1"));
+
+        map.append("y".repeat(65536), value("3")).unwrap();
+        assert!(HeaderMap::try_from(&map).is_err());
+        assert!(HeaderMap::try_from(map).is_err());
+    }
+}
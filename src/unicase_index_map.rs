@@ -1,114 +1,195 @@
-use crate::key::{Key, ToKey};
+use crate::fold::{FoldPolicy, FullUnicode, InvalidKey};
+use crate::key::{AsKeyStr, Key, ToKey};
 use indexmap::map::{Entry, IntoIter, Iter, IterMut, Keys, Values, ValuesMut};
-use indexmap::IndexMap;
+use indexmap::{IndexMap, TryReserveError};
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
 use std::iter::FromIterator;
 use std::ops::Index;
 
-#[derive(Debug, Default, Clone)]
-pub struct UniCaseIndexMap<V> {
-    inner: IndexMap<Key, V>,
+#[derive(Debug, Clone)]
+pub struct UniCaseIndexMap<V, S = String, P = FullUnicode, H = RandomState> {
+    inner: IndexMap<Key<S, P>, V, H>,
 }
 
-impl<V> Eq for UniCaseIndexMap<V> where V: Eq {}
+impl<V, S, P, H: Default> Default for UniCaseIndexMap<V, S, P, H> {
+    fn default() -> Self {
+        Self {
+            inner: Default::default(),
+        }
+    }
+}
+
+impl<V, S, P, H> Eq for UniCaseIndexMap<V, S, P, H>
+where
+    V: Eq,
+    S: AsRef<str>,
+    P: FoldPolicy,
+    H: BuildHasher,
+{
+}
 
-impl<V> PartialEq for UniCaseIndexMap<V>
+impl<V, S, P, H> PartialEq for UniCaseIndexMap<V, S, P, H>
 where
     V: PartialEq,
+    S: AsRef<str>,
+    P: FoldPolicy,
+    H: BuildHasher,
 {
-    fn eq(&self, other: &UniCaseIndexMap<V>) -> bool {
+    fn eq(&self, other: &UniCaseIndexMap<V, S, P, H>) -> bool {
         if self.len() != other.len() {
             return false;
         }
 
         self.iter()
-            .all(|(key, value)| other.get(&***key).map_or(false, |v| *value == *v))
+            .all(|(key, value)| other.get(key) == Some(value))
     }
 }
 
-impl<K, V> Extend<(K, V)> for UniCaseIndexMap<V>
+impl<K, V, S, P, H> Extend<(K, V)> for UniCaseIndexMap<V, S, P, H>
 where
-    K: ToKey,
+    K: ToKey<S, P>,
+    S: AsRef<str>,
+    P: FoldPolicy,
+    H: BuildHasher,
 {
     fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
-        // Transform the keys into UniCases.
+        // Transform the keys into `Key`s.
         let iter = iter.into_iter().map(|(k, v)| (k.to_key(), v));
         self.inner.extend(iter);
     }
 }
 
-impl<'a, K, V> Extend<(K, &'a V)> for UniCaseIndexMap<V>
+impl<'a, K, V, S, P, H> Extend<(K, &'a V)> for UniCaseIndexMap<V, S, P, H>
 where
-    K: ToKey,
+    K: ToKey<S, P>,
+    S: AsRef<str>,
+    P: FoldPolicy,
+    H: BuildHasher,
     V: Copy,
 {
     fn extend<T: IntoIterator<Item = (K, &'a V)>>(&mut self, iter: T) {
-        // Transform the keys into UniCases and copy the values.
+        // Transform the keys into `Key`s and copy the values.
         let iter = iter.into_iter().map(|(k, v)| (k.to_key(), *v));
         self.inner.extend(iter);
     }
 }
 
-impl<K, V> FromIterator<(K, V)> for UniCaseIndexMap<V>
+impl<K, V, S, P, H> FromIterator<(K, V)> for UniCaseIndexMap<V, S, P, H>
 where
-    K: ToKey,
+    K: ToKey<S, P>,
+    S: AsRef<str>,
+    P: FoldPolicy,
+    H: BuildHasher + Default,
 {
     fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
-        let mut map = Self::new();
+        let mut map = Self::default();
         map.extend(iter);
         map
     }
 }
 
-impl<'a, V> IntoIterator for &'a UniCaseIndexMap<V> {
-    type Item = (&'a Key, &'a V);
-    type IntoIter = Iter<'a, Key, V>;
+impl<'a, V, S, P, H> IntoIterator for &'a UniCaseIndexMap<V, S, P, H>
+where
+    S: AsRef<str>,
+    P: FoldPolicy,
+    H: BuildHasher,
+{
+    type Item = (&'a Key<S, P>, &'a V);
+    type IntoIter = Iter<'a, Key<S, P>, V>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.iter()
     }
 }
 
-impl<'a, V> IntoIterator for &'a mut UniCaseIndexMap<V> {
-    type Item = (&'a Key, &'a mut V);
-    type IntoIter = IterMut<'a, Key, V>;
+impl<'a, V, S, P, H> IntoIterator for &'a mut UniCaseIndexMap<V, S, P, H>
+where
+    S: AsRef<str>,
+    P: FoldPolicy,
+    H: BuildHasher,
+{
+    type Item = (&'a Key<S, P>, &'a mut V);
+    type IntoIter = IterMut<'a, Key<S, P>, V>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.iter_mut()
     }
 }
 
-impl<V> IntoIterator for UniCaseIndexMap<V> {
-    type Item = (Key, V);
-    type IntoIter = IntoIter<Key, V>;
+impl<V, S, P, H> IntoIterator for UniCaseIndexMap<V, S, P, H> {
+    type Item = (Key<S, P>, V);
+    type IntoIter = IntoIter<Key<S, P>, V>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.inner.into_iter()
     }
 }
 
-impl<'a, K, V> Index<K> for UniCaseIndexMap<V>
+impl<K, V, S, P, H> Index<K> for UniCaseIndexMap<V, S, P, H>
 where
-    K: ToKey,
+    K: AsKeyStr,
+    S: AsRef<str>,
+    P: FoldPolicy,
+    H: BuildHasher,
 {
     type Output = V;
 
     fn index(&self, index: K) -> &Self::Output {
-        let key = index.to_key();
-        self.inner.index(&key)
+        self.inner.index(index.as_key_str::<P>())
     }
 }
 
 impl<V> UniCaseIndexMap<V> {
-    /// Creates a new UniCaseBTreeMap with the default
+    /// Creates a new UniCaseIndexMap with the default
     /// hasher and capacity.
     pub fn new() -> Self {
         Self {
             inner: Default::default(),
         }
     }
+
+    /// Creates an empty map with space for at least `capacity` elements.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            inner: IndexMap::with_capacity(capacity),
+        }
+    }
 }
 
-impl<V> UniCaseIndexMap<V> {
+impl<V, S, P, H> UniCaseIndexMap<V, S, P, H> {
+    /// Creates an empty map that uses `hash_builder` to hash the folded keys.
+    pub fn with_hasher(hash_builder: H) -> Self {
+        Self {
+            inner: IndexMap::with_hasher(hash_builder),
+        }
+    }
+
+    /// Creates an empty map with space for at least `capacity` elements, using
+    /// `hash_builder` to hash the folded keys.
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: H) -> Self {
+        Self {
+            inner: IndexMap::with_capacity_and_hasher(capacity, hash_builder),
+        }
+    }
+
+    /// Returns a reference to the hasher of the map.
+    pub fn hasher(&self) -> &H {
+        self.inner.hasher()
+    }
+}
+
+impl<V, S, P, H> UniCaseIndexMap<V, S, P, H>
+where
+    S: AsRef<str>,
+    P: FoldPolicy,
+    H: BuildHasher,
+{
+    /// Returns the number of elements the map can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.inner.capacity()
+    }
+
     /// Clears the map, removing all key-value pairs. Keeps the allocated memory for reuse.
     pub fn clear(&mut self) {
         self.inner.clear();
@@ -116,36 +197,32 @@ impl<V> UniCaseIndexMap<V> {
 
     /// Returns true if the map contains a value for the specified key.
     /// The key may be a String, str or UniCase value.
-    pub fn contains_key<K: ToKey>(&self, k: K) -> bool {
-        let key = k.to_key();
-        self.inner.contains_key(&key)
+    pub fn contains_key<K: AsKeyStr>(&self, k: K) -> bool {
+        self.inner.contains_key(k.as_key_str::<P>())
     }
 
     /// Gets the given key's corresponding entry in the map for in-place manipulation.
-    pub fn entry<K: ToKey>(&mut self, k: K) -> Entry<'_, Key, V> {
+    pub fn entry<K: ToKey<S, P>>(&mut self, k: K) -> Entry<'_, Key<S, P>, V> {
         let key = k.to_key();
         self.inner.entry(key)
     }
 
     /// Returns a reference to the value corresponding to the key.
     /// The key may be a String, str or UniCase value.
-    pub fn get<K: ToKey>(&self, k: K) -> Option<&V> {
-        let key = k.to_key();
-        self.inner.get(&key)
+    pub fn get<K: AsKeyStr>(&self, k: K) -> Option<&V> {
+        self.inner.get(k.as_key_str::<P>())
     }
 
     /// Returns the key-value pair corresponding to the supplied key.
     /// The key may be a String, str or UniCase value.
-    pub fn get_key_value<K: ToKey>(&self, k: K) -> Option<(&Key, &V)> {
-        let key = k.to_key();
-        self.inner.get_key_value(&key)
+    pub fn get_key_value<K: AsKeyStr>(&self, k: K) -> Option<(&Key<S, P>, &V)> {
+        self.inner.get_key_value(k.as_key_str::<P>())
     }
 
     /// Returns a mutable reference to the value corresponding to the key.
     /// The key may be a String, str or UniCase value.
-    pub fn get_mut<K: ToKey>(&mut self, k: K) -> Option<&mut V> {
-        let key = k.to_key();
-        self.inner.get_mut(&key)
+    pub fn get_mut<K: AsKeyStr>(&mut self, k: K) -> Option<&mut V> {
+        self.inner.get_mut(k.as_key_str::<P>())
     }
 
     /// Inserts a key-value pair into the map.
@@ -154,7 +231,7 @@ impl<V> UniCaseIndexMap<V> {
     /// The key is not updated, though; this matters for types that can be == without being identical.
     /// See the module-level documentation of [BTreeMap](https://doc.rust-lang.org/std/collections/index.html#insert-and-complex-keys)
     // for more.
-    pub fn insert<K: ToKey>(&mut self, k: K, v: V) -> Option<V> {
+    pub fn insert<K: ToKey<S, P>>(&mut self, k: K, v: V) -> Option<V> {
         let key = k.to_key();
         self.inner.insert(key, v)
     }
@@ -164,21 +241,21 @@ impl<V> UniCaseIndexMap<V> {
         self.inner.is_empty()
     }
 
-    /// An iterator visiting all key-value pairs in arbitrary order.
-    /// The iterator element type is (&'a UniCase<String>, &'a V).
-    pub fn iter(&self) -> Iter<Key, V> {
+    /// An iterator visiting all key-value pairs in insertion order.
+    /// The iterator element type is (&'a Key<S, P>, &'a V).
+    pub fn iter(&self) -> Iter<'_, Key<S, P>, V> {
         self.inner.iter()
     }
 
-    /// An iterator visiting all key-value pairs in arbitrary order,
+    /// An iterator visiting all key-value pairs in insertion order,
     /// with mutable references to the values. The iterator element type is
-    /// (&'a UniCase<String>, &'a mut V).
-    pub fn iter_mut(&mut self) -> IterMut<Key, V> {
+    /// (&'a Key<S, P>, &'a mut V).
+    pub fn iter_mut(&mut self) -> IterMut<'_, Key<S, P>, V> {
         self.inner.iter_mut()
     }
 
-    /// An iterator visiting all keys in arbitrary order. The iterator element type is &'a UniCase<String>.
-    pub fn keys(&self) -> Keys<Key, V> {
+    /// An iterator visiting all keys in insertion order. The iterator element type is &'a Key<S, P>.
+    pub fn keys(&self) -> Keys<'_, Key<S, P>, V> {
         self.inner.keys()
     }
 
@@ -189,41 +266,82 @@ impl<V> UniCaseIndexMap<V> {
 
     /// Removes a key from the map, returning the value at the key if the key was previously in the map.
     /// The key may be a String, str or UniCase value.
-    pub fn remove<K: ToKey>(&mut self, k: K) -> Option<V> {
-        let key = k.to_key();
-        self.inner.remove(&key)
+    pub fn remove<K: AsKeyStr>(&mut self, k: K) -> Option<V> {
+        self.inner.swap_remove(k.as_key_str::<P>())
     }
 
     /// Removes a key from the map, returning the stored key and value if the key was previously in the map.
     /// The key may be a String, str or UniCase value.
-    pub fn remove_entry<K: ToKey>(&mut self, k: K) -> Option<(Key, V)> {
-        let key = k.to_key();
-        self.inner.remove_entry(&key)
+    pub fn remove_entry<K: AsKeyStr>(&mut self, k: K) -> Option<(Key<S, P>, V)> {
+        self.inner.swap_remove_entry(k.as_key_str::<P>())
+    }
+
+    /// Reserves capacity for at least `additional` more elements.
+    /// Panics if the new capacity overflows `usize`.
+    pub fn reserve(&mut self, additional: usize) {
+        self.inner.reserve(additional);
     }
 
     /// Retains only the elements specified by the predicate.
     /// In other words, remove all pairs (k, v) such that f(&k,&mut v) returns false.
     pub fn retain<F>(&mut self, f: F)
     where
-        F: FnMut(&Key, &mut V) -> bool,
+        F: FnMut(&Key<S, P>, &mut V) -> bool,
     {
         self.inner.retain(f);
     }
 
-    /// An iterator visiting all values in arbitrary order. The iterator element type is &'a V.
-    pub fn values(&self) -> Values<Key, V> {
+    /// Shrinks the capacity of the map as much as possible.
+    pub fn shrink_to_fit(&mut self) {
+        self.inner.shrink_to_fit();
+    }
+
+    /// Inserts a key-value pair into the map like [`insert`](Self::insert), but first
+    /// checks the key with [`FoldPolicy::validate`] and returns an error if the policy
+    /// rejects it.
+    pub fn try_insert<K: ToKey<S, P>>(&mut self, k: K, v: V) -> Result<Option<V>, InvalidKey> {
+        let key = k.to_key();
+        P::validate(key.as_str())?;
+        Ok(self.inner.insert(key, v))
+    }
+
+    /// Tries to reserve capacity for at least `additional` more elements, returning an
+    /// error instead of panicking or aborting if the allocation fails.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.inner.try_reserve(additional)
+    }
+
+    /// An iterator visiting all values in insertion order. The iterator element type is &'a V.
+    pub fn values(&self) -> Values<'_, Key<S, P>, V> {
         self.inner.values()
     }
 
-    /// An iterator visiting all values mutably in arbitrary order. The iterator element type is &'a mut V.
-    pub fn values_mut(&mut self) -> ValuesMut<Key, V> {
+    /// An iterator visiting all values mutably in insertion order. The iterator element type is &'a mut V.
+    pub fn values_mut(&mut self) -> ValuesMut<'_, Key<S, P>, V> {
         self.inner.values_mut()
     }
 }
 
+/// Creates a [`UniCaseIndexMap`] with the default key type and policy from `key => value` pairs.
+///
+/// The keys must be string literals. Two keys that are equal under case folding, like
+/// `"Foo"` and `"FOO"`, are a compile error instead of silently overwriting each other.
+#[macro_export]
+macro_rules! unicase_index_map {
+    ($($key:literal => $value:expr),* $(,)?) => {{
+        const KEYS: &[&str] = &[$($key),*];
+        const _: () = $crate::literal::assert_unique_keys(KEYS);
+        #[allow(unused_mut)]
+        let mut map = $crate::unicase_index_map::UniCaseIndexMap::with_capacity(KEYS.len());
+        $(map.insert($key, $value);)*
+        map
+    }};
+}
+
 #[cfg(test)]
 mod tests {
     use super::UniCaseIndexMap;
+    use crate::key::Key;
     use unicase::UniCase;
 
     #[test]
@@ -270,7 +388,7 @@ mod tests {
         let mut map = UniCaseIndexMap::<u8>::new();
         map.insert("A", 1);
         let entry = map.entry("A");
-        assert_eq!(entry.key(), &UniCase::new("A".to_string()));
+        assert_eq!(entry.key(), &Key::new("A".to_string()));
     }
 
     #[test]
@@ -294,7 +412,6 @@ mod tests {
     fn get_unicase() {
         let mut map = UniCaseIndexMap::<u8>::new();
         map.insert("A", 1);
-        // Won't work with plain &str, which is annoying.
         let uc = UniCase::new("a".to_string());
         assert_eq!(map.get(uc).unwrap(), &1);
     }
@@ -304,7 +421,7 @@ mod tests {
         let mut map = UniCaseIndexMap::<u8>::new();
         map.insert("A", 1);
         let result = map.get_key_value("a");
-        assert_eq!(result.unwrap().0, &UniCase::new("a".to_string()));
+        assert_eq!(result.unwrap().0, &Key::new("a".to_string()));
         assert_eq!(result.unwrap().1, &1);
     }
 
@@ -351,7 +468,7 @@ mod tests {
         map.insert("A".to_string(), 1);
         map.insert("B".to_string(), 2);
 
-        let mut elems: Vec<_> = map.iter().map(|(_, v)| v.clone()).collect();
+        let mut elems: Vec<_> = map.iter().map(|(_, v)| *v).collect();
         elems.sort();
         assert_eq!(elems, vec![1, 2]);
     }
@@ -366,7 +483,7 @@ mod tests {
             *v += 10;
         }
 
-        let mut elems: Vec<_> = map.iter().map(|(_, v)| v.clone()).collect();
+        let mut elems: Vec<_> = map.iter().map(|(_, v)| *v).collect();
         elems.sort();
         assert_eq!(elems, vec![11, 12]);
     }
@@ -381,10 +498,7 @@ mod tests {
         keys.sort();
         assert_eq!(
             keys,
-            vec![
-                &UniCase::new("A".to_string()),
-                &UniCase::new("B".to_string())
-            ]
+            vec![&Key::new("A".to_string()), &Key::new("B".to_string())]
         );
     }
 
@@ -517,4 +631,217 @@ mod tests {
 
         let _map: UniCaseIndexMap<u8> = v.into_iter().collect();
     }
+
+    #[test]
+    fn box_str_keys() {
+        let mut map = UniCaseIndexMap::<u8, Box<str>>::default();
+        map.insert("A", 1);
+        map.insert("b".to_string(), 2);
+        assert_eq!(map["a"], 1);
+        assert_eq!(map["B"], 2);
+    }
+
+    #[test]
+    fn cow_keys() {
+        use std::borrow::Cow;
+
+        let mut map: UniCaseIndexMap<u8, Cow<'static, str>> =
+            vec![("A", 1), ("B", 2)].into_iter().collect();
+        map.insert("c".to_string(), 3);
+        assert!(matches!(
+            map.keys().next().map(|k| &**k),
+            Some(&Cow::Borrowed("A"))
+        ));
+        assert_eq!(map.get("C"), Some(&3));
+    }
+
+    #[test]
+    fn simple_unicode_policy() {
+        use crate::fold::SimpleUnicode;
+
+        let mut map = UniCaseIndexMap::<u8, &str, SimpleUnicode>::default();
+        map.insert("K", 1);
+        map.insert("\u{212a}", 2);
+        map.insert("Maße", 3);
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get("k"), Some(&2));
+        assert_eq!(map.get("MASSE"), None);
+    }
+
+    #[test]
+    fn full_folding_expansions() {
+        let mut map = UniCaseIndexMap::<u8>::new();
+        map.insert("straße", 1);
+        map.insert("STRASSE", 2);
+        map.insert("\u{0390}", 3);
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get("strasse"), Some(&2));
+        assert_eq!(map.get("STRAẞE"), Some(&2));
+        assert_eq!(map.get("\u{03b9}\u{0308}\u{0301}"), Some(&3));
+        assert_eq!(map.get("\u{0399}\u{0308}\u{0301}"), Some(&3));
+    }
+
+    #[test]
+    fn normalized_policy() {
+        use crate::fold::Normalized;
+
+        let mut map = UniCaseIndexMap::<u8, String, Normalized>::default();
+        map.insert("A\u{030a}", 1);
+        map.insert("\u{00c5}", 2);
+        map.insert("\u{00e5}", 3);
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.get("\u{212b}"), Some(&3));
+        assert_eq!(map.keys().next().unwrap().as_str(), "A\u{030a}");
+    }
+
+    #[test]
+    fn accent_insensitive_policy() {
+        use crate::fold::AccentInsensitive;
+
+        let mut map = UniCaseIndexMap::<u8, String, AccentInsensitive>::default();
+        map.insert("café", 1);
+        assert_eq!(map.get("Cafe"), Some(&1));
+        assert_eq!(map.get("CAFÉ"), Some(&1));
+        assert_eq!(map.get("cafes"), None);
+        assert_eq!(map.keys().next().unwrap().as_str(), "café");
+    }
+
+    #[test]
+    fn separator_insensitive_policy() {
+        use crate::fold::SeparatorInsensitive;
+
+        let mut map = UniCaseIndexMap::<u8, String, SeparatorInsensitive>::default();
+        map.insert("content_type", 1);
+        map.insert("max-age", 2);
+        assert_eq!(map.get("Content-Type"), Some(&1));
+        assert_eq!(map.get("contentType"), Some(&1));
+        assert_eq!(map.get(" content type "), Some(&1));
+        assert_eq!(map.get("maxAge"), Some(&2));
+
+        map.insert("ContentType", 3);
+        assert_eq!(map.len(), 2);
+        let keys: Vec<_> = map.keys().map(|k| k.as_str()).collect();
+        assert_eq!(keys, vec!["content_type", "max-age"]);
+    }
+
+    #[test]
+    fn tailored_policy() {
+        use crate::fold::{Tailored, Tr};
+
+        let mut turkish = UniCaseIndexMap::<u8, String, Tailored<Tr>>::default();
+        turkish.insert("İstanbul", 34);
+        turkish.insert("Isparta", 32);
+        assert_eq!(turkish.get("istanbul"), Some(&34));
+        assert_eq!(turkish.get("ISTANBUL"), None);
+        assert_eq!(turkish.get("ısparta"), Some(&32));
+
+        let mut other = UniCaseIndexMap::<u8>::new();
+        other.insert("İstanbul", 34);
+        assert_eq!(other.get("istanbul"), None);
+    }
+
+    #[test]
+    fn east_asian_policies() {
+        use crate::fold::{KanaInsensitive, WidthInsensitive};
+
+        let mut catalog =
+            UniCaseIndexMap::<u32, String, KanaInsensitive<WidthInsensitive>>::default();
+        catalog.insert("ABC123", 1);
+        catalog.insert("ガイドブック", 2);
+        assert_eq!(catalog.get("ＡＢＣ１２３"), Some(&1));
+        assert_eq!(catalog.get("ａｂｃ123"), Some(&1));
+        assert_eq!(catalog.get("ｶﾞｲﾄﾞﾌﾞｯｸ"), Some(&2));
+        assert_eq!(catalog.get("がいどぶっく"), Some(&2));
+        assert_eq!(
+            catalog.get_key_value("ｶﾞｲﾄﾞﾌﾞｯｸ").unwrap().0.as_str(),
+            "ガイドブック"
+        );
+    }
+
+    #[test]
+    fn identifier_policy() {
+        use crate::fold::{Identifier, InvalidKey};
+
+        let mut symbols = UniCaseIndexMap::<u32, String, Identifier>::default();
+        assert_eq!(symbols.try_insert("ﬁle", 1), Ok(None));
+        assert_eq!(symbols.try_insert("FILE", 2), Ok(Some(1)));
+        assert_eq!(symbols.try_insert("2nd", 3), Err(InvalidKey::at(0, '2')));
+        assert_eq!(symbols.try_insert("", 4), Err(InvalidKey::empty()));
+        assert_eq!(symbols.len(), 1);
+        assert_eq!(symbols.get("ｆｉｌｅ"), Some(&2));
+        assert_eq!(symbols.get("fi\u{200b}le"), Some(&2));
+    }
+
+    #[test]
+    fn phonetic_policies() {
+        use crate::fold::{DoubleMetaphone, Soundex};
+
+        let customers = [(1, "Smith"), (2, "Jones"), (3, "Smyth"), (4, "SMITHE")];
+
+        let mut by_soundex = UniCaseIndexMap::<Vec<u32>, String, Soundex>::default();
+        let mut by_metaphone = UniCaseIndexMap::<Vec<u32>, String, DoubleMetaphone>::default();
+        for (id, name) in customers {
+            by_soundex.entry(name).or_default().push(id);
+            by_metaphone.entry(name).or_default().push(id);
+        }
+
+        assert_eq!(by_soundex.len(), 2);
+        assert_eq!(by_soundex.get("smith"), Some(&vec![1, 3, 4]));
+        assert_eq!(by_soundex.get("Smitt"), Some(&vec![1, 3, 4]));
+        assert_eq!(by_metaphone.get("Smythe"), Some(&vec![1, 3, 4]));
+        assert_eq!(by_metaphone.get("Jonez"), Some(&vec![2]));
+        assert_eq!(by_metaphone.keys().next().unwrap().as_str(), "Smith");
+    }
+
+    #[test]
+    fn custom_hasher() {
+        use crate::fold::FullUnicode;
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::BuildHasherDefault;
+
+        type FixedState = BuildHasherDefault<DefaultHasher>;
+
+        let mut map = UniCaseIndexMap::<u8, String, FullUnicode, FixedState>::with_hasher(
+            FixedState::default(),
+        );
+        map.insert("A", 1);
+        assert_eq!(map.get("a"), Some(&1));
+
+        let map = UniCaseIndexMap::<u8, String, FullUnicode, _>::with_capacity_and_hasher(
+            8,
+            FixedState::default(),
+        );
+        assert!(map.is_empty());
+
+        let map: UniCaseIndexMap<u8, String, FullUnicode, FixedState> =
+            vec![("A", 1), ("a", 2)].into_iter().collect();
+        assert_eq!(map.len(), 1);
+        assert_eq!(map, map.clone());
+    }
+
+    #[test]
+    fn capacity() {
+        let mut map = UniCaseIndexMap::<u8>::with_capacity(10);
+        assert!(map.capacity() >= 10);
+        map.reserve(100);
+        assert!(map.capacity() >= 100);
+        map.insert("A", 1);
+        map.shrink_to_fit();
+        assert!(map.capacity() >= 1 && map.capacity() < 100);
+        assert!(map.try_reserve(1000).is_ok());
+        assert!(map.capacity() >= 1001);
+        assert!(map.try_reserve(usize::MAX).is_err());
+        assert_eq!(map["a"], 1);
+    }
+
+    #[test]
+    fn literal_macro() {
+        let map = crate::unicase_index_map! {
+            "b" => 2,
+            "A" => 1,
+        };
+        assert_eq!(map.get("a"), Some(&1));
+        let keys: Vec<_> = map.keys().map(|k| k.as_str()).collect();
+        assert_eq!(keys, vec!["b", "A"]);
+    }
 }
@@ -1,57 +1,84 @@
-use crate::key::{Key, ToKey};
+use crate::fold::{FoldPolicy, FullUnicode, InvalidKey};
+use crate::key::{AsKeyStr, Key, ToKey};
 use indexmap::set::{IntoIter, Iter};
-use indexmap::IndexSet;
+use indexmap::{IndexSet, TryReserveError};
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
 use std::iter::FromIterator;
 
-#[derive(Debug, Default, Clone)]
-pub struct UniCaseIndexSet {
-    inner: IndexSet<Key>,
+#[derive(Debug, Clone)]
+pub struct UniCaseIndexSet<S = String, P = FullUnicode, H = RandomState> {
+    inner: IndexSet<Key<S, P>, H>,
 }
 
-impl PartialEq for UniCaseIndexSet {
-    fn eq(&self, other: &UniCaseIndexSet) -> bool {
+impl<S, P, H: Default> Default for UniCaseIndexSet<S, P, H> {
+    fn default() -> Self {
+        Self {
+            inner: Default::default(),
+        }
+    }
+}
+
+impl<S, P, H> PartialEq for UniCaseIndexSet<S, P, H>
+where
+    S: AsRef<str>,
+    P: FoldPolicy,
+    H: BuildHasher,
+{
+    fn eq(&self, other: &UniCaseIndexSet<S, P, H>) -> bool {
         if self.len() != other.len() {
             return false;
         }
 
-        self.iter().all(|key| other.contains(key.as_ref()))
+        self.iter().all(|key| other.contains(key))
     }
 }
 
-impl<K> Extend<K> for UniCaseIndexSet
+impl<K, S, P, H> Extend<K> for UniCaseIndexSet<S, P, H>
 where
-    K: ToKey,
+    K: ToKey<S, P>,
+    S: AsRef<str>,
+    P: FoldPolicy,
+    H: BuildHasher,
 {
     fn extend<T: IntoIterator<Item = K>>(&mut self, iter: T) {
-        // Transform the keys into UniCases.
+        // Transform the keys into `Key`s.
         let iter = iter.into_iter().map(|k| k.to_key());
         self.inner.extend(iter);
     }
 }
 
-impl<K> FromIterator<K> for UniCaseIndexSet
+impl<K, S, P, H> FromIterator<K> for UniCaseIndexSet<S, P, H>
 where
-    K: ToKey,
+    K: ToKey<S, P>,
+    S: AsRef<str>,
+    P: FoldPolicy,
+    H: BuildHasher + Default,
 {
     fn from_iter<T: IntoIterator<Item = K>>(iter: T) -> Self {
-        let mut map = Self::new();
+        let mut map = Self::default();
         map.extend(iter);
         map
     }
 }
 
-impl<'a> IntoIterator for &'a UniCaseIndexSet {
-    type Item = &'a Key;
-    type IntoIter = Iter<'a, Key>;
+impl<'a, S, P, H> IntoIterator for &'a UniCaseIndexSet<S, P, H>
+where
+    S: AsRef<str>,
+    P: FoldPolicy,
+    H: BuildHasher,
+{
+    type Item = &'a Key<S, P>;
+    type IntoIter = Iter<'a, Key<S, P>>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.iter()
     }
 }
 
-impl IntoIterator for UniCaseIndexSet {
-    type Item = Key;
-    type IntoIter = IntoIter<Key>;
+impl<S, P, H> IntoIterator for UniCaseIndexSet<S, P, H> {
+    type Item = Key<S, P>;
+    type IntoIter = IntoIter<Key<S, P>>;
 
     fn into_iter(self) -> Self::IntoIter {
         self.inner.into_iter()
@@ -59,16 +86,55 @@ impl IntoIterator for UniCaseIndexSet {
 }
 
 impl UniCaseIndexSet {
-    /// Creates a new UniCaseBTreeSet with the default
+    /// Creates a new UniCaseIndexSet with the default
     /// hasher and capacity.
     pub fn new() -> Self {
         Self {
             inner: Default::default(),
         }
     }
+
+    /// Creates an empty set with space for at least `capacity` elements.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            inner: IndexSet::with_capacity(capacity),
+        }
+    }
 }
 
-impl UniCaseIndexSet {
+impl<S, P, H> UniCaseIndexSet<S, P, H> {
+    /// Creates an empty set that uses `hash_builder` to hash the folded keys.
+    pub fn with_hasher(hash_builder: H) -> Self {
+        Self {
+            inner: IndexSet::with_hasher(hash_builder),
+        }
+    }
+
+    /// Creates an empty set with space for at least `capacity` elements, using
+    /// `hash_builder` to hash the folded keys.
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: H) -> Self {
+        Self {
+            inner: IndexSet::with_capacity_and_hasher(capacity, hash_builder),
+        }
+    }
+
+    /// Returns a reference to the hasher of the set.
+    pub fn hasher(&self) -> &H {
+        self.inner.hasher()
+    }
+}
+
+impl<S, P, H> UniCaseIndexSet<S, P, H>
+where
+    S: AsRef<str>,
+    P: FoldPolicy,
+    H: BuildHasher,
+{
+    /// Returns the number of elements the set can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.inner.capacity()
+    }
+
     /// Clears the map, removing all key-value pairs. Keeps the allocated memory for reuse.
     pub fn clear(&mut self) {
         self.inner.clear();
@@ -76,23 +142,21 @@ impl UniCaseIndexSet {
 
     /// Returns true if the map contains a value for the specified key.
     /// The key may be a String, str or UniCase value.
-    pub fn contains<K: ToKey>(&self, k: K) -> bool {
-        let key = k.to_key();
-        self.inner.contains(&key)
+    pub fn contains<K: AsKeyStr>(&self, k: K) -> bool {
+        self.inner.contains(k.as_key_str::<P>())
     }
 
     /// Returns a reference to the value corresponding to the key.
     /// The key may be a String, str or UniCase value.
-    pub fn get<K: ToKey>(&self, k: K) -> Option<&Key> {
-        let key = k.to_key();
-        self.inner.get(&key)
+    pub fn get<K: AsKeyStr>(&self, k: K) -> Option<&Key<S, P>> {
+        self.inner.get(k.as_key_str::<P>())
     }
 
     // Adds a value to the set.
     // Returns whether the value was newly inserted. That is:
     // If the set did not previously contain an equal value, true is returned.
     // If the set already contained an equal value, false is returned, and the entry is not updated.
-    pub fn insert<K: ToKey>(&mut self, k: K) -> bool {
+    pub fn insert<K: ToKey<S, P>>(&mut self, k: K) -> bool {
         let key = k.to_key();
         self.inner.insert(key)
     }
@@ -102,9 +166,9 @@ impl UniCaseIndexSet {
         self.inner.is_empty()
     }
 
-    /// An iterator visiting all key-value pairs in arbitrary order.
-    /// The iterator element type is (&'a UniCase<String>, &'a V).
-    pub fn iter(&self) -> Iter<Key> {
+    /// An iterator visiting all keys in insertion order.
+    /// The iterator element type is &'a Key<S, P>.
+    pub fn iter(&self) -> Iter<'_, Key<S, P>> {
         self.inner.iter()
     }
 
@@ -115,24 +179,65 @@ impl UniCaseIndexSet {
 
     /// Removes a key from the map, returning the value at the key if the key was previously in the map.
     /// The key may be a String, str or UniCase value.
-    pub fn remove<K: ToKey>(&mut self, k: K) -> bool {
-        let key = k.to_key();
-        self.inner.remove(&key)
+    pub fn remove<K: AsKeyStr>(&mut self, k: K) -> bool {
+        self.inner.swap_remove(k.as_key_str::<P>())
+    }
+
+    /// Reserves capacity for at least `additional` more elements.
+    /// Panics if the new capacity overflows `usize`.
+    pub fn reserve(&mut self, additional: usize) {
+        self.inner.reserve(additional);
     }
 
     /// Retains only the elements specified by the predicate.
     /// In other words, remove all pairs (k, v) such that f(&k,&mut v) returns false.
     pub fn retain<F>(&mut self, f: F)
     where
-        F: FnMut(&Key) -> bool,
+        F: FnMut(&Key<S, P>) -> bool,
     {
         self.inner.retain(f);
     }
+
+    /// Shrinks the capacity of the set as much as possible.
+    pub fn shrink_to_fit(&mut self) {
+        self.inner.shrink_to_fit();
+    }
+
+    /// Adds a value to the set like [`insert`](Self::insert), but first checks the key
+    /// with [`FoldPolicy::validate`] and returns an error if the policy rejects it.
+    pub fn try_insert<K: ToKey<S, P>>(&mut self, k: K) -> Result<bool, InvalidKey> {
+        let key = k.to_key();
+        P::validate(key.as_str())?;
+        Ok(self.inner.insert(key))
+    }
+
+    /// Tries to reserve capacity for at least `additional` more elements, returning an
+    /// error instead of panicking or aborting if the allocation fails.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.inner.try_reserve(additional)
+    }
+}
+
+/// Creates a [`UniCaseIndexSet`] with the default key type and policy from keys.
+///
+/// The keys must be string literals. Two keys that are equal under case folding, like
+/// `"Foo"` and `"FOO"`, are a compile error instead of silently overwriting each other.
+#[macro_export]
+macro_rules! unicase_index_set {
+    ($($key:literal),* $(,)?) => {{
+        const KEYS: &[&str] = &[$($key),*];
+        const _: () = $crate::literal::assert_unique_keys(KEYS);
+        #[allow(unused_mut)]
+        let mut set = $crate::unicase_index_set::UniCaseIndexSet::with_capacity(KEYS.len());
+        $(set.insert($key);)*
+        set
+    }};
 }
 
 #[cfg(test)]
 mod tests {
     use super::UniCaseIndexSet;
+    use crate::key::Key;
     use unicase::UniCase;
 
     #[test]
@@ -178,8 +283,8 @@ mod tests {
     fn get_str() {
         let mut map = UniCaseIndexSet::new();
         map.insert("A");
-        assert_eq!(map.get("A"), Some(&UniCase::new("A".to_string())));
-        assert_eq!(map.get("a"), Some(&UniCase::new("a".to_string())));
+        assert_eq!(map.get("A"), Some(&Key::new("A".to_string())));
+        assert_eq!(map.get("a"), Some(&Key::new("a".to_string())));
         assert!(map.get("B").is_none());
         assert!(map.get("Å").is_none());
     }
@@ -190,7 +295,7 @@ mod tests {
         map.insert("A");
         assert_eq!(
             map.get("A".to_string()).unwrap(),
-            &UniCase::new("a".to_string())
+            &Key::new("a".to_string())
         );
     }
 
@@ -198,9 +303,8 @@ mod tests {
     fn get_unicase() {
         let mut map = UniCaseIndexSet::new();
         map.insert("A");
-        // Won't work with plain &str, which is annoying.
         let uc = UniCase::new("a".to_string());
-        assert_eq!(map.get(uc).unwrap(), &UniCase::new("A".to_string()));
+        assert_eq!(map.get(uc).unwrap(), &Key::new("A".to_string()));
     }
 
     #[test]
@@ -208,18 +312,18 @@ mod tests {
         let mut map = UniCaseIndexSet::new();
         map.insert("A");
         let result = map.get("a");
-        assert_eq!(result, Some(&UniCase::new("a".to_string())));
+        assert_eq!(result, Some(&Key::new("a".to_string())));
     }
 
     #[test]
     fn insert_str() {
         let mut map = UniCaseIndexSet::new();
         let result = map.insert("A");
-        assert_eq!(result, true);
+        assert!(result);
         let result = map.insert("B");
-        assert_eq!(result, true);
+        assert!(result);
         let result = map.insert("A");
-        assert_eq!(result, false);
+        assert!(!result);
 
         assert_eq!(map.len(), 2);
     }
@@ -246,11 +350,11 @@ mod tests {
         map.insert("A".to_string());
         map.insert("B".to_string());
 
-        let mut elems: Vec<_> = map.iter().map(|v| v.clone()).collect();
+        let mut elems: Vec<_> = map.iter().cloned().collect();
         elems.sort();
         assert_eq!(
             elems,
-            vec![UniCase::new("a".to_string()), UniCase::new("b".to_string())]
+            vec![Key::new("a".to_string()), Key::new("b".to_string())]
         );
     }
 
@@ -264,10 +368,7 @@ mod tests {
         keys.sort();
         assert_eq!(
             keys,
-            vec![
-                &UniCase::new("A".to_string()),
-                &UniCase::new("B".to_string())
-            ]
+            vec![&Key::new("A".to_string()), &Key::new("B".to_string())]
         );
     }
 
@@ -288,8 +389,8 @@ mod tests {
         let mut map = UniCaseIndexSet::new();
         map.insert("A".to_string());
         map.insert("B".to_string());
-        assert_eq!(map.remove("b"), true);
-        assert_eq!(map.remove("b"), false);
+        assert!(map.remove("b"));
+        assert!(!map.remove("b"));
     }
 
     #[test]
@@ -297,8 +398,8 @@ mod tests {
         let mut map = UniCaseIndexSet::new();
         map.insert("A".to_string());
         map.insert("B".to_string());
-        assert_eq!(map.remove("b"), true);
-        assert_eq!(map.remove("b"), false);
+        assert!(map.remove("b"));
+        assert!(!map.remove("b"));
     }
 
     #[test]
@@ -326,7 +427,7 @@ mod tests {
         map.extend(v);
 
         assert_eq!(map.len(), 3);
-        assert_eq!(map.get("a"), Some(&UniCase::new("a".to_string())));
+        assert_eq!(map.get("a"), Some(&Key::new("a".to_string())));
     }
 
     #[test]
@@ -346,4 +447,106 @@ mod tests {
 
         let _map: UniCaseIndexSet = v.into_iter().collect();
     }
+
+    #[test]
+    fn arc_str_keys() {
+        let mut set = UniCaseIndexSet::<std::sync::Arc<str>>::default();
+        set.insert("A");
+        set.insert("a".to_string());
+        assert_eq!(set.len(), 1);
+        assert_eq!(set.get("A").map(|k| &***k), Some("A"));
+    }
+
+    #[test]
+    fn ascii_policy() {
+        use crate::fold::Ascii;
+
+        let set: UniCaseIndexSet<String, Ascii> = vec!["Å", "å", "A", "a"].into_iter().collect();
+        assert_eq!(set.len(), 3);
+    }
+
+    #[test]
+    fn normalized_policy() {
+        use crate::fold::Normalized;
+
+        let mut set = UniCaseIndexSet::<String, Normalized>::default();
+        set.insert("\u{00c5}");
+        assert!(!set.insert("a\u{030a}"));
+        assert_eq!(set.iter().next().unwrap().as_str(), "\u{00c5}");
+    }
+
+    #[test]
+    fn tailored_policy() {
+        use crate::fold::{Tailored, Tr};
+
+        let mut set = UniCaseIndexSet::<String, Tailored<Tr>>::default();
+        set.insert("KIZ");
+        assert!(set.contains("kız"));
+        assert!(!set.contains("kiz"));
+    }
+
+    #[test]
+    fn try_insert() {
+        let mut set = UniCaseIndexSet::new();
+        assert_eq!(set.try_insert("a b"), Ok(true));
+        assert_eq!(set.try_insert("A B"), Ok(false));
+    }
+
+    #[test]
+    fn confusable_insensitive_policy() {
+        use crate::fold::ConfusableInsensitive;
+
+        let set: UniCaseIndexSet<String, ConfusableInsensitive> =
+            vec!["admin", "\u{0430}dmin", "\u{0410}dmin", "root"]
+                .into_iter()
+                .collect();
+        assert_eq!(set.len(), 2);
+        assert!(set.contains("r\u{043e}\u{043e}t"));
+    }
+
+    #[test]
+    fn custom_hasher() {
+        use crate::fold::FullUnicode;
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::BuildHasherDefault;
+
+        type FixedState = BuildHasherDefault<DefaultHasher>;
+
+        let mut set =
+            UniCaseIndexSet::<String, FullUnicode, FixedState>::with_hasher(FixedState::default());
+        set.insert("A");
+        assert!(set.contains("a"));
+
+        let set = UniCaseIndexSet::<String, FullUnicode, _>::with_capacity_and_hasher(
+            8,
+            FixedState::default(),
+        );
+        assert!(set.is_empty());
+
+        let set: UniCaseIndexSet<String, FullUnicode, FixedState> =
+            vec!["A", "a"].into_iter().collect();
+        assert_eq!(set.len(), 1);
+    }
+
+    #[test]
+    fn capacity() {
+        let mut set = UniCaseIndexSet::with_capacity(10);
+        assert!(set.capacity() >= 10);
+        set.reserve(100);
+        assert!(set.capacity() >= 100);
+        set.insert("A");
+        set.shrink_to_fit();
+        assert!(set.capacity() >= 1 && set.capacity() < 100);
+        assert!(set.try_reserve(1000).is_ok());
+        assert!(set.try_reserve(usize::MAX).is_err());
+        assert!(set.contains("a"));
+    }
+
+    #[test]
+    fn literal_macro() {
+        let set = crate::unicase_index_set!["b", "A"];
+        assert!(set.contains("B"));
+        let keys: Vec<_> = set.iter().map(|k| k.as_str()).collect();
+        assert_eq!(keys, vec!["b", "A"]);
+    }
 }
@@ -0,0 +1,593 @@
+use crate::fold::{FoldPolicy, FullUnicode, InvalidKey};
+use crate::key::{AsKeyStr, Key, ToKey};
+use indexmap::IndexMap;
+use std::iter::{self, FromIterator, FusedIterator};
+use std::mem;
+use std::slice;
+
+/// A map that keeps every value appended for a key, like repeated HTTP or email headers.
+///
+/// Values are kept in the order they arrived, both per key and across all keys. Keys are
+/// visited in the order they were first appended, keeping the spelling of that first key.
+///
+/// Appending a value is O(1), and removing the values of a key is O(1) per removed value,
+/// amortized over the compactions of the removed values.
+#[derive(Debug, Clone)]
+pub struct UniCaseMultiMap<V, S = String, P = FullUnicode> {
+    /// The positions of the values of every key in `values`. The order of the keys is
+    /// the order of their first values in `values`, not the order of this map.
+    keys: IndexMap<Key<S, P>, Vec<usize>>,
+    /// The values in arrival order, with the index of their key in `keys`. Removed values
+    /// leave a hole until the holes outnumber the values and the vector is compacted.
+    values: Vec<Option<(usize, V)>>,
+    /// The number of values, without the holes.
+    len: usize,
+}
+
+impl<V, S, P> Default for UniCaseMultiMap<V, S, P> {
+    fn default() -> Self {
+        Self {
+            keys: Default::default(),
+            values: Default::default(),
+            len: 0,
+        }
+    }
+}
+
+impl<V, S, P> Eq for UniCaseMultiMap<V, S, P>
+where
+    V: Eq,
+    S: AsRef<str>,
+    P: FoldPolicy,
+{
+}
+
+impl<V, S, P> PartialEq for UniCaseMultiMap<V, S, P>
+where
+    V: PartialEq,
+    S: AsRef<str>,
+    P: FoldPolicy,
+{
+    fn eq(&self, other: &UniCaseMultiMap<V, S, P>) -> bool {
+        if self.len() != other.len() || self.keys_len() != other.keys_len() {
+            return false;
+        }
+
+        self.groups()
+            .all(|(key, values)| values.eq(other.get_all(key)))
+    }
+}
+
+impl<K, V, S, P> Extend<(K, V)> for UniCaseMultiMap<V, S, P>
+where
+    K: ToKey<S, P>,
+    S: AsRef<str>,
+    P: FoldPolicy,
+{
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        for (k, v) in iter {
+            self.append(k, v);
+        }
+    }
+}
+
+impl<K, V, S, P> FromIterator<(K, V)> for UniCaseMultiMap<V, S, P>
+where
+    K: ToKey<S, P>,
+    S: AsRef<str>,
+    P: FoldPolicy,
+{
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut map = Self::default();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<'a, V, S, P> IntoIterator for &'a UniCaseMultiMap<V, S, P>
+where
+    S: AsRef<str>,
+    P: FoldPolicy,
+{
+    type Item = (&'a Key<S, P>, &'a V);
+    type IntoIter = Iter<'a, V, S, P>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<V> UniCaseMultiMap<V> {
+    /// Creates a new UniCaseMultiMap.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<V, S, P> UniCaseMultiMap<V, S, P>
+where
+    S: AsRef<str>,
+    P: FoldPolicy,
+{
+    /// Appends a value to the values of the key, keeping the values that are already there.
+    /// The key is only stored if it is new.
+    pub fn append<K: ToKey<S, P>>(&mut self, k: K, v: V) {
+        let entry = self.keys.entry(k.to_key());
+        let index = entry.index();
+        entry.or_default().push(self.values.len());
+        self.values.push(Some((index, v)));
+        self.len += 1;
+    }
+
+    /// Clears the map, removing all keys and values.
+    pub fn clear(&mut self) {
+        self.keys.clear();
+        self.values.clear();
+        self.len = 0;
+    }
+
+    /// Returns true if the map contains at least one value for the key.
+    /// The key may be a String, str or UniCase value.
+    pub fn contains_key<K: AsKeyStr>(&self, k: K) -> bool {
+        self.keys.contains_key(k.as_key_str::<P>())
+    }
+
+    /// Returns the first value of the key.
+    /// The key may be a String, str or UniCase value.
+    pub fn get<K: AsKeyStr>(&self, k: K) -> Option<&V> {
+        let positions = self.keys.get(k.as_key_str::<P>())?;
+        Some(value(&self.values, positions[0]))
+    }
+
+    /// Returns an iterator over all values of the key in arrival order, which is empty
+    /// if the key is not present.
+    /// The key may be a String, str or UniCase value.
+    pub fn get_all<K: AsKeyStr>(&self, k: K) -> GetAll<'_, V> {
+        let positions = self
+            .keys
+            .get(k.as_key_str::<P>())
+            .map_or(&[][..], Vec::as_slice);
+        GetAll {
+            positions: positions.iter(),
+            values: &self.values,
+        }
+    }
+
+    /// Returns a mutable reference to the first value of the key.
+    /// The key may be a String, str or UniCase value.
+    pub fn get_mut<K: AsKeyStr>(&mut self, k: K) -> Option<&mut V> {
+        let positions = self.keys.get(k.as_key_str::<P>())?;
+        match &mut self.values[positions[0]] {
+            Some((_, v)) => Some(v),
+            None => unreachable!("the positions of a key point to its values"),
+        }
+    }
+
+    /// An iterator visiting every key once, with an iterator over its values, in the
+    /// order the keys were first appended.
+    pub fn groups(&self) -> Groups<'_, V, S, P> {
+        Groups {
+            keys: &self.keys,
+            slots: self.values.iter().enumerate(),
+            values: &self.values,
+            remaining: self.keys.len(),
+        }
+    }
+
+    /// Replaces all values of the key with a single value, returning the removed values.
+    /// The new value is the last one in arrival order.
+    pub fn insert<K: ToKey<S, P>>(&mut self, k: K, v: V) -> Vec<V> {
+        let key = k.to_key();
+        let removed = self.remove_all(&key);
+        self.append(key, v);
+        removed
+    }
+
+    /// Returns true if the map contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// An iterator visiting all key-value pairs in arrival order. Keys with several
+    /// values are visited once for every value.
+    pub fn iter(&self) -> Iter<'_, V, S, P> {
+        Iter {
+            keys: &self.keys,
+            values: self.values.iter(),
+            remaining: self.len,
+        }
+    }
+
+    /// An iterator visiting every key once, in the order the keys were first appended.
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item = &Key<S, P>> + ExactSizeIterator {
+        self.groups().map(|(k, _)| k)
+    }
+
+    /// Returns the number of distinct keys in the map.
+    pub fn keys_len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns the number of values in the map.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Removes a key from the map, returning all of its values in arrival order. The
+    /// returned vector is empty if the key was not present.
+    /// The key may be a String, str or UniCase value.
+    pub fn remove_all<K: AsKeyStr>(&mut self, k: K) -> Vec<V> {
+        let (index, _, positions) = match self.keys.swap_remove_full(k.as_key_str::<P>()) {
+            Some(removed) => removed,
+            None => return Vec::new(),
+        };
+
+        let removed: Vec<V> = positions
+            .into_iter()
+            .map(|i| match self.values[i].take() {
+                Some((_, v)) => v,
+                None => unreachable!("the positions of a key point to its values"),
+            })
+            .collect();
+        self.len -= removed.len();
+
+        // Removing the key moved the last key to its index.
+        if let Some(positions) = self.keys.get_index(index).map(|(_, p)| p) {
+            for &i in positions {
+                if let Some((moved, _)) = &mut self.values[i] {
+                    *moved = index;
+                }
+            }
+        }
+        if self.values.len() > 2 * self.len {
+            self.compact();
+        }
+        removed
+    }
+
+    /// Removes the holes of the removed values from `values`.
+    fn compact(&mut self) {
+        for positions in self.keys.values_mut() {
+            positions.clear();
+        }
+        for (index, v) in mem::take(&mut self.values).into_iter().flatten() {
+            self.keys[index].push(self.values.len());
+            self.values.push(Some((index, v)));
+        }
+    }
+
+    /// Appends a value like [`append`](Self::append), but first checks the key with
+    /// [`FoldPolicy::validate`] and returns an error if the policy rejects it.
+    pub fn try_append<K: ToKey<S, P>>(&mut self, k: K, v: V) -> Result<(), InvalidKey> {
+        let key = k.to_key();
+        P::validate(key.as_str())?;
+        self.append(key, v);
+        Ok(())
+    }
+
+    /// Replaces all values of the key like [`insert`](Self::insert), but first checks the
+    /// key with [`FoldPolicy::validate`] and returns an error if the policy rejects it.
+    pub fn try_insert<K: ToKey<S, P>>(&mut self, k: K, v: V) -> Result<Vec<V>, InvalidKey> {
+        let key = k.to_key();
+        P::validate(key.as_str())?;
+        Ok(self.insert(key, v))
+    }
+
+    /// An iterator visiting all values in arrival order.
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = &V> + ExactSizeIterator {
+        self.iter().map(|(_, v)| v)
+    }
+}
+
+/// Returns the value at the position `i` of `values`, which must not be a hole.
+fn value<V>(values: &[Option<(usize, V)>], i: usize) -> &V {
+    match &values[i] {
+        Some((_, v)) => v,
+        None => unreachable!("the positions of a key point to its values"),
+    }
+}
+
+/// An iterator over the values of a single key, in arrival order.
+pub struct GetAll<'a, V> {
+    positions: slice::Iter<'a, usize>,
+    values: &'a [Option<(usize, V)>],
+}
+
+impl<'a, V> Iterator for GetAll<'a, V> {
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<&'a V> {
+        self.positions.next().map(|&i| value(self.values, i))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.positions.size_hint()
+    }
+}
+
+impl<V> DoubleEndedIterator for GetAll<'_, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.positions.next_back().map(|&i| value(self.values, i))
+    }
+}
+
+impl<V> ExactSizeIterator for GetAll<'_, V> {}
+
+impl<V> FusedIterator for GetAll<'_, V> {}
+
+/// An iterator over all key-value pairs of a [`UniCaseMultiMap`] in arrival order.
+pub struct Iter<'a, V, S, P> {
+    keys: &'a IndexMap<Key<S, P>, Vec<usize>>,
+    values: slice::Iter<'a, Option<(usize, V)>>,
+    remaining: usize,
+}
+
+impl<'a, V, S, P> Iterator for Iter<'a, V, S, P> {
+    type Item = (&'a Key<S, P>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (index, v) = self.values.by_ref().flatten().next()?;
+        self.remaining -= 1;
+        Some((self.keys.get_index(*index)?.0, v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<V, S, P> DoubleEndedIterator for Iter<'_, V, S, P> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let (index, v) = self.values.by_ref().rev().flatten().next()?;
+        self.remaining -= 1;
+        Some((self.keys.get_index(*index)?.0, v))
+    }
+}
+
+impl<V, S, P> ExactSizeIterator for Iter<'_, V, S, P> {}
+
+impl<V, S, P> FusedIterator for Iter<'_, V, S, P> {}
+
+/// An iterator over the keys of a [`UniCaseMultiMap`] with the values of each key.
+pub struct Groups<'a, V, S, P> {
+    keys: &'a IndexMap<Key<S, P>, Vec<usize>>,
+    slots: iter::Enumerate<slice::Iter<'a, Option<(usize, V)>>>,
+    values: &'a [Option<(usize, V)>],
+    remaining: usize,
+}
+
+impl<'a, V, S, P> Groups<'a, V, S, P> {
+    /// Returns the group of the key of the value at `position` if it is the first value
+    /// of the key, which is where the key is visited.
+    fn group(
+        &mut self,
+        position: usize,
+        slot: &'a Option<(usize, V)>,
+    ) -> Option<(&'a Key<S, P>, GetAll<'a, V>)> {
+        let (key, positions) = self.keys.get_index(slot.as_ref()?.0)?;
+        if positions[0] != position {
+            return None;
+        }
+
+        self.remaining -= 1;
+        let values = GetAll {
+            positions: positions.iter(),
+            values: self.values,
+        };
+        Some((key, values))
+    }
+}
+
+impl<'a, V, S, P> Iterator for Groups<'a, V, S, P> {
+    type Item = (&'a Key<S, P>, GetAll<'a, V>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((position, slot)) = self.slots.next() {
+            if let Some(group) = self.group(position, slot) {
+                return Some(group);
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<V, S, P> DoubleEndedIterator for Groups<'_, V, S, P> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        while let Some((position, slot)) = self.slots.next_back() {
+            if let Some(group) = self.group(position, slot) {
+                return Some(group);
+            }
+        }
+        None
+    }
+}
+
+impl<V, S, P> ExactSizeIterator for Groups<'_, V, S, P> {}
+
+impl<V, S, P> FusedIterator for Groups<'_, V, S, P> {}
+
+#[cfg(test)]
+mod tests {
+    use super::UniCaseMultiMap;
+
+    fn received() -> UniCaseMultiMap<&'static str> {
+        let mut map = UniCaseMultiMap::new();
+        map.append("Received", "a");
+        map.append("Subject", "hello");
+        map.append("received", "b");
+        map.append("RECEIVED", "c");
+        map.append("To", "me");
+        map
+    }
+
+    #[test]
+    fn append_and_get_all() {
+        let map = received();
+        assert_eq!(map.len(), 5);
+        assert_eq!(map.keys_len(), 3);
+        assert_eq!(map.get("received"), Some(&"a"));
+        assert_eq!(
+            map.get_all("RECEIVED").collect::<Vec<_>>(),
+            vec![&"a", &"b", &"c"]
+        );
+        assert_eq!(map.get_all("received").next_back(), Some(&"c"));
+        assert_eq!(map.get_all("Received").len(), 3);
+        assert_eq!(map.get_all("Cc").next(), None);
+        assert!(map.contains_key("subject"));
+        assert!(!map.contains_key("cc"));
+    }
+
+    #[test]
+    fn iter_keeps_arrival_order() {
+        let map = received();
+        let pairs: Vec<_> = map.iter().map(|(k, v)| (k.as_str(), *v)).collect();
+        assert_eq!(
+            pairs,
+            vec![
+                ("Received", "a"),
+                ("Subject", "hello"),
+                ("Received", "b"),
+                ("Received", "c"),
+                ("To", "me"),
+            ]
+        );
+        assert_eq!(
+            map.values().copied().collect::<Vec<_>>(),
+            vec!["a", "hello", "b", "c", "me"]
+        );
+        assert_eq!((&map).into_iter().len(), 5);
+    }
+
+    #[test]
+    fn keys_and_groups() {
+        let map = received();
+        let keys: Vec<_> = map.keys().map(|k| k.as_str()).collect();
+        assert_eq!(keys, vec!["Received", "Subject", "To"]);
+
+        let groups: Vec<_> = map
+            .groups()
+            .map(|(k, values)| (k.as_str(), values.copied().collect::<Vec<_>>()))
+            .collect();
+        assert_eq!(
+            groups,
+            vec![
+                ("Received", vec!["a", "b", "c"]),
+                ("Subject", vec!["hello"]),
+                ("To", vec!["me"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn remove_all() {
+        let mut map = received();
+        assert_eq!(map.remove_all("received"), vec!["a", "b", "c"]);
+        assert!(map.remove_all("received").is_empty());
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.keys_len(), 2);
+        assert_eq!(map.get("subject"), Some(&"hello"));
+        assert_eq!(map.get("to"), Some(&"me"));
+
+        map.append("Subject", "again");
+        let pairs: Vec<_> = map.iter().map(|(k, v)| (k.as_str(), *v)).collect();
+        assert_eq!(
+            pairs,
+            vec![("Subject", "hello"), ("To", "me"), ("Subject", "again")]
+        );
+    }
+
+    #[test]
+    fn insert_replaces_all_values() {
+        let mut map = received();
+        assert_eq!(map.insert("RECEIVED", "d"), vec!["a", "b", "c"]);
+        assert_eq!(map.get_all("received").collect::<Vec<_>>(), vec![&"d"]);
+        assert_eq!(
+            map.iter().next_back().map(|(k, _)| k.as_str()),
+            Some("RECEIVED")
+        );
+        assert!(map.insert("Cc", "you").is_empty());
+    }
+
+    #[test]
+    fn many_keys() {
+        // Replaces every even key and removes every other odd key, then checks the values,
+        // their arrival order and the lengths of the iterators.
+        let n = 100_000;
+        let mut map: UniCaseMultiMap<usize> = (0..n).map(|i| (format!("key{}", i), i)).collect();
+        for i in (0..n).step_by(2) {
+            assert_eq!(map.insert(format!("KEY{}", i), n + i), vec![i]);
+        }
+        for i in (1..n).step_by(4) {
+            assert_eq!(map.remove_all(format!("Key{}", i)), vec![i]);
+        }
+        assert_eq!(map.len(), n * 3 / 4);
+        assert_eq!(map.keys_len(), n * 3 / 4);
+        assert_eq!(map.get("key2"), Some(&(n + 2)));
+        assert_eq!(map.get("key3"), Some(&3));
+        assert!(!map.contains_key("key5"));
+
+        // The odd keys that are left keep their place before the reinserted even keys.
+        let expected: Vec<_> = (3..n)
+            .step_by(4)
+            .chain((0..n).step_by(2).map(|i| n + i))
+            .collect();
+        assert_eq!(map.values().copied().collect::<Vec<_>>(), expected);
+        let keys: Vec<_> = map.keys().rev().take(2).map(|k| k.as_str()).collect();
+        assert_eq!(keys, vec![format!("KEY{}", n - 2), format!("KEY{}", n - 4)]);
+        assert_eq!(map.iter().len(), map.len());
+        assert_eq!(map.groups().len(), map.keys_len());
+    }
+
+    #[test]
+    fn get_mut() {
+        let mut map = received();
+        *map.get_mut("received").unwrap() = "z";
+        assert_eq!(
+            map.get_all("received").collect::<Vec<_>>(),
+            vec![&"z", &"b", &"c"]
+        );
+        assert!(map.get_mut("cc").is_none());
+    }
+
+    #[test]
+    fn clear() {
+        let mut map = received();
+        map.clear();
+        assert!(map.is_empty());
+        assert_eq!(map.keys_len(), 0);
+    }
+
+    #[test]
+    fn partial_eq() {
+        let a: UniCaseMultiMap<u8> = vec![("A", 1), ("B", 2), ("a", 3)].into_iter().collect();
+        let b: UniCaseMultiMap<u8> = vec![("b", 2), ("A", 1), ("A", 3)].into_iter().collect();
+        let c: UniCaseMultiMap<u8> = vec![("A", 3), ("B", 2), ("a", 1)].into_iter().collect();
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    fn extend() {
+        let mut map = UniCaseMultiMap::new();
+        map.append("Set-Cookie", "a=1");
+        map.extend(vec![("set-cookie", "b=2"), ("SET-COOKIE", "c=3")]);
+        assert_eq!(map.get_all("Set-Cookie").count(), 3);
+    }
+
+    #[test]
+    fn try_append() {
+        use crate::fold::{HttpToken, InvalidKey};
+
+        let mut map = UniCaseMultiMap::<u8, String, HttpToken>::default();
+        assert_eq!(map.try_append("Via", 1), Ok(()));
+        assert_eq!(map.try_append("VIA", 2), Ok(()));
+        assert_eq!(map.try_append("Bad Name", 3), Err(InvalidKey::at(3, ' ')));
+        assert_eq!(map.try_insert("via", 4), Ok(vec![1, 2]));
+        assert_eq!(map.try_insert("", 5), Err(InvalidKey::empty()));
+        assert_eq!(map.len(), 1);
+    }
+}
@@ -0,0 +1,309 @@
+use crate::fold::{FoldPolicy, FullUnicode, InvalidKey};
+use crate::key::{AsKeyStr, Key, ToKey};
+use imbl::ordmap::{DiffIter, Iter, Keys, Values};
+use imbl::shared_ptr::DefaultSharedPtr;
+use imbl::OrdMap;
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops::Index;
+
+/// An immutable map, ordered by the folded keys, whose versions share their structure.
+///
+/// The map is a B-tree with reference counted nodes, so cloning it is O(1) and
+/// [`insert`](Self::insert) and [`remove`](Self::remove) return a new version in
+/// O(log n) that copies only the nodes on the path to the key. Use `Arc<str>` keys to
+/// make copying those nodes cheap as well. Versions that share nodes can be compared
+/// quickly with [`ptr_eq`](Self::ptr_eq) and [`diff`](Self::diff).
+pub struct UniCasePersistentMap<V, S = String, P = FullUnicode> {
+    inner: OrdMap<Key<S, P>, V>,
+}
+
+impl<V, S, P> Clone for UniCasePersistentMap<V, S, P> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<V, S, P> fmt::Debug for UniCasePersistentMap<V, S, P>
+where
+    V: fmt::Debug,
+    S: AsRef<str> + fmt::Debug,
+    P: FoldPolicy,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.inner.iter()).finish()
+    }
+}
+
+impl<V, S, P> Default for UniCasePersistentMap<V, S, P> {
+    fn default() -> Self {
+        Self {
+            inner: Default::default(),
+        }
+    }
+}
+
+impl<V, S, P> Eq for UniCasePersistentMap<V, S, P>
+where
+    V: Eq + Clone,
+    S: AsRef<str> + Clone,
+    P: FoldPolicy,
+{
+}
+
+impl<V, S, P> PartialEq for UniCasePersistentMap<V, S, P>
+where
+    V: PartialEq + Clone,
+    S: AsRef<str> + Clone,
+    P: FoldPolicy,
+{
+    fn eq(&self, other: &UniCasePersistentMap<V, S, P>) -> bool {
+        self.ptr_eq(other) || self.diff(other).next().is_none()
+    }
+}
+
+impl<K, V, S, P> Extend<(K, V)> for UniCasePersistentMap<V, S, P>
+where
+    K: ToKey<S, P>,
+    V: Clone,
+    S: AsRef<str> + Clone,
+    P: FoldPolicy,
+{
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        for (k, v) in iter {
+            *self = self.insert(k, v);
+        }
+    }
+}
+
+impl<K, V, S, P> FromIterator<(K, V)> for UniCasePersistentMap<V, S, P>
+where
+    K: ToKey<S, P>,
+    V: Clone,
+    S: AsRef<str> + Clone,
+    P: FoldPolicy,
+{
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut map = Self::default();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<'a, V, S, P> IntoIterator for &'a UniCasePersistentMap<V, S, P>
+where
+    V: Clone,
+    S: AsRef<str> + Clone,
+    P: FoldPolicy,
+{
+    type Item = (&'a Key<S, P>, &'a V);
+    type IntoIter = Iter<'a, Key<S, P>, V, DefaultSharedPtr>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K, V, S, P> Index<K> for UniCasePersistentMap<V, S, P>
+where
+    K: AsKeyStr,
+    V: Clone,
+    S: AsRef<str> + Clone,
+    P: FoldPolicy,
+{
+    type Output = V;
+
+    fn index(&self, index: K) -> &Self::Output {
+        self.get(index).expect("key not found")
+    }
+}
+
+impl<V> UniCasePersistentMap<V> {
+    /// Creates a new, empty UniCasePersistentMap.
+    pub fn new() -> Self {
+        Self {
+            inner: OrdMap::new(),
+        }
+    }
+}
+
+impl<V, S, P> UniCasePersistentMap<V, S, P>
+where
+    V: Clone,
+    S: AsRef<str> + Clone,
+    P: FoldPolicy,
+{
+    /// Returns true if the map contains a value for the specified key.
+    /// The key may be a String, str or UniCase value.
+    pub fn contains_key<K: AsKeyStr>(&self, k: K) -> bool {
+        self.inner.contains_key(k.as_key_str::<P>())
+    }
+
+    /// An iterator over the changes from this version to `other` in folded key order.
+    /// Subtrees that the versions share are skipped without visiting them, so diffing
+    /// a version with its recent ancestor is fast even for large maps.
+    pub fn diff<'a, 'b>(
+        &'a self,
+        other: &'b UniCasePersistentMap<V, S, P>,
+    ) -> DiffIter<'a, 'b, Key<S, P>, V, DefaultSharedPtr> {
+        self.inner.diff(&other.inner)
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    /// The key may be a String, str or UniCase value.
+    pub fn get<K: AsKeyStr>(&self, k: K) -> Option<&V> {
+        self.inner.get(k.as_key_str::<P>())
+    }
+
+    /// Returns the key-value pair corresponding to the supplied key.
+    /// The key may be a String, str or UniCase value.
+    pub fn get_key_value<K: AsKeyStr>(&self, k: K) -> Option<(&Key<S, P>, &V)> {
+        self.inner.get_key_value(k.as_key_str::<P>())
+    }
+
+    /// Returns a new version of the map with the key-value pair inserted.
+    /// If the map already has this key, the value is replaced but the key keeps its
+    /// spelling, like the inserts of the other maps.
+    pub fn insert<K: ToKey<S, P>>(&self, k: K, v: V) -> Self {
+        let key = k.to_key();
+        let key = match self.inner.get_key_value(key.as_key_str::<P>()) {
+            Some((stored, _)) => stored.clone(),
+            None => key,
+        };
+        Self {
+            inner: self.inner.update(key, v),
+        }
+    }
+
+    /// Returns true if the map contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// An iterator visiting all key-value pairs in folded key order.
+    pub fn iter(&self) -> Iter<'_, Key<S, P>, V, DefaultSharedPtr> {
+        self.inner.iter()
+    }
+
+    /// An iterator visiting all keys in folded key order.
+    pub fn keys(&self) -> Keys<'_, Key<S, P>, V, DefaultSharedPtr> {
+        self.inner.keys()
+    }
+
+    /// Returns the number of elements in the map.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns true if both versions share their root, which makes them equal, in O(1).
+    /// A false result does not mean that the versions differ.
+    pub fn ptr_eq(&self, other: &UniCasePersistentMap<V, S, P>) -> bool {
+        self.inner.ptr_eq(&other.inner)
+    }
+
+    /// Returns a new version of the map without the key.
+    /// The key may be a String, str or UniCase value.
+    pub fn remove<K: AsKeyStr>(&self, k: K) -> Self {
+        Self {
+            inner: self.inner.without(k.as_key_str::<P>()),
+        }
+    }
+
+    /// Returns a new version of the map with the key-value pair inserted like
+    /// [`insert`](Self::insert), but first checks the key with [`FoldPolicy::validate`]
+    /// and returns an error if the policy rejects it.
+    pub fn try_insert<K: ToKey<S, P>>(&self, k: K, v: V) -> Result<Self, InvalidKey> {
+        let key = k.to_key();
+        P::validate(key.as_str())?;
+        Ok(self.insert(key, v))
+    }
+
+    /// An iterator visiting all values in folded key order.
+    pub fn values(&self) -> Values<'_, Key<S, P>, V, DefaultSharedPtr> {
+        self.inner.values()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UniCasePersistentMap;
+    use imbl::ordmap::DiffItem;
+    use std::sync::Arc;
+    use unicase::UniCase;
+
+    #[test]
+    fn new() {
+        let map = UniCasePersistentMap::<u8>::new();
+        assert_eq!(map.len(), 0);
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn insert_returns_new_version() {
+        let v1 = UniCasePersistentMap::new().insert("Timeout", 30);
+        let v2 = v1.insert("TIMEOUT", 60).insert("Retries", 3);
+        assert_eq!(v1.len(), 1);
+        assert_eq!(v1.get("timeout"), Some(&30));
+        assert_eq!(v2.len(), 2);
+        assert_eq!(v2["timeout"], 60);
+        assert_eq!(v2.get_key_value("timeout").unwrap().0.as_str(), "Timeout");
+        assert!(v2.contains_key(UniCase::new("RETRIES")));
+
+        let v3 = v2.remove("retries");
+        assert!(!v3.contains_key("Retries"));
+        assert!(v2.contains_key("Retries"));
+        assert_eq!(v3.remove("missing"), v3);
+    }
+
+    #[test]
+    fn ordered_by_folded_keys() {
+        let map: UniCasePersistentMap<i32> = vec![("b", 2), ("A", 1), ("Straße", 3)]
+            .into_iter()
+            .collect();
+        let keys: Vec<_> = map.keys().map(|k| k.as_str()).collect();
+        assert_eq!(keys, vec!["A", "b", "Straße"]);
+        assert_eq!(map.values().sum::<i32>(), 6);
+        assert_eq!(map.get("STRASSE"), Some(&3));
+    }
+
+    #[test]
+    fn ptr_eq_and_diff() {
+        let v1: UniCasePersistentMap<i32, Arc<str>> =
+            (0..1000).map(|i| (format!("key{}", i), i)).collect();
+        let same = v1.clone();
+        assert!(v1.ptr_eq(&same));
+        assert_eq!(v1.diff(&same).count(), 0);
+
+        let v2 = v1.insert("KEY5", 50).remove("key7").insert("new", 1);
+        assert!(!v1.ptr_eq(&v2));
+        let changes: Vec<_> = v1
+            .diff(&v2)
+            .map(|item| match item {
+                DiffItem::Add(k, v) => format!("+{}={}", k, v),
+                DiffItem::Update { old, new } => format!("{}={}->{}", old.0, old.1, new.1),
+                DiffItem::Remove(k, v) => format!("-{}={}", k, v),
+            })
+            .collect();
+        assert_eq!(changes, vec!["key5=5->50", "-key7=7", "+new=1"]);
+    }
+
+    #[test]
+    fn partial_eq() {
+        let map1: UniCasePersistentMap<i32> = vec![("A", 1), ("B", 2)].into_iter().collect();
+        let map2: UniCasePersistentMap<i32> = vec![("b", 2), ("a", 1)].into_iter().collect();
+        assert_eq!(map1, map2);
+        assert_ne!(map1, map2.insert("a", 3));
+    }
+
+    #[test]
+    fn try_insert() {
+        use crate::fold::{Identifier, InvalidKey};
+
+        let map = UniCasePersistentMap::<u8, String, Identifier>::default();
+        let map = map.try_insert("ﬁle", 1).unwrap();
+        assert_eq!(map.get("FILE"), Some(&1));
+        assert_eq!(map.try_insert("a b", 2), Err(InvalidKey::at(1, ' ')));
+    }
+}
@@ -0,0 +1,247 @@
+use crate::fold::{FoldPolicy, FullUnicode, InvalidKey};
+use crate::key::{AsKeyStr, Key, ToKey};
+use imbl::ordset::{DiffIter, Iter};
+use imbl::shared_ptr::DefaultSharedPtr;
+use imbl::OrdSet;
+use std::fmt;
+use std::iter::FromIterator;
+
+/// An immutable set, ordered by the folded keys, whose versions share their structure.
+///
+/// Like [`UniCasePersistentMap`](crate::unicase_persistent_map::UniCasePersistentMap),
+/// cloning the set is O(1) and [`insert`](Self::insert) and [`remove`](Self::remove)
+/// return a new version in O(log n).
+pub struct UniCasePersistentSet<S = String, P = FullUnicode> {
+    inner: OrdSet<Key<S, P>>,
+}
+
+impl<S, P> Clone for UniCasePersistentSet<S, P> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<S, P> fmt::Debug for UniCasePersistentSet<S, P>
+where
+    S: AsRef<str> + fmt::Debug,
+    P: FoldPolicy,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.inner.iter()).finish()
+    }
+}
+
+impl<S, P> Default for UniCasePersistentSet<S, P> {
+    fn default() -> Self {
+        Self {
+            inner: Default::default(),
+        }
+    }
+}
+
+impl<S, P> Eq for UniCasePersistentSet<S, P>
+where
+    S: AsRef<str> + Clone,
+    P: FoldPolicy,
+{
+}
+
+impl<S, P> PartialEq for UniCasePersistentSet<S, P>
+where
+    S: AsRef<str> + Clone,
+    P: FoldPolicy,
+{
+    fn eq(&self, other: &UniCasePersistentSet<S, P>) -> bool {
+        self.ptr_eq(other) || self.diff(other).next().is_none()
+    }
+}
+
+impl<K, S, P> Extend<K> for UniCasePersistentSet<S, P>
+where
+    K: ToKey<S, P>,
+    S: AsRef<str> + Clone,
+    P: FoldPolicy,
+{
+    fn extend<T: IntoIterator<Item = K>>(&mut self, iter: T) {
+        for k in iter {
+            *self = self.insert(k);
+        }
+    }
+}
+
+impl<K, S, P> FromIterator<K> for UniCasePersistentSet<S, P>
+where
+    K: ToKey<S, P>,
+    S: AsRef<str> + Clone,
+    P: FoldPolicy,
+{
+    fn from_iter<T: IntoIterator<Item = K>>(iter: T) -> Self {
+        let mut set = Self::default();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<'a, S, P> IntoIterator for &'a UniCasePersistentSet<S, P>
+where
+    S: AsRef<str> + Clone,
+    P: FoldPolicy,
+{
+    type Item = &'a Key<S, P>;
+    type IntoIter = Iter<'a, Key<S, P>, DefaultSharedPtr>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl UniCasePersistentSet {
+    /// Creates a new, empty UniCasePersistentSet.
+    pub fn new() -> Self {
+        Self {
+            inner: OrdSet::new(),
+        }
+    }
+}
+
+impl<S, P> UniCasePersistentSet<S, P>
+where
+    S: AsRef<str> + Clone,
+    P: FoldPolicy,
+{
+    /// Returns true if the set contains the specified key.
+    /// The key may be a String, str or UniCase value.
+    pub fn contains<K: AsKeyStr>(&self, k: K) -> bool {
+        self.inner.contains(k.as_key_str::<P>())
+    }
+
+    /// An iterator over the keys added and removed from this version to `other` in
+    /// folded key order. Subtrees that the versions share are skipped without visiting
+    /// them.
+    pub fn diff<'a, 'b>(
+        &'a self,
+        other: &'b UniCasePersistentSet<S, P>,
+    ) -> DiffIter<'a, 'b, Key<S, P>, DefaultSharedPtr> {
+        self.inner.diff(&other.inner)
+    }
+
+    /// Returns a reference to the stored key that is equal to the given key.
+    /// The key may be a String, str or UniCase value.
+    pub fn get<K: AsKeyStr>(&self, k: K) -> Option<&Key<S, P>> {
+        self.inner.get(k.as_key_str::<P>())
+    }
+
+    /// Returns a new version of the set with the key added. If the set already contains
+    /// an equal key, the set is returned unchanged, keeping the spelling of the key.
+    pub fn insert<K: ToKey<S, P>>(&self, k: K) -> Self {
+        let key = k.to_key();
+        if self.inner.contains(key.as_key_str::<P>()) {
+            return self.clone();
+        }
+        Self {
+            inner: self.inner.update(key),
+        }
+    }
+
+    /// Returns true if the set contains no keys.
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// An iterator visiting all keys in folded key order.
+    pub fn iter(&self) -> Iter<'_, Key<S, P>, DefaultSharedPtr> {
+        self.inner.iter()
+    }
+
+    /// Returns the number of keys in the set.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns true if both versions share their root, which makes them equal, in O(1).
+    /// A false result does not mean that the versions differ.
+    pub fn ptr_eq(&self, other: &UniCasePersistentSet<S, P>) -> bool {
+        self.inner.ptr_eq(&other.inner)
+    }
+
+    /// Returns a new version of the set without the key.
+    /// The key may be a String, str or UniCase value.
+    pub fn remove<K: AsKeyStr>(&self, k: K) -> Self {
+        Self {
+            inner: self.inner.without(k.as_key_str::<P>()),
+        }
+    }
+
+    /// Returns a new version of the set with the key added like [`insert`](Self::insert),
+    /// but first checks the key with [`FoldPolicy::validate`] and returns an error if the
+    /// policy rejects it.
+    pub fn try_insert<K: ToKey<S, P>>(&self, k: K) -> Result<Self, InvalidKey> {
+        let key = k.to_key();
+        P::validate(key.as_str())?;
+        Ok(self.insert(key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UniCasePersistentSet;
+    use imbl::ordset::DiffItem;
+
+    #[test]
+    fn new() {
+        let set = UniCasePersistentSet::new();
+        assert_eq!(set.len(), 0);
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn insert_returns_new_version() {
+        let v1 = UniCasePersistentSet::new().insert("Admin");
+        let v2 = v1.insert("ADMIN");
+        assert!(v1.ptr_eq(&v2));
+        assert_eq!(v2.get("admin").unwrap().as_str(), "Admin");
+
+        let v3 = v2.insert("Guest");
+        assert_eq!(v3.len(), 2);
+        assert!(!v1.contains("guest"));
+        assert!(v3.contains("GUEST"));
+        assert!(!v3.remove("admin").contains("Admin"));
+        assert_eq!(v3.len(), 2);
+    }
+
+    #[test]
+    fn diff() {
+        let v1: UniCasePersistentSet = (0..500).map(|i| format!("role{}", i)).collect();
+        let v2 = v1.remove("ROLE1").insert("Role500");
+        let changes: Vec<_> = v1
+            .diff(&v2)
+            .map(|item| match item {
+                DiffItem::Add(k) => format!("+{}", k),
+                DiffItem::Remove(k) => format!("-{}", k),
+            })
+            .collect();
+        assert_eq!(changes, vec!["-role1", "+Role500"]);
+        assert_eq!(v1, v1.clone());
+        assert_ne!(v1, v2);
+    }
+
+    #[test]
+    fn iter_in_folded_order() {
+        let set: UniCasePersistentSet = vec!["b", "A", "C"].into_iter().collect();
+        let keys: Vec<_> = set.iter().map(|k| k.as_str()).collect();
+        assert_eq!(keys, vec!["A", "b", "C"]);
+        for _ in &set {}
+    }
+
+    #[test]
+    fn try_insert() {
+        use crate::fold::{HttpToken, InvalidKey};
+
+        let set = UniCasePersistentSet::<String, HttpToken>::default();
+        let set = set.try_insert("Accept").unwrap();
+        assert!(set.contains("ACCEPT"));
+        assert_eq!(set.try_insert(""), Err(InvalidKey::empty()));
+    }
+}
@@ -0,0 +1,331 @@
+use crate::fold::{FoldPolicy, FullUnicode};
+use crate::key::{AsKeyStr, Key, KeyStr};
+use crate::phf;
+use std::fmt::{self, Write};
+use std::iter::FusedIterator;
+use std::marker::PhantomData;
+use std::ops::Index;
+use std::slice;
+
+/// A map with a perfect hash table that is generated at compile time, for large fixed
+/// tables like SQL keywords or MIME types that should not be built at startup.
+///
+/// The table is generated with [`PhfMapCodegen`] in a build script and stored in a
+/// `static` with `&'static str` keys. Lookups hash the folded key once and compare it
+/// with a single entry.
+pub struct UniCasePhfMap<V: 'static, P: 'static = FullUnicode> {
+    seed: u64,
+    disps: &'static [(u32, u32)],
+    entries: &'static [(Key<&'static str, P>, V)],
+}
+
+impl<V, P> fmt::Debug for UniCasePhfMap<V, P>
+where
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.entries.iter().map(|(k, v)| (k, v)))
+            .finish()
+    }
+}
+
+impl<K, V, P> Index<K> for UniCasePhfMap<V, P>
+where
+    K: AsKeyStr,
+    P: FoldPolicy,
+{
+    type Output = V;
+
+    fn index(&self, index: K) -> &Self::Output {
+        self.get(index).expect("key not found")
+    }
+}
+
+impl<'a, V, P> IntoIterator for &'a UniCasePhfMap<V, P> {
+    type Item = (&'a Key<&'static str, P>, &'a V);
+    type IntoIter = Iter<'a, V, P>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<V, P> UniCasePhfMap<V, P> {
+    /// Creates a map from the parts generated by [`PhfMapCodegen`].
+    #[doc(hidden)]
+    pub const fn from_parts(
+        seed: u64,
+        disps: &'static [(u32, u32)],
+        entries: &'static [(Key<&'static str, P>, V)],
+    ) -> Self {
+        Self {
+            seed,
+            disps,
+            entries,
+        }
+    }
+
+    /// Returns true if the map contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// An iterator visiting all key-value pairs in table order.
+    pub fn iter(&self) -> Iter<'_, V, P> {
+        Iter {
+            inner: self.entries.iter(),
+        }
+    }
+
+    /// An iterator visiting all keys in table order.
+    pub fn keys(&self) -> impl Iterator<Item = &Key<&'static str, P>> {
+        self.entries.iter().map(|(k, _)| k)
+    }
+
+    /// Returns the number of elements in the map.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// An iterator visiting all values in table order.
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.entries.iter().map(|(_, v)| v)
+    }
+}
+
+impl<V, P: FoldPolicy> UniCasePhfMap<V, P> {
+    /// Returns true if the map contains a value for the specified key.
+    /// The key may be a String, str or UniCase value.
+    pub fn contains_key<K: AsKeyStr>(&self, k: K) -> bool {
+        self.get_key_value(k).is_some()
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    /// The key may be a String, str or UniCase value.
+    pub fn get<K: AsKeyStr>(&self, k: K) -> Option<&V> {
+        self.get_key_value(k).map(|(_, v)| v)
+    }
+
+    /// Returns the key-value pair corresponding to the supplied key.
+    /// The key may be a String, str or UniCase value.
+    pub fn get_key_value<K: AsKeyStr>(&self, k: K) -> Option<(&Key<&'static str, P>, &V)> {
+        let k = k.as_key_str::<P>();
+        let slot = phf::index::<P>(k.as_str(), self.seed, self.disps, self.entries.len())?;
+        let (key, v) = &self.entries[slot];
+        if KeyStr::new(key.as_str()) == k {
+            Some((key, v))
+        } else {
+            None
+        }
+    }
+}
+
+/// An iterator over the key-value pairs of a [`UniCasePhfMap`] in table order.
+pub struct Iter<'a, V: 'static, P: 'static> {
+    inner: slice::Iter<'a, (Key<&'static str, P>, V)>,
+}
+
+impl<'a, V, P> Iterator for Iter<'a, V, P> {
+    type Item = (&'a Key<&'static str, P>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(k, v)| (k, v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<V, P> ExactSizeIterator for Iter<'_, V, P> {}
+
+impl<V, P> FusedIterator for Iter<'_, V, P> {}
+
+/// Generates the source of a [`UniCasePhfMap`] in a build script.
+///
+/// The values are given as Rust expressions. Write the generated expression to a file in
+/// `OUT_DIR` and declare the `static` with [`unicase_phf_map!`](crate::unicase_phf_map!).
+/// The expression names the policy `P` by its type name, so a `static` with another
+/// policy fails to compile, and `P` must be reachable by that name from the crate that
+/// includes the file.
+pub struct PhfMapCodegen<P = FullUnicode> {
+    entries: Vec<(String, String)>,
+    crate_path: String,
+    policy: PhantomData<fn() -> P>,
+}
+
+impl<P> Default for PhfMapCodegen<P> {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            crate_path: "::unicase_collections".to_string(),
+            policy: PhantomData,
+        }
+    }
+}
+
+impl PhfMapCodegen {
+    /// Creates a new PhfMapCodegen.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<P: FoldPolicy> PhfMapCodegen<P> {
+    /// Generates the expression that creates the map.
+    ///
+    /// # Panics
+    ///
+    /// Panics if two keys are equal under the policy.
+    pub fn build(&self) -> String {
+        let keys: Vec<&str> = self.entries.iter().map(|(k, _)| k.as_str()).collect();
+        let table = phf::generate::<P>(&keys);
+
+        let mut out = format!(
+            "{}::unicase_phf_map::UniCasePhfMap::<_, {}>::from_parts(",
+            self.crate_path,
+            phf::policy_path::<P>(&self.crate_path)
+        );
+        table.write_parts(&mut out);
+        out.push_str(", &[");
+        for &i in &table.order {
+            let (key, value) = &self.entries[i];
+            write!(
+                out,
+                "({}::key::Key::new({:?}), {}), ",
+                self.crate_path, key, value
+            )
+            .unwrap();
+        }
+        out.push_str("])");
+        out
+    }
+
+    /// Sets the path of this crate in the generated code, for crates that rename the
+    /// dependency. Defaults to `::unicase_collections`.
+    pub fn crate_path(&mut self, path: &str) -> &mut Self {
+        self.crate_path = path.to_string();
+        self
+    }
+
+    /// Adds an entry with the value given as a Rust expression.
+    pub fn entry(&mut self, key: &str, value: &str) -> &mut Self {
+        self.entries.push((key.to_string(), value.to_string()));
+        self
+    }
+}
+
+/// Declares a `static` [`UniCasePhfMap`] with the code that a build script generated
+/// with [`PhfMapCodegen`] into a file in `OUT_DIR`.
+#[macro_export]
+macro_rules! unicase_phf_map {
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $ty:ty = $file:literal;) => {
+        $(#[$attr])*
+        $vis static $name: $ty = include!(concat!(env!("OUT_DIR"), "/", $file));
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PhfMapCodegen, UniCasePhfMap};
+    use crate::fold::{Ascii, FoldPolicy};
+    use crate::key::Key;
+    use crate::phf;
+
+    /// Builds a map at runtime from the same table as the generated code.
+    fn leak<V, P: FoldPolicy>(entries: Vec<(&'static str, V)>) -> UniCasePhfMap<V, P> {
+        let keys: Vec<&str> = entries.iter().map(|(k, _)| *k).collect();
+        let table = phf::generate::<P>(&keys);
+        let mut entries: Vec<_> = entries.into_iter().map(Some).collect();
+        let ordered: Vec<_> = table
+            .order
+            .iter()
+            .map(|&i| {
+                let (k, v) = entries[i].take().unwrap();
+                (Key::new(k), v)
+            })
+            .collect();
+        UniCasePhfMap::from_parts(
+            table.seed,
+            Box::leak(table.disps.into_boxed_slice()),
+            Box::leak(ordered.into_boxed_slice()),
+        )
+    }
+
+    #[test]
+    fn get() {
+        let map: UniCasePhfMap<u8> = leak(vec![("SELECT", 1), ("From", 2), ("straße", 3)]);
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.get("select"), Some(&1));
+        assert_eq!(map.get("FROM".to_string()), Some(&2));
+        assert_eq!(map["STRASSE"], 3);
+        assert_eq!(map.get_key_value("from").unwrap().0.as_str(), "From");
+        assert!(map.contains_key("Select"));
+        assert!(!map.contains_key("where"));
+        assert!(!map.contains_key(""));
+    }
+
+    #[test]
+    fn many_keys() {
+        let keys: Vec<&'static str> = (0..300)
+            .map(|i| &*Box::leak(format!("keyword_{}", i).into_boxed_str()))
+            .collect();
+        let map: UniCasePhfMap<usize> = leak(keys.iter().copied().zip(0..).collect());
+        for (i, key) in keys.iter().enumerate() {
+            assert_eq!(map.get(key.to_uppercase()), Some(&i));
+        }
+        assert_eq!(map.get("keyword_300"), None);
+        assert_eq!(map.iter().count(), 300);
+        assert_eq!(map.values().sum::<usize>(), (0..300).sum());
+    }
+
+    #[test]
+    fn empty() {
+        let map: UniCasePhfMap<u8> = UniCasePhfMap::from_parts(0, &[], &[]);
+        assert!(map.is_empty());
+        assert_eq!(map.get("a"), None);
+    }
+
+    #[test]
+    fn static_map() {
+        static METHODS: UniCasePhfMap<u8, Ascii> =
+            UniCasePhfMap::from_parts(0, &[(0, 0)], &[(Key::new("GET"), 1)]);
+        // A single key always lands in the only slot.
+        assert_eq!(METHODS.len(), 1);
+        assert_eq!(METHODS.get("get"), Some(&1));
+        assert_eq!(METHODS.get("\u{212a}"), None);
+        assert_eq!(format!("{:?}", METHODS), r#"{"GET": 1}"#);
+    }
+
+    #[test]
+    fn codegen() {
+        let code = PhfMapCodegen::<Ascii>::default()
+            .entry("GET", "Method::Get")
+            .entry("Quote\"", "Method::Other")
+            .build();
+        assert!(code.starts_with(
+            "::unicase_collections::unicase_phf_map::UniCasePhfMap::<_, \
+             ::unicase_collections::fold::Ascii>::from_parts("
+        ));
+        assert!(code.contains(r#"(::unicase_collections::key::Key::new("GET"), Method::Get)"#));
+        assert!(code.contains(r#"Key::new("Quote\""), Method::Other)"#));
+
+        let code = PhfMapCodegen::new()
+            .crate_path("crate")
+            .entry("a", "1")
+            .build();
+        assert!(code.starts_with(
+            "crate::unicase_phf_map::UniCasePhfMap::<_, crate::fold::FullUnicode>::from_parts("
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "duplicate key")]
+    fn codegen_duplicate_keys() {
+        PhfMapCodegen::new()
+            .entry("Key", "1")
+            .entry("KEY", "2")
+            .build();
+    }
+}
@@ -0,0 +1,225 @@
+use crate::fold::{FoldPolicy, FullUnicode};
+use crate::key::{AsKeyStr, Key, KeyStr};
+use crate::phf;
+use std::fmt::{self, Write};
+use std::marker::PhantomData;
+use std::slice::Iter;
+
+/// A set with a perfect hash table that is generated at compile time, for large fixed
+/// tables like SQL keywords or HTTP methods that should not be built at startup.
+///
+/// The table is generated with [`PhfSetCodegen`] in a build script and stored in a
+/// `static` with `&'static str` keys. Lookups hash the folded key once and compare it
+/// with a single key.
+pub struct UniCasePhfSet<P: 'static = FullUnicode> {
+    seed: u64,
+    disps: &'static [(u32, u32)],
+    keys: &'static [Key<&'static str, P>],
+}
+
+impl<P> fmt::Debug for UniCasePhfSet<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.keys).finish()
+    }
+}
+
+impl<'a, P> IntoIterator for &'a UniCasePhfSet<P> {
+    type Item = &'a Key<&'static str, P>;
+    type IntoIter = Iter<'a, Key<&'static str, P>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<P> UniCasePhfSet<P> {
+    /// Creates a set from the parts generated by [`PhfSetCodegen`].
+    #[doc(hidden)]
+    pub const fn from_parts(
+        seed: u64,
+        disps: &'static [(u32, u32)],
+        keys: &'static [Key<&'static str, P>],
+    ) -> Self {
+        Self { seed, disps, keys }
+    }
+
+    /// Returns true if the set contains no keys.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// An iterator visiting all keys in table order.
+    pub fn iter(&self) -> Iter<'_, Key<&'static str, P>> {
+        self.keys.iter()
+    }
+
+    /// Returns the number of keys in the set.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+}
+
+impl<P: FoldPolicy> UniCasePhfSet<P> {
+    /// Returns true if the set contains the specified key.
+    /// The key may be a String, str or UniCase value.
+    pub fn contains<K: AsKeyStr>(&self, k: K) -> bool {
+        self.get(k).is_some()
+    }
+
+    /// Returns a reference to the stored key that is equal to the given key.
+    /// The key may be a String, str or UniCase value.
+    pub fn get<K: AsKeyStr>(&self, k: K) -> Option<&Key<&'static str, P>> {
+        let k = k.as_key_str::<P>();
+        let slot = phf::index::<P>(k.as_str(), self.seed, self.disps, self.keys.len())?;
+        let key = &self.keys[slot];
+        if KeyStr::new(key.as_str()) == k {
+            Some(key)
+        } else {
+            None
+        }
+    }
+}
+
+/// Generates the source of a [`UniCasePhfSet`] in a build script.
+///
+/// Write the generated expression to a file in `OUT_DIR` and declare the `static` with
+/// [`unicase_phf_set!`](crate::unicase_phf_set!). The expression names the policy `P`
+/// by its type name, so a `static` with another policy fails to compile, and `P` must
+/// be reachable by that name from the crate that includes the file.
+pub struct PhfSetCodegen<P = FullUnicode> {
+    keys: Vec<String>,
+    crate_path: String,
+    policy: PhantomData<fn() -> P>,
+}
+
+impl<P> Default for PhfSetCodegen<P> {
+    fn default() -> Self {
+        Self {
+            keys: Vec::new(),
+            crate_path: "::unicase_collections".to_string(),
+            policy: PhantomData,
+        }
+    }
+}
+
+impl PhfSetCodegen {
+    /// Creates a new PhfSetCodegen.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<P: FoldPolicy> PhfSetCodegen<P> {
+    /// Generates the expression that creates the set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if two keys are equal under the policy.
+    pub fn build(&self) -> String {
+        let keys: Vec<&str> = self.keys.iter().map(String::as_str).collect();
+        let table = phf::generate::<P>(&keys);
+
+        let mut out = format!(
+            "{}::unicase_phf_set::UniCasePhfSet::<{}>::from_parts(",
+            self.crate_path,
+            phf::policy_path::<P>(&self.crate_path)
+        );
+        table.write_parts(&mut out);
+        out.push_str(", &[");
+        for &i in &table.order {
+            write!(out, "{}::key::Key::new({:?}), ", self.crate_path, keys[i]).unwrap();
+        }
+        out.push_str("])");
+        out
+    }
+
+    /// Sets the path of this crate in the generated code, for crates that rename the
+    /// dependency. Defaults to `::unicase_collections`.
+    pub fn crate_path(&mut self, path: &str) -> &mut Self {
+        self.crate_path = path.to_string();
+        self
+    }
+
+    /// Adds a key.
+    pub fn entry(&mut self, key: &str) -> &mut Self {
+        self.keys.push(key.to_string());
+        self
+    }
+}
+
+/// Declares a `static` [`UniCasePhfSet`] with the code that a build script generated
+/// with [`PhfSetCodegen`] into a file in `OUT_DIR`.
+#[macro_export]
+macro_rules! unicase_phf_set {
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $ty:ty = $file:literal;) => {
+        $(#[$attr])*
+        $vis static $name: $ty = include!(concat!(env!("OUT_DIR"), "/", $file));
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PhfSetCodegen, UniCasePhfSet};
+    use crate::fold::{Ascii, FoldPolicy};
+    use crate::key::Key;
+    use crate::phf;
+
+    /// Builds a set at runtime from the same table as the generated code.
+    fn leak<P: FoldPolicy>(keys: &[&'static str]) -> UniCasePhfSet<P> {
+        let table = phf::generate::<P>(keys);
+        let ordered: Vec<_> = table.order.iter().map(|&i| Key::new(keys[i])).collect();
+        UniCasePhfSet::from_parts(
+            table.seed,
+            Box::leak(table.disps.into_boxed_slice()),
+            Box::leak(ordered.into_boxed_slice()),
+        )
+    }
+
+    #[test]
+    fn contains() {
+        let set: UniCasePhfSet = leak(&["GET", "Put", "DELETE", "Maße"]);
+        assert_eq!(set.len(), 4);
+        assert!(set.contains("get"));
+        assert!(set.contains("PUT".to_string()));
+        assert!(set.contains("MASSE"));
+        assert!(!set.contains("POST"));
+        assert_eq!(set.get("put").unwrap().as_str(), "Put");
+        assert_eq!(set.iter().count(), 4);
+    }
+
+    #[test]
+    fn policy() {
+        let set: UniCasePhfSet<Ascii> = leak(&["K", "Maße"]);
+        assert!(set.contains("k"));
+        assert!(!set.contains("\u{212a}"));
+        assert!(!set.contains("MASSE"));
+    }
+
+    #[test]
+    fn static_set() {
+        static EMPTY: UniCasePhfSet = UniCasePhfSet::from_parts(0, &[], &[]);
+        assert!(EMPTY.is_empty());
+        assert!(!EMPTY.contains(""));
+        assert_eq!(format!("{:?}", EMPTY), "{}");
+    }
+
+    #[test]
+    fn codegen() {
+        let code = PhfSetCodegen::new().entry("GET").entry("tab\t").build();
+        assert!(code.starts_with(
+            "::unicase_collections::unicase_phf_set::UniCasePhfSet::<\
+             ::unicase_collections::fold::FullUnicode>::from_parts("
+        ));
+        assert!(code.contains(r#"::unicase_collections::key::Key::new("GET"), "#));
+        assert!(code.contains(r#"Key::new("tab\t"), "#));
+    }
+
+    #[test]
+    #[should_panic(expected = "duplicate key")]
+    fn codegen_duplicate_keys() {
+        PhfSetCodegen::<Ascii>::default()
+            .entry("get")
+            .entry("GET")
+            .build();
+    }
+}
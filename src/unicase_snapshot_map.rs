@@ -0,0 +1,206 @@
+use crate::fold::FullUnicode;
+use crate::unicase_index_map::UniCaseIndexMap;
+use arc_swap::ArcSwap;
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::ops::Deref;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// A map for data that is read much more often than it is written, like configuration.
+///
+/// Readers load the current [`Snapshot`] without taking a lock and keep it for as long as
+/// they need, while writers copy the map, modify the copy and atomically publish it. Every
+/// publish increments the generation, so readers can cheaply check whether their snapshot
+/// is still current.
+pub struct UniCaseSnapshotMap<V, S = String, P = FullUnicode, H = RandomState> {
+    current: ArcSwap<Snapshot<V, S, P, H>>,
+    // Serializes the writers, so that concurrent updates do not overwrite each other.
+    writer: Mutex<()>,
+}
+
+/// An immutable version of a [`UniCaseSnapshotMap`], which derefs to the
+/// [`UniCaseIndexMap`] of that version.
+#[derive(Debug)]
+pub struct Snapshot<V, S = String, P = FullUnicode, H = RandomState> {
+    map: UniCaseIndexMap<V, S, P, H>,
+    generation: u64,
+}
+
+impl<V, S, P, H> Snapshot<V, S, P, H> {
+    /// Returns the generation of the snapshot, which starts at zero and is incremented by
+    /// every publish.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Returns the map of the snapshot.
+    pub fn map(&self) -> &UniCaseIndexMap<V, S, P, H> {
+        &self.map
+    }
+}
+
+impl<V, S, P, H> Deref for Snapshot<V, S, P, H> {
+    type Target = UniCaseIndexMap<V, S, P, H>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.map
+    }
+}
+
+impl<V, S, P, H> fmt::Debug for UniCaseSnapshotMap<V, S, P, H>
+where
+    V: fmt::Debug,
+    S: fmt::Debug,
+    P: fmt::Debug,
+    H: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.current.load().fmt(f)
+    }
+}
+
+impl<V, S, P, H: Default> Default for UniCaseSnapshotMap<V, S, P, H> {
+    fn default() -> Self {
+        Self::from(UniCaseIndexMap::default())
+    }
+}
+
+impl<V, S, P, H> From<UniCaseIndexMap<V, S, P, H>> for UniCaseSnapshotMap<V, S, P, H> {
+    fn from(map: UniCaseIndexMap<V, S, P, H>) -> Self {
+        Self {
+            current: ArcSwap::from_pointee(Snapshot { map, generation: 0 }),
+            writer: Mutex::new(()),
+        }
+    }
+}
+
+impl<V> UniCaseSnapshotMap<V> {
+    /// Creates a new, empty UniCaseSnapshotMap at generation zero.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<V, S, P, H> UniCaseSnapshotMap<V, S, P, H> {
+    /// Returns the generation of the current snapshot, without loading it.
+    pub fn generation(&self) -> u64 {
+        self.current.load().generation
+    }
+
+    /// Publishes `map` as the new snapshot, replacing the current one, and returns the new
+    /// generation.
+    pub fn publish(&self, map: UniCaseIndexMap<V, S, P, H>) -> u64 {
+        let _writer = self.writer.lock().unwrap_or_else(PoisonError::into_inner);
+        let generation = self.current.load().generation + 1;
+        self.current.store(Arc::new(Snapshot { map, generation }));
+        generation
+    }
+
+    /// Returns the current snapshot. Loading it is lock-free, and the snapshot stays valid
+    /// and unchanged while later versions are published.
+    pub fn snapshot(&self) -> Arc<Snapshot<V, S, P, H>> {
+        self.current.load_full()
+    }
+
+    /// Copies the current map, calls `f` with the copy and publishes it as the new
+    /// snapshot. Returns the result of `f`.
+    ///
+    /// Updates run one at a time, so every update sees the changes of the previous ones.
+    /// If `f` panics, nothing is published.
+    pub fn update<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut UniCaseIndexMap<V, S, P, H>) -> R,
+        UniCaseIndexMap<V, S, P, H>: Clone,
+    {
+        let _writer = self.writer.lock().unwrap_or_else(PoisonError::into_inner);
+        let current = self.current.load();
+        let mut map = current.map.clone();
+        let result = f(&mut map);
+        let generation = current.generation + 1;
+        self.current.store(Arc::new(Snapshot { map, generation }));
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UniCaseSnapshotMap;
+    use crate::unicase_index_map::UniCaseIndexMap;
+    use std::thread;
+
+    #[test]
+    fn new() {
+        let map = UniCaseSnapshotMap::<u8>::new();
+        assert_eq!(map.generation(), 0);
+        assert!(map.snapshot().is_empty());
+    }
+
+    #[test]
+    fn update() {
+        let map = UniCaseSnapshotMap::new();
+        let old = map.snapshot();
+        let previous = map.update(|m| m.insert("Timeout", 30));
+        assert_eq!(previous, None);
+        assert_eq!(map.generation(), 1);
+
+        let current = map.snapshot();
+        assert_eq!(current.get("timeout"), Some(&30));
+        assert_eq!(current.generation(), 1);
+        // Old snapshots are not affected by later updates.
+        assert!(old.is_empty());
+        assert_eq!(old.generation(), 0);
+
+        map.update(|m| m.insert("TIMEOUT", 60));
+        assert_eq!(map.snapshot()["timeout"], 60);
+        assert_eq!(current["timeout"], 30);
+        assert_eq!(map.generation(), 2);
+    }
+
+    #[test]
+    fn publish() {
+        let mut initial = UniCaseIndexMap::new();
+        initial.insert("Retries", 3);
+        let map = UniCaseSnapshotMap::from(initial);
+        assert_eq!(map.snapshot().map().get("RETRIES"), Some(&3));
+
+        let mut reloaded = UniCaseIndexMap::new();
+        reloaded.insert("Timeout", 30);
+        assert_eq!(map.publish(reloaded), 1);
+        assert!(!map.snapshot().contains_key("retries"));
+        assert!(map.snapshot().contains_key("timeout"));
+    }
+
+    #[test]
+    fn concurrent_updates() {
+        let map = UniCaseSnapshotMap::new();
+        map.update(|m| m.insert("Count", 0));
+        thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for _ in 0..50 {
+                        map.update(|m| *m.get_mut("count").unwrap() += 1);
+                        let snapshot = map.snapshot();
+                        assert!(snapshot["count"] as u64 <= snapshot.generation());
+                    }
+                });
+            }
+        });
+        assert_eq!(map.snapshot()["COUNT"], 200);
+        assert_eq!(map.generation(), 201);
+    }
+
+    #[test]
+    fn update_panics() {
+        let map = UniCaseSnapshotMap::new();
+        map.update(|m| m.insert("A", 1));
+        let result = thread::scope(|scope| {
+            scope
+                .spawn(|| map.update(|_| panic!("update failed")))
+                .join()
+        });
+        assert!(result.is_err());
+        assert_eq!(map.generation(), 1);
+        map.update(|m| m.insert("B", 2));
+        assert_eq!(map.snapshot().len(), 2);
+    }
+}
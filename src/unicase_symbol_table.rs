@@ -0,0 +1,184 @@
+use crate::fold::{FoldPolicy, Identifier, InvalidKey};
+use crate::key::{AsKeyStr, ToKey};
+use crate::unicase_index_map::UniCaseIndexMap;
+use std::collections::hash_map::RandomState;
+use std::convert::TryFrom;
+use std::hash::BuildHasher;
+use std::ops::Deref;
+
+/// A [`UniCaseIndexMap`] whose keys are always accepted by [`FoldPolicy::validate`].
+///
+/// The table has no infallible insert: every key goes through
+/// [`try_insert`](Self::try_insert), [`try_extend`](Self::try_extend) or
+/// [`try_from_iter`](Self::try_from_iter), so it cannot contain a key that the policy
+/// rejects. Lookups and iteration are available through the map the table derefs to.
+#[derive(Debug, Clone)]
+pub struct UniCaseSymbolTable<V, S = String, P = Identifier, H = RandomState> {
+    map: UniCaseIndexMap<V, S, P, H>,
+}
+
+impl<V, S, P, H: Default> Default for UniCaseSymbolTable<V, S, P, H> {
+    fn default() -> Self {
+        Self {
+            map: UniCaseIndexMap::default(),
+        }
+    }
+}
+
+impl<V, S, P, H> Deref for UniCaseSymbolTable<V, S, P, H> {
+    type Target = UniCaseIndexMap<V, S, P, H>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.map
+    }
+}
+
+impl<V, S, P, H> From<UniCaseSymbolTable<V, S, P, H>> for UniCaseIndexMap<V, S, P, H> {
+    fn from(table: UniCaseSymbolTable<V, S, P, H>) -> Self {
+        table.map
+    }
+}
+
+impl<V, S, P, H> TryFrom<UniCaseIndexMap<V, S, P, H>> for UniCaseSymbolTable<V, S, P, H>
+where
+    S: AsRef<str>,
+    P: FoldPolicy,
+    H: BuildHasher,
+{
+    type Error = InvalidKey;
+
+    /// Checks every key of `map` and fails with the error of the first rejected key.
+    fn try_from(map: UniCaseIndexMap<V, S, P, H>) -> Result<Self, InvalidKey> {
+        for k in map.keys() {
+            P::validate(k.as_str())?;
+        }
+        Ok(Self { map })
+    }
+}
+
+impl<V> UniCaseSymbolTable<V> {
+    /// Creates a new, empty UniCaseSymbolTable.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<V, S, P, H> UniCaseSymbolTable<V, S, P, H>
+where
+    S: AsRef<str>,
+    P: FoldPolicy,
+    H: BuildHasher,
+{
+    /// Clears the table, removing all symbols.
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    /// The key may be a String, str or UniCase value.
+    pub fn get_mut<K: AsKeyStr>(&mut self, k: K) -> Option<&mut V> {
+        self.map.get_mut(k)
+    }
+
+    /// Removes a key from the table, returning the value at the key if the key was
+    /// previously in the table. The key may be a String, str or UniCase value.
+    pub fn remove<K: AsKeyStr>(&mut self, k: K) -> Option<V> {
+        self.map.remove(k)
+    }
+
+    /// Inserts the key-value pairs of `iter` with [`try_insert`](Self::try_insert).
+    /// Stops at the first rejected key and returns its error. The pairs before it
+    /// stay inserted.
+    pub fn try_extend<K, I>(&mut self, iter: I) -> Result<(), InvalidKey>
+    where
+        K: ToKey<S, P>,
+        I: IntoIterator<Item = (K, V)>,
+    {
+        for (k, v) in iter {
+            self.try_insert(k, v)?;
+        }
+        Ok(())
+    }
+
+    /// Creates a table from the key-value pairs of `iter`, or returns the error of the
+    /// first key the policy rejects.
+    pub fn try_from_iter<K, I>(iter: I) -> Result<Self, InvalidKey>
+    where
+        K: ToKey<S, P>,
+        I: IntoIterator<Item = (K, V)>,
+        H: Default,
+    {
+        let mut table = Self::default();
+        table.try_extend(iter)?;
+        Ok(table)
+    }
+
+    /// Inserts a key-value pair into the table if the policy accepts the key.
+    /// Like [`UniCaseIndexMap::insert`], the old value is returned if the table already
+    /// has the key, and the key keeps its spelling.
+    pub fn try_insert<K: ToKey<S, P>>(&mut self, k: K, v: V) -> Result<Option<V>, InvalidKey> {
+        self.map.try_insert(k, v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UniCaseSymbolTable;
+    use crate::fold::InvalidKey;
+    use crate::unicase_index_map::UniCaseIndexMap;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn new() {
+        let table = UniCaseSymbolTable::<u8>::new();
+        assert!(table.is_empty());
+    }
+
+    #[test]
+    fn try_insert() {
+        let mut table = UniCaseSymbolTable::<i32>::new();
+        assert_eq!(table.try_insert("ﬁle", 1), Ok(None));
+        assert_eq!(table.try_insert("FILE", 2), Ok(Some(1)));
+        assert_eq!(table.try_insert("1st", 3), Err(InvalidKey::at(0, '1')));
+        assert_eq!(table.try_insert("", 3), Err(InvalidKey::empty()));
+        assert_eq!(table.len(), 1);
+        assert_eq!(table.get_key_value("file").unwrap().0.as_str(), "ﬁle");
+
+        *table.get_mut("ｆｉｌｅ").unwrap() += 1;
+        assert_eq!(table["file"], 3);
+        assert_eq!(table.remove("File"), Some(3));
+        assert!(table.is_empty());
+    }
+
+    #[test]
+    fn try_from_iter() {
+        let table = UniCaseSymbolTable::<i32>::try_from_iter(vec![("x", 1), ("Y", 2)]).unwrap();
+        assert_eq!(
+            table.keys().map(|k| k.as_str()).collect::<Vec<_>>(),
+            ["x", "Y"]
+        );
+
+        let err = UniCaseSymbolTable::<i32>::try_from_iter(vec![("x", 1), ("a b", 2)]).unwrap_err();
+        assert_eq!(err, InvalidKey::at(1, ' '));
+
+        let mut table = table;
+        assert_eq!(
+            table.try_extend(vec![("z", 3), ("-", 4), ("w", 5)]),
+            Err(InvalidKey::at(0, '-'))
+        );
+        assert_eq!(table.len(), 3);
+        assert!(!table.contains_key("w"));
+    }
+
+    #[test]
+    fn try_from_map() {
+        let mut map = UniCaseIndexMap::default();
+        map.insert("valid", 1);
+        let table = UniCaseSymbolTable::<i32>::try_from(map.clone()).unwrap();
+        assert_eq!(UniCaseIndexMap::from(table), map);
+
+        map.insert("not valid", 2);
+        let result = UniCaseSymbolTable::<i32>::try_from(map);
+        assert_eq!(result.unwrap_err(), InvalidKey::at(3, ' '));
+    }
+}
@@ -0,0 +1,591 @@
+use crate::fold::{FoldPolicy, FullUnicode, InvalidKey};
+use crate::key::{AsKeyStr, Key, ToKey};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{btree_map, BTreeMap, BinaryHeap};
+use std::fmt;
+use std::iter::{FromIterator, FusedIterator};
+use std::ptr;
+
+/// A trie of case-insensitive keys, for prefix search, autocomplete and longest-match
+/// routing.
+///
+/// Keys are stored along the chars of their folded form, so the prefix queries cost
+/// O(prefix) before visiting any matches. Prefixes are matched against the folded
+/// keys, e.g. with full folding the prefix `s` matches `ß`, which folds to `ss`. Keys
+/// are visited in the same order as in a
+/// [`UniCaseBTreeMap`](crate::unicase_btree_map::UniCaseBTreeMap).
+///
+/// The prefix queries assume that the policy folds a prefix of a key to a prefix of the
+/// folded key, which holds for the case folding policies but not for the phonetic ones.
+#[derive(Clone)]
+pub struct UniCaseTrie<V, S = String, P = FullUnicode> {
+    root: Node<V, S, P>,
+}
+
+#[derive(Clone)]
+struct Node<V, S, P> {
+    children: BTreeMap<char, Node<V, S, P>>,
+    entry: Option<(Key<S, P>, V)>,
+    /// The number of entries in this node and below it.
+    len: usize,
+}
+
+impl<V, S, P> Default for Node<V, S, P> {
+    fn default() -> Self {
+        Self {
+            children: BTreeMap::new(),
+            entry: None,
+            len: 0,
+        }
+    }
+}
+
+impl<V, S, P> Default for UniCaseTrie<V, S, P> {
+    fn default() -> Self {
+        Self {
+            root: Node::default(),
+        }
+    }
+}
+
+impl<V, S, P> fmt::Debug for UniCaseTrie<V, S, P>
+where
+    V: fmt::Debug,
+    S: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(Iter::new(&self.root)).finish()
+    }
+}
+
+impl<V, S, P> Eq for UniCaseTrie<V, S, P>
+where
+    V: Eq,
+    S: AsRef<str>,
+    P: FoldPolicy,
+{
+}
+
+impl<V, S, P> PartialEq for UniCaseTrie<V, S, P>
+where
+    V: PartialEq,
+    S: AsRef<str>,
+    P: FoldPolicy,
+{
+    fn eq(&self, other: &UniCaseTrie<V, S, P>) -> bool {
+        if self.len() != other.len() {
+            return false;
+        }
+
+        // Both tries visit their keys in folded order.
+        self.iter()
+            .zip(other.iter())
+            .all(|((k1, v1), (k2, v2))| k1 == k2 && v1 == v2)
+    }
+}
+
+impl<K, V, S, P> Extend<(K, V)> for UniCaseTrie<V, S, P>
+where
+    K: ToKey<S, P>,
+    S: AsRef<str>,
+    P: FoldPolicy,
+{
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl<K, V, S, P> FromIterator<(K, V)> for UniCaseTrie<V, S, P>
+where
+    K: ToKey<S, P>,
+    S: AsRef<str>,
+    P: FoldPolicy,
+{
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut trie = Self::default();
+        trie.extend(iter);
+        trie
+    }
+}
+
+impl<'a, V, S, P> IntoIterator for &'a UniCaseTrie<V, S, P>
+where
+    S: AsRef<str>,
+    P: FoldPolicy,
+{
+    type Item = (&'a Key<S, P>, &'a V);
+    type IntoIter = Iter<'a, V, S, P>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<V> UniCaseTrie<V> {
+    /// Creates a new UniCaseTrie.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<V, S, P> UniCaseTrie<V, S, P>
+where
+    S: AsRef<str>,
+    P: FoldPolicy,
+{
+    /// Clears the trie, removing all key-value pairs.
+    pub fn clear(&mut self) {
+        self.root = Node::default();
+    }
+
+    /// Returns true if the trie contains a value for the specified key.
+    /// The key may be a String, str or UniCase value.
+    pub fn contains_key<K: AsKeyStr>(&self, k: K) -> bool {
+        self.get_key_value(k).is_some()
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    /// The key may be a String, str or UniCase value.
+    pub fn get<K: AsKeyStr>(&self, k: K) -> Option<&V> {
+        self.get_key_value(k).map(|(_, v)| v)
+    }
+
+    /// Returns the key-value pair corresponding to the supplied key.
+    /// The key may be a String, str or UniCase value.
+    pub fn get_key_value<K: AsKeyStr>(&self, k: K) -> Option<(&Key<S, P>, &V)> {
+        let node = self.node(P::fold(k.as_key_str::<P>().as_str().chars()))?;
+        node.entry.as_ref().map(|(k, v)| (k, v))
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    /// The key may be a String, str or UniCase value.
+    pub fn get_mut<K: AsKeyStr>(&mut self, k: K) -> Option<&mut V> {
+        let mut node = &mut self.root;
+        for c in P::fold(k.as_key_str::<P>().as_str().chars()) {
+            node = node.children.get_mut(&c)?;
+        }
+        node.entry.as_mut().map(|(_, v)| v)
+    }
+
+    /// Inserts a key-value pair into the trie.
+    /// If the trie did not have this key present, None is returned.
+    /// If the trie did have this key present, the value is updated, and the old value is
+    /// returned. The key keeps the spelling it was first inserted with.
+    pub fn insert<K: ToKey<S, P>>(&mut self, k: K, v: V) -> Option<V> {
+        let key = k.to_key();
+        let path: Vec<char> = P::fold(key.as_str().chars()).collect();
+        if let Some((_, old)) = self.node_mut(&path).and_then(|node| node.entry.as_mut()) {
+            return Some(std::mem::replace(old, v));
+        }
+
+        let mut node = &mut self.root;
+        node.len += 1;
+        for c in path {
+            node = node.children.entry(c).or_default();
+            node.len += 1;
+        }
+        node.entry = Some((key, v));
+        None
+    }
+
+    /// Returns true if the trie contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// An iterator visiting all key-value pairs in folded key order.
+    pub fn iter(&self) -> Iter<'_, V, S, P> {
+        Iter::new(&self.root)
+    }
+
+    /// An iterator visiting the key-value pairs whose folded key starts with the folded
+    /// prefix, in folded key order. The prefix may be a String, str or UniCase value.
+    pub fn iter_prefix<K: AsKeyStr>(&self, prefix: K) -> Iter<'_, V, S, P> {
+        match self.node(P::fold(prefix.as_key_str::<P>().as_str().chars())) {
+            Some(node) => Iter::new(node),
+            None => Iter::empty(),
+        }
+    }
+
+    /// An iterator visiting all keys in folded key order.
+    pub fn keys(&self) -> impl Iterator<Item = &Key<S, P>> {
+        self.iter().map(|(k, _)| k)
+    }
+
+    /// Returns the number of elements in the trie.
+    pub fn len(&self) -> usize {
+        self.root.len
+    }
+
+    /// Returns the longest key that is a prefix of the input, with its value. Like a
+    /// routing table, `/api/users/1` matches `/API/users` rather than `/api`.
+    /// The input may be a String, str or UniCase value.
+    pub fn longest_prefix_match<K: AsKeyStr>(&self, input: K) -> Option<(&Key<S, P>, &V)> {
+        let mut node = &self.root;
+        let mut longest = node.entry.as_ref();
+        for c in P::fold(input.as_key_str::<P>().as_str().chars()) {
+            node = match node.children.get(&c) {
+                Some(child) => child,
+                None => break,
+            };
+            if node.entry.is_some() {
+                longest = node.entry.as_ref();
+            }
+        }
+        longest.map(|(k, v)| (k, v))
+    }
+
+    /// Removes a key from the trie, returning the value at the key if the key was
+    /// previously in the trie.
+    /// The key may be a String, str or UniCase value.
+    pub fn remove<K: AsKeyStr>(&mut self, k: K) -> Option<V> {
+        self.remove_entry(k).map(|(_, v)| v)
+    }
+
+    /// Removes a key from the trie, returning the stored key and value if the key was
+    /// previously in the trie.
+    /// The key may be a String, str or UniCase value.
+    pub fn remove_entry<K: AsKeyStr>(&mut self, k: K) -> Option<(Key<S, P>, V)> {
+        let path: Vec<char> = P::fold(k.as_key_str::<P>().as_str().chars()).collect();
+        let entry = self.node_mut(&path)?.entry.take()?;
+
+        // Update the counts along the path and drop the branch that no longer has entries.
+        let mut node = &mut self.root;
+        node.len -= 1;
+        for c in &path {
+            if node.children[c].len == 1 {
+                node.children.remove(c);
+                break;
+            }
+            node = node.children.get_mut(c).expect("the path exists");
+            node.len -= 1;
+        }
+        Some(entry)
+    }
+
+    /// Returns the shortest prefix of the stored spelling of the key that no other key
+    /// starts with, e.g. `stat` for `status` next to `stash`. Returns the whole key if it
+    /// is a prefix of other keys, and None if the key is not present.
+    /// The key may be a String, str or UniCase value.
+    pub fn shortest_unique_prefix<K: AsKeyStr>(&self, k: K) -> Option<&str> {
+        let (key, _) = self.get_key_value(k)?;
+        let s = key.as_str();
+        for (i, c) in s.char_indices() {
+            let prefix = &s[..i + c.len_utf8()];
+            let node = match self.node(P::fold(prefix.chars())) {
+                Some(node) if node.len == 1 => node,
+                _ => continue,
+            };
+            if Iter::new(node).next().is_some_and(|(k, _)| ptr::eq(k, key)) {
+                return Some(prefix);
+            }
+        }
+        Some(s)
+    }
+
+    /// Returns up to `k` key-value pairs that start with the prefix, ranked by the
+    /// weight the closure gives them, highest first. Pairs of equal weight are in
+    /// folded key order. The prefix may be a String, str or UniCase value.
+    pub fn top_completions<K, W, F>(
+        &self,
+        prefix: K,
+        k: usize,
+        mut weight: F,
+    ) -> Vec<(&Key<S, P>, &V)>
+    where
+        K: AsKeyStr,
+        W: Ord,
+        F: FnMut(&Key<S, P>, &V) -> W,
+    {
+        if k == 0 {
+            return Vec::new();
+        }
+
+        // A heap of the best pairs so far, with the worst one on top.
+        let mut heap = BinaryHeap::with_capacity(k + 1);
+        for (seq, (key, v)) in self.iter_prefix(prefix).enumerate() {
+            heap.push(Ranked {
+                weight: Reverse(weight(key, v)),
+                seq,
+                item: (key, v),
+            });
+            if heap.len() > k {
+                heap.pop();
+            }
+        }
+        heap.into_sorted_vec()
+            .into_iter()
+            .map(|ranked| ranked.item)
+            .collect()
+    }
+
+    /// Inserts a key-value pair into the trie like [`insert`](Self::insert), but first
+    /// checks the key with [`FoldPolicy::validate`] and returns an error if the policy
+    /// rejects it.
+    pub fn try_insert<K: ToKey<S, P>>(&mut self, k: K, v: V) -> Result<Option<V>, InvalidKey> {
+        let key = k.to_key();
+        P::validate(key.as_str())?;
+        Ok(self.insert(key, v))
+    }
+
+    /// An iterator visiting all values in folded key order.
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.iter().map(|(_, v)| v)
+    }
+
+    fn node<I: Iterator<Item = char>>(&self, path: I) -> Option<&Node<V, S, P>> {
+        let mut node = &self.root;
+        for c in path {
+            node = node.children.get(&c)?;
+        }
+        Some(node)
+    }
+
+    fn node_mut(&mut self, path: &[char]) -> Option<&mut Node<V, S, P>> {
+        let mut node = &mut self.root;
+        for c in path {
+            node = node.children.get_mut(c)?;
+        }
+        Some(node)
+    }
+}
+
+/// A completion ranked by its weight, then by its position in folded key order.
+struct Ranked<W, T> {
+    weight: Reverse<W>,
+    seq: usize,
+    item: T,
+}
+
+impl<W: Ord, T> PartialEq for Ranked<W, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<W: Ord, T> Eq for Ranked<W, T> {}
+
+impl<W: Ord, T> PartialOrd for Ranked<W, T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<W: Ord, T> Ord for Ranked<W, T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.weight, self.seq).cmp(&(&other.weight, other.seq))
+    }
+}
+
+/// An iterator over the key-value pairs of a [`UniCaseTrie`] in folded key order.
+pub struct Iter<'a, V, S, P> {
+    next: Option<&'a Node<V, S, P>>,
+    stack: Vec<btree_map::Values<'a, char, Node<V, S, P>>>,
+    len: usize,
+}
+
+impl<'a, V, S, P> Iter<'a, V, S, P> {
+    fn new(node: &'a Node<V, S, P>) -> Self {
+        Self {
+            next: Some(node),
+            stack: Vec::new(),
+            len: node.len,
+        }
+    }
+
+    fn empty() -> Self {
+        Self {
+            next: None,
+            stack: Vec::new(),
+            len: 0,
+        }
+    }
+}
+
+impl<'a, V, S, P> Iterator for Iter<'a, V, S, P> {
+    type Item = (&'a Key<S, P>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(node) = self.next.take() {
+                self.stack.push(node.children.values());
+                if let Some((k, v)) = &node.entry {
+                    self.len -= 1;
+                    return Some((k, v));
+                }
+            }
+
+            let children = self.stack.last_mut()?;
+            match children.next() {
+                Some(child) => self.next = Some(child),
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<V, S, P> ExactSizeIterator for Iter<'_, V, S, P> {}
+
+impl<V, S, P> FusedIterator for Iter<'_, V, S, P> {}
+
+#[cfg(test)]
+mod tests {
+    use super::UniCaseTrie;
+    use crate::key::Key;
+
+    fn commands() -> UniCaseTrie<u32> {
+        vec![
+            ("Status", 50),
+            ("stash", 20),
+            ("Start", 90),
+            ("stop", 10),
+            ("Show", 70),
+            ("help", 5),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    fn keys<'a, I: Iterator<Item = (&'a Key, &'a u32)>>(iter: I) -> Vec<&'a str> {
+        iter.map(|(k, _)| k.as_str()).collect()
+    }
+
+    #[test]
+    fn insert_get_remove() {
+        let mut trie = commands();
+        assert_eq!(trie.len(), 6);
+        assert_eq!(trie.get("STATUS"), Some(&50));
+        assert!(trie.contains_key("sTaSh"));
+        assert!(!trie.contains_key("sta"));
+        assert_eq!(trie.insert("STATUS", 51), Some(50));
+        assert_eq!(trie.get_key_value("status").unwrap().0.as_str(), "Status");
+        *trie.get_mut("status").unwrap() += 1;
+        assert_eq!(trie.get("status"), Some(&52));
+
+        assert_eq!(trie.remove("STASH"), Some(20));
+        assert_eq!(trie.remove("stash"), None);
+        assert_eq!(trie.remove("sta"), None);
+        assert_eq!(trie.len(), 5);
+        assert_eq!(trie.remove_entry("status").unwrap().0.as_str(), "Status");
+        assert_eq!(keys(trie.iter_prefix("sta")), vec!["Start"]);
+
+        trie.clear();
+        assert!(trie.is_empty());
+        assert!(trie.iter_prefix("").next().is_none());
+    }
+
+    #[test]
+    fn iter_in_folded_order() {
+        let trie = commands();
+        assert_eq!(
+            keys(trie.iter()),
+            vec!["help", "Show", "Start", "stash", "Status", "stop"]
+        );
+        assert_eq!(trie.iter().len(), 6);
+        assert_eq!(trie.values().count(), 6);
+        assert_eq!(trie.keys().next().unwrap().as_str(), "help");
+    }
+
+    #[test]
+    fn iter_prefix() {
+        let trie = commands();
+        assert_eq!(
+            keys(trie.iter_prefix("ST")),
+            vec!["Start", "stash", "Status", "stop"]
+        );
+        assert_eq!(keys(trie.iter_prefix("stat")), vec!["Status"]);
+        assert_eq!(keys(trie.iter_prefix("status")), vec!["Status"]);
+        assert_eq!(trie.iter_prefix("statuses").len(), 0);
+        assert_eq!(trie.iter_prefix("").len(), 6);
+        assert_eq!(trie.iter_prefix("s").len(), 5);
+    }
+
+    #[test]
+    fn longest_prefix_match() {
+        let trie: UniCaseTrie<u8> = vec![("/", 0), ("/api", 1), ("/API/users", 2)]
+            .into_iter()
+            .collect();
+        let matched = |input| trie.longest_prefix_match(input).map(|(_, v)| *v);
+        assert_eq!(matched("/api/users/1"), Some(2));
+        assert_eq!(matched("/Api/Users"), Some(2));
+        assert_eq!(matched("/api/user"), Some(1));
+        assert_eq!(matched("/apix"), Some(1));
+        assert_eq!(matched("/static"), Some(0));
+        assert_eq!(matched("static"), None);
+
+        let empty: UniCaseTrie<u8> = vec![("", 9)].into_iter().collect();
+        assert_eq!(empty.longest_prefix_match("anything"), empty.iter().next());
+    }
+
+    #[test]
+    fn shortest_unique_prefix() {
+        let mut trie = commands();
+        assert_eq!(trie.shortest_unique_prefix("status"), Some("Stat"));
+        assert_eq!(trie.shortest_unique_prefix("STASH"), Some("stas"));
+        assert_eq!(trie.shortest_unique_prefix("stop"), Some("sto"));
+        assert_eq!(trie.shortest_unique_prefix("help"), Some("h"));
+        assert_eq!(trie.shortest_unique_prefix("st"), None);
+
+        trie.insert("Stat", 0);
+        assert_eq!(trie.shortest_unique_prefix("stat"), Some("Stat"));
+        assert_eq!(trie.shortest_unique_prefix("status"), Some("Statu"));
+    }
+
+    #[test]
+    fn top_completions() {
+        let trie = commands();
+        let top = trie.top_completions("st", 2, |_, v| *v);
+        assert_eq!(keys(top.into_iter()), vec!["Start", "Status"]);
+
+        let all = trie.top_completions("S", 10, |_, v| *v);
+        assert_eq!(
+            keys(all.into_iter()),
+            vec!["Start", "Show", "Status", "stash", "stop"]
+        );
+
+        // Equal weights keep the folded key order.
+        let tied = trie.top_completions("st", 3, |_, _| 0);
+        assert_eq!(keys(tied.into_iter()), vec!["Start", "stash", "Status"]);
+
+        assert!(trie.top_completions("st", 0, |_, v| *v).is_empty());
+        assert!(trie.top_completions("x", 3, |_, v| *v).is_empty());
+    }
+
+    #[test]
+    fn full_folding_expansions() {
+        let trie: UniCaseTrie<u8> = vec![("Straße", 1), ("Strasbourg", 2)].into_iter().collect();
+        assert_eq!(trie.get("STRASSE"), Some(&1));
+        assert_eq!(trie.iter_prefix("strass").len(), 1);
+        assert_eq!(trie.iter_prefix("stras").len(), 2);
+        assert_eq!(trie.shortest_unique_prefix("strasse"), Some("Straß"));
+    }
+
+    #[test]
+    fn partial_eq() {
+        let a: UniCaseTrie<u8> = vec![("A", 1), ("b", 2)].into_iter().collect();
+        let b: UniCaseTrie<u8> = vec![("B", 2), ("a", 1)].into_iter().collect();
+        let c: UniCaseTrie<u8> = vec![("A", 1), ("b", 3)].into_iter().collect();
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(format!("{:?}", a), r#"{"A": 1, "b": 2}"#);
+    }
+
+    #[test]
+    fn try_insert() {
+        use crate::fold::{Identifier, InvalidKey};
+
+        let mut trie = UniCaseTrie::<u8, String, Identifier>::default();
+        assert_eq!(trie.try_insert("name", 1), Ok(None));
+        assert_eq!(trie.try_insert("NAME", 2), Ok(Some(1)));
+        assert_eq!(trie.try_insert("1name", 3), Err(InvalidKey::at(0, '1')));
+        assert_eq!(trie.len(), 1);
+    }
+}
@@ -0,0 +1,688 @@
+use crate::fold::{FoldPolicy, FullUnicode, InvalidKey};
+use crate::key::{AsKeyStr, Key, KeyStr, ToKey};
+use std::iter::{FromIterator, FusedIterator};
+use std::mem;
+use std::ops::Index;
+use std::{slice, vec};
+
+/// A map backed by a `Vec` sorted by the folded keys, for small maps where the node
+/// allocations of a [`UniCaseBTreeMap`](crate::unicase_btree_map::UniCaseBTreeMap) dominate.
+///
+/// Lookups are binary searches, while inserts and removes shift the entries after the
+/// key, so the map is best for up to a few dozen entries or for maps that are built once.
+#[derive(Debug, Clone)]
+pub struct UniCaseVecMap<V, S = String, P = FullUnicode> {
+    inner: Vec<(Key<S, P>, V)>,
+}
+
+impl<V, S, P> Default for UniCaseVecMap<V, S, P> {
+    fn default() -> Self {
+        Self {
+            inner: Default::default(),
+        }
+    }
+}
+
+impl<V, S, P> Eq for UniCaseVecMap<V, S, P>
+where
+    V: Eq,
+    S: AsRef<str>,
+    P: FoldPolicy,
+{
+}
+
+impl<V, S, P> PartialEq for UniCaseVecMap<V, S, P>
+where
+    V: PartialEq,
+    S: AsRef<str>,
+    P: FoldPolicy,
+{
+    fn eq(&self, other: &UniCaseVecMap<V, S, P>) -> bool {
+        if self.len() != other.len() {
+            return false;
+        }
+
+        // Both maps are sorted by the folded keys.
+        self.iter()
+            .zip(other.iter())
+            .all(|((k1, v1), (k2, v2))| k1 == k2 && v1 == v2)
+    }
+}
+
+impl<K, V, S, P> Extend<(K, V)> for UniCaseVecMap<V, S, P>
+where
+    K: ToKey<S, P>,
+    S: AsRef<str>,
+    P: FoldPolicy,
+{
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        // Transform the keys into `Key`s, then sort once instead of for every key.
+        let iter = iter.into_iter().map(|(k, v)| (k.to_key(), v));
+        self.inner.extend(iter);
+        self.sort_and_dedup();
+    }
+}
+
+impl<'a, K, V, S, P> Extend<(K, &'a V)> for UniCaseVecMap<V, S, P>
+where
+    K: ToKey<S, P>,
+    S: AsRef<str>,
+    P: FoldPolicy,
+    V: Copy,
+{
+    fn extend<T: IntoIterator<Item = (K, &'a V)>>(&mut self, iter: T) {
+        // Transform the keys into `Key`s and copy the values.
+        let iter = iter.into_iter().map(|(k, v)| (k.to_key(), *v));
+        self.inner.extend(iter);
+        self.sort_and_dedup();
+    }
+}
+
+impl<K, V, S, P> FromIterator<(K, V)> for UniCaseVecMap<V, S, P>
+where
+    K: ToKey<S, P>,
+    S: AsRef<str>,
+    P: FoldPolicy,
+{
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut map = Self::default();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<'a, V, S, P> IntoIterator for &'a UniCaseVecMap<V, S, P>
+where
+    S: AsRef<str>,
+    P: FoldPolicy,
+{
+    type Item = (&'a Key<S, P>, &'a V);
+    type IntoIter = Iter<'a, V, S, P>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, V, S, P> IntoIterator for &'a mut UniCaseVecMap<V, S, P>
+where
+    S: AsRef<str>,
+    P: FoldPolicy,
+{
+    type Item = (&'a Key<S, P>, &'a mut V);
+    type IntoIter = IterMut<'a, V, S, P>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<V, S, P> IntoIterator for UniCaseVecMap<V, S, P> {
+    type Item = (Key<S, P>, V);
+    type IntoIter = vec::IntoIter<(Key<S, P>, V)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.inner.into_iter()
+    }
+}
+
+impl<K, V, S, P> Index<K> for UniCaseVecMap<V, S, P>
+where
+    K: AsKeyStr,
+    S: AsRef<str>,
+    P: FoldPolicy,
+{
+    type Output = V;
+
+    fn index(&self, index: K) -> &Self::Output {
+        self.get(index).expect("key not found")
+    }
+}
+
+impl<V> UniCaseVecMap<V> {
+    /// Creates a new UniCaseVecMap.
+    pub fn new() -> Self {
+        Self {
+            inner: Default::default(),
+        }
+    }
+}
+
+impl<V, S, P> UniCaseVecMap<V, S, P>
+where
+    S: AsRef<str>,
+    P: FoldPolicy,
+{
+    /// Creates a map from a vector of entries that is sorted by the folded keys, without
+    /// sorting it again. If it is not sorted after all, it is sorted, and of equal keys the
+    /// first spelling and the last value are kept, like inserting the entries one by one.
+    /// The allocation of the vector can be reused when the key types have the same layout.
+    pub fn from_sorted_vec<K: ToKey<S, P>>(entries: Vec<(K, V)>) -> Self {
+        let inner: Vec<_> = entries.into_iter().map(|(k, v)| (k.to_key(), v)).collect();
+        let mut map = Self { inner };
+        if !map.inner.windows(2).all(|w| w[0].0 < w[1].0) {
+            map.sort_and_dedup();
+        }
+        map
+    }
+
+    /// Clears the map, removing all key-value pairs. Keeps the allocated memory for reuse.
+    pub fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    /// Returns true if the map contains a value for the specified key.
+    /// The key may be a String, str or UniCase value.
+    pub fn contains_key<K: AsKeyStr>(&self, k: K) -> bool {
+        self.search(k.as_key_str::<P>()).is_ok()
+    }
+
+    /// Gets the given key's corresponding entry in the map for in-place manipulation.
+    pub fn entry<K: ToKey<S, P>>(&mut self, k: K) -> Entry<'_, V, S, P> {
+        let key = k.to_key();
+        match self.search(key.as_key_str::<P>()) {
+            Ok(index) => Entry::Occupied(OccupiedEntry {
+                entries: &mut self.inner,
+                index,
+            }),
+            Err(index) => Entry::Vacant(VacantEntry {
+                entries: &mut self.inner,
+                index,
+                key,
+            }),
+        }
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    pub fn get<K: AsKeyStr>(&self, k: K) -> Option<&V> {
+        self.get_key_value(k).map(|(_, v)| v)
+    }
+
+    /// Returns the key-value pair corresponding to the supplied key.
+    /// The key may be a String, str or UniCase value.
+    pub fn get_key_value<K: AsKeyStr>(&self, k: K) -> Option<(&Key<S, P>, &V)> {
+        let index = self.search(k.as_key_str::<P>()).ok()?;
+        let (k, v) = &self.inner[index];
+        Some((k, v))
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    /// The key may be a String, str or UniCase value.
+    pub fn get_mut<K: AsKeyStr>(&mut self, k: K) -> Option<&mut V> {
+        let index = self.search(k.as_key_str::<P>()).ok()?;
+        Some(&mut self.inner[index].1)
+    }
+
+    /// Inserts a key-value pair into the map.
+    /// If the map did not have this key present, None is returned.
+    /// If the map did have this key present, the value is updated, and the old value is returned.
+    /// The key is not updated, though; this matters for types that can be == without being identical.
+    pub fn insert<K: ToKey<S, P>>(&mut self, k: K, v: V) -> Option<V> {
+        let key = k.to_key();
+        match self.search(key.as_key_str::<P>()) {
+            Ok(index) => Some(mem::replace(&mut self.inner[index].1, v)),
+            Err(index) => {
+                self.inner.insert(index, (key, v));
+                None
+            }
+        }
+    }
+
+    /// Returns true if the map contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// An iterator visiting all key-value pairs in folded key order.
+    pub fn iter(&self) -> Iter<'_, V, S, P> {
+        Iter {
+            inner: self.inner.iter(),
+        }
+    }
+
+    /// An iterator visiting all key-value pairs in folded key order,
+    /// with mutable references to the values.
+    pub fn iter_mut(&mut self) -> IterMut<'_, V, S, P> {
+        IterMut {
+            inner: self.inner.iter_mut(),
+        }
+    }
+
+    /// An iterator visiting all keys in folded key order.
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item = &Key<S, P>> + ExactSizeIterator {
+        self.inner.iter().map(|(k, _)| k)
+    }
+
+    /// Returns the number of elements in the map.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Removes a key from the map, returning the value at the key if the key was previously in the map.
+    /// The key may be a String, str or UniCase value.
+    pub fn remove<K: AsKeyStr>(&mut self, k: K) -> Option<V> {
+        self.remove_entry(k).map(|(_, v)| v)
+    }
+
+    /// Removes a key from the map, returning the stored key and value if the key was previously in the map.
+    /// The key may be a String, str or UniCase value.
+    pub fn remove_entry<K: AsKeyStr>(&mut self, k: K) -> Option<(Key<S, P>, V)> {
+        let index = self.search(k.as_key_str::<P>()).ok()?;
+        Some(self.inner.remove(index))
+    }
+
+    /// Retains only the elements specified by the predicate.
+    /// In other words, remove all pairs (k, v) such that f(&k,&mut v) returns false.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&Key<S, P>, &mut V) -> bool,
+    {
+        self.inner.retain_mut(|(k, v)| f(k, v));
+    }
+
+    /// Inserts a key-value pair into the map like [`insert`](Self::insert), but first
+    /// checks the key with [`FoldPolicy::validate`] and returns an error if the policy
+    /// rejects it.
+    pub fn try_insert<K: ToKey<S, P>>(&mut self, k: K, v: V) -> Result<Option<V>, InvalidKey> {
+        let key = k.to_key();
+        P::validate(key.as_str())?;
+        Ok(self.insert(key, v))
+    }
+
+    /// An iterator visiting all values in folded key order.
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = &V> + ExactSizeIterator {
+        self.inner.iter().map(|(_, v)| v)
+    }
+
+    /// An iterator visiting all values mutably in folded key order.
+    pub fn values_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut V> + ExactSizeIterator {
+        self.inner.iter_mut().map(|(_, v)| v)
+    }
+
+    fn search(&self, k: &KeyStr<P>) -> Result<usize, usize> {
+        self.inner
+            .binary_search_by(|(key, _)| key.as_key_str::<P>().cmp(k))
+    }
+
+    fn sort_and_dedup(&mut self) {
+        // The sort is stable, so of equal keys the earlier one comes first.
+        self.inner.sort_by(|(a, _), (b, _)| a.cmp(b));
+        self.inner.dedup_by(|later, earlier| {
+            if later.0 != earlier.0 {
+                return false;
+            }
+            mem::swap(&mut later.1, &mut earlier.1);
+            true
+        });
+    }
+}
+
+/// A view into a single entry of a [`UniCaseVecMap`], which may be vacant or occupied.
+pub enum Entry<'a, V, S, P> {
+    Occupied(OccupiedEntry<'a, V, S, P>),
+    Vacant(VacantEntry<'a, V, S, P>),
+}
+
+impl<'a, V, S, P> Entry<'a, V, S, P> {
+    /// Provides in-place mutable access to an occupied entry before any potential inserts.
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, f: F) -> Self {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+
+    /// Returns a reference to the key of the entry.
+    pub fn key(&self) -> &Key<S, P> {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    /// Ensures a value is in the entry by inserting the default if empty, and returns a
+    /// mutable reference to the value in the entry.
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    /// Ensures a value is in the entry by inserting the result of the default function if
+    /// empty, and returns a mutable reference to the value in the entry.
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+}
+
+impl<'a, V: Default, S, P> Entry<'a, V, S, P> {
+    /// Ensures a value is in the entry by inserting the default value if empty, and
+    /// returns a mutable reference to the value in the entry.
+    pub fn or_default(self) -> &'a mut V {
+        self.or_insert_with(V::default)
+    }
+}
+
+/// A view into an occupied entry of a [`UniCaseVecMap`].
+pub struct OccupiedEntry<'a, V, S, P> {
+    entries: &'a mut Vec<(Key<S, P>, V)>,
+    index: usize,
+}
+
+impl<'a, V, S, P> OccupiedEntry<'a, V, S, P> {
+    /// Gets a reference to the value in the entry.
+    pub fn get(&self) -> &V {
+        &self.entries[self.index].1
+    }
+
+    /// Gets a mutable reference to the value in the entry.
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.entries[self.index].1
+    }
+
+    /// Sets the value of the entry, and returns the entry's old value.
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(self.get_mut(), value)
+    }
+
+    /// Converts the entry into a mutable reference to its value.
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.entries[self.index].1
+    }
+
+    /// Gets a reference to the key in the entry.
+    pub fn key(&self) -> &Key<S, P> {
+        &self.entries[self.index].0
+    }
+
+    /// Takes the value of the entry out of the map, and returns it.
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+
+    /// Takes the key-value pair of the entry out of the map, and returns it.
+    pub fn remove_entry(self) -> (Key<S, P>, V) {
+        self.entries.remove(self.index)
+    }
+}
+
+/// A view into a vacant entry of a [`UniCaseVecMap`].
+pub struct VacantEntry<'a, V, S, P> {
+    entries: &'a mut Vec<(Key<S, P>, V)>,
+    index: usize,
+    key: Key<S, P>,
+}
+
+impl<'a, V, S, P> VacantEntry<'a, V, S, P> {
+    /// Sets the value of the entry with its key, and returns a mutable reference to it.
+    pub fn insert(self, value: V) -> &'a mut V {
+        self.entries.insert(self.index, (self.key, value));
+        &mut self.entries[self.index].1
+    }
+
+    /// Takes ownership of the key.
+    pub fn into_key(self) -> Key<S, P> {
+        self.key
+    }
+
+    /// Gets a reference to the key that would be used when inserting a value.
+    pub fn key(&self) -> &Key<S, P> {
+        &self.key
+    }
+}
+
+/// An iterator over the key-value pairs of a [`UniCaseVecMap`] in folded key order.
+pub struct Iter<'a, V, S, P> {
+    inner: slice::Iter<'a, (Key<S, P>, V)>,
+}
+
+impl<'a, V, S, P> Iterator for Iter<'a, V, S, P> {
+    type Item = (&'a Key<S, P>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(k, v)| (k, v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<V, S, P> DoubleEndedIterator for Iter<'_, V, S, P> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(k, v)| (k, v))
+    }
+}
+
+impl<V, S, P> ExactSizeIterator for Iter<'_, V, S, P> {}
+
+impl<V, S, P> FusedIterator for Iter<'_, V, S, P> {}
+
+/// A mutable iterator over the key-value pairs of a [`UniCaseVecMap`] in folded key order.
+pub struct IterMut<'a, V, S, P> {
+    inner: slice::IterMut<'a, (Key<S, P>, V)>,
+}
+
+impl<'a, V, S, P> Iterator for IterMut<'a, V, S, P> {
+    type Item = (&'a Key<S, P>, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(k, v)| (&*k, v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<V, S, P> DoubleEndedIterator for IterMut<'_, V, S, P> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(k, v)| (&*k, v))
+    }
+}
+
+impl<V, S, P> ExactSizeIterator for IterMut<'_, V, S, P> {}
+
+impl<V, S, P> FusedIterator for IterMut<'_, V, S, P> {}
+
+#[cfg(test)]
+mod tests {
+    use super::{Entry, UniCaseVecMap};
+    use crate::key::Key;
+    use unicase::UniCase;
+
+    #[test]
+    fn new() {
+        let map = UniCaseVecMap::<u8>::new();
+        assert_eq!(map.len(), 0);
+    }
+
+    #[test]
+    fn clear() {
+        let mut map = UniCaseVecMap::<u8>::new();
+        map.insert("A", 1);
+        assert!(!map.is_empty());
+        map.clear();
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn contains_key() {
+        let mut map = UniCaseVecMap::<u8>::new();
+        map.insert("A", 1);
+        assert!(map.contains_key("a"));
+        assert!(map.contains_key("A".to_string()));
+        assert!(map.contains_key(UniCase::new("a")));
+        assert!(!map.contains_key("B"));
+        assert!(!map.contains_key("Å"));
+    }
+
+    #[test]
+    fn insert_keeps_keys_sorted() {
+        let mut map = UniCaseVecMap::<u8>::new();
+        assert_eq!(map.insert("c", 1), None);
+        assert_eq!(map.insert("A", 2), None);
+        assert_eq!(map.insert("b", 3), None);
+        assert_eq!(map.insert("C", 4), Some(1));
+        let keys: Vec<_> = map.keys().map(|k| k.as_str()).collect();
+        assert_eq!(keys, vec!["A", "b", "c"]);
+        assert_eq!(map.get("c"), Some(&4));
+        assert_eq!(map.get_key_value("B").unwrap().0.as_str(), "b");
+    }
+
+    #[test]
+    fn get_mut_and_index() {
+        let mut map = UniCaseVecMap::<u8>::new();
+        map.insert("A", 1);
+        *map.get_mut("a").unwrap() += 1;
+        assert_eq!(map["A"], 2);
+        assert!(map.get_mut("b").is_none());
+    }
+
+    #[test]
+    fn entry() {
+        let mut map = UniCaseVecMap::<u8>::new();
+        *map.entry("b").or_insert(1) += 1;
+        *map.entry("B").or_insert(1) += 1;
+        map.entry("a").and_modify(|v| *v = 0).or_default();
+        assert_eq!(map.get("b"), Some(&3));
+        assert_eq!(map.get("a"), Some(&0));
+        assert_eq!(map.entry("A").key(), &Key::new("a".to_string()));
+
+        match map.entry("B") {
+            Entry::Occupied(entry) => assert_eq!(entry.remove_entry().1, 3),
+            Entry::Vacant(_) => unreachable!(),
+        }
+        match map.entry("C") {
+            Entry::Occupied(_) => unreachable!(),
+            Entry::Vacant(entry) => assert_eq!(entry.into_key().as_str(), "C"),
+        }
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    fn remove() {
+        let mut map = UniCaseVecMap::<u8>::new();
+        map.insert("A", 1);
+        map.insert("B", 2);
+        assert_eq!(map.remove("b"), Some(2));
+        assert_eq!(map.remove("b"), None);
+        assert_eq!(map.remove_entry("a").unwrap().0.as_str(), "A");
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn retain() {
+        let mut map: UniCaseVecMap<u8> = vec![("A", 1), ("B", 2), ("C", 1)].into_iter().collect();
+        map.retain(|_, v| v == &1);
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get("B"), None);
+    }
+
+    #[test]
+    fn iterators() {
+        let mut map: UniCaseVecMap<u8> = vec![("b", 2), ("A", 1), ("c", 3)].into_iter().collect();
+        for (_, v) in map.iter_mut() {
+            *v += 10;
+        }
+        for v in map.values_mut() {
+            *v += 10;
+        }
+        let pairs: Vec<_> = map.iter().map(|(k, v)| (k.as_str(), *v)).collect();
+        assert_eq!(pairs, vec![("A", 21), ("b", 22), ("c", 23)]);
+        assert_eq!(map.values().next_back(), Some(&23));
+        assert_eq!(map.iter().len(), 3);
+
+        // These should all compile.
+        for _ in &map {}
+        for _ in &mut map {}
+        for _ in map {}
+    }
+
+    #[test]
+    fn from_iterator_keeps_last_value() {
+        let map: UniCaseVecMap<u8> = vec![("b", 1), ("A", 2), ("B", 3), ("a", 4)]
+            .into_iter()
+            .collect();
+        let pairs: Vec<_> = map.iter().map(|(k, v)| (k.as_str(), *v)).collect();
+        assert_eq!(pairs, vec![("A", 4), ("b", 3)]);
+    }
+
+    #[test]
+    fn extend() {
+        let mut map = UniCaseVecMap::<u8>::new();
+        map.insert("A", 1);
+        map.extend(vec![("a", 2), ("C", 3), ("B", 4)]);
+        map.extend(vec![("D", &5)]);
+        assert_eq!(map.len(), 4);
+        assert_eq!(map.get("a"), Some(&2));
+        assert_eq!(map.keys().next().unwrap().as_str(), "A");
+        let keys: Vec<_> = map.keys().map(|k| k.as_str()).collect();
+        assert_eq!(keys, vec!["A", "B", "C", "D"]);
+    }
+
+    #[test]
+    fn from_sorted_vec() {
+        let map = UniCaseVecMap::<u8, &'static str>::from_sorted_vec(vec![
+            ("accept", 1),
+            ("Content-Type", 2),
+            ("HOST", 3),
+        ]);
+        assert_eq!(map.get("content-type"), Some(&2));
+        assert_eq!(map.get("host"), Some(&3));
+
+        let unsorted =
+            UniCaseVecMap::<u8>::from_sorted_vec(vec![("b", 1), ("A", 2), ("a", 3), ("c", 4)]);
+        let pairs: Vec<_> = unsorted.iter().map(|(k, v)| (k.as_str(), *v)).collect();
+        assert_eq!(pairs, vec![("A", 3), ("b", 1), ("c", 4)]);
+    }
+
+    #[test]
+    fn partial_eq() {
+        let map1: UniCaseVecMap<u8> = vec![("A", 1), ("B", 2)].into_iter().collect();
+        let map2: UniCaseVecMap<u8> = vec![("b", 2), ("a", 1)].into_iter().collect();
+        let map3: UniCaseVecMap<u8> = vec![("b", 3), ("a", 1)].into_iter().collect();
+        assert_eq!(map1, map2);
+        assert_ne!(map1, map3);
+    }
+
+    #[test]
+    fn full_folding_expansions() {
+        let mut map = UniCaseVecMap::<u8>::new();
+        map.insert("straße", 1);
+        map.insert("STRASSE", 2);
+        map.insert("strasz", 3);
+        map.insert("strasr", 4);
+        map.insert("ﬁx", 5);
+        map.insert("FIX", 6);
+        assert_eq!(map.len(), 4);
+        assert_eq!(map.get("Strasse"), Some(&2));
+        assert_eq!(map.get("fix"), Some(&6));
+
+        let keys: Vec<_> = map.keys().map(|k| k.as_str()).collect();
+        assert_eq!(keys, vec!["ﬁx", "strasr", "straße", "strasz"]);
+    }
+
+    #[test]
+    fn ascii_policy() {
+        use crate::fold::Ascii;
+
+        let mut map = UniCaseVecMap::<u8, String, Ascii>::default();
+        map.insert("K", 1);
+        map.insert("\u{212a}", 2);
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get("k"), Some(&1));
+    }
+
+    #[test]
+    fn try_insert() {
+        use crate::fold::{HttpToken, InvalidKey};
+
+        let mut map = UniCaseVecMap::<u8, String, HttpToken>::default();
+        assert_eq!(map.try_insert("Accept", 1), Ok(None));
+        assert_eq!(map.try_insert("ACCEPT", 2), Ok(Some(1)));
+        assert_eq!(map.try_insert("Bad:Name", 3), Err(InvalidKey::at(3, ':')));
+        assert_eq!(map.len(), 1);
+    }
+}
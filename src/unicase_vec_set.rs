@@ -0,0 +1,296 @@
+use crate::fold::{FoldPolicy, FullUnicode, InvalidKey};
+use crate::key::{AsKeyStr, Key, KeyStr, ToKey};
+use std::iter::FromIterator;
+use std::slice::Iter;
+use std::vec::IntoIter;
+
+/// A set backed by a `Vec` sorted by the folded keys, for small sets where the node
+/// allocations of a [`UniCaseBTreeSet`](crate::unicase_btree_set::UniCaseBTreeSet) dominate.
+///
+/// Lookups are binary searches, while inserts and removes shift the keys after the key,
+/// so the set is best for up to a few dozen keys or for sets that are built once.
+#[derive(Debug, Clone)]
+pub struct UniCaseVecSet<S = String, P = FullUnicode> {
+    inner: Vec<Key<S, P>>,
+}
+
+impl<S, P> Default for UniCaseVecSet<S, P> {
+    fn default() -> Self {
+        Self {
+            inner: Default::default(),
+        }
+    }
+}
+
+impl<S, P> PartialEq for UniCaseVecSet<S, P>
+where
+    S: AsRef<str>,
+    P: FoldPolicy,
+{
+    fn eq(&self, other: &UniCaseVecSet<S, P>) -> bool {
+        self.inner == other.inner
+    }
+}
+
+impl<K, S, P> Extend<K> for UniCaseVecSet<S, P>
+where
+    K: ToKey<S, P>,
+    S: AsRef<str>,
+    P: FoldPolicy,
+{
+    fn extend<T: IntoIterator<Item = K>>(&mut self, iter: T) {
+        // Transform the keys into `Key`s, then sort once instead of for every key.
+        let iter = iter.into_iter().map(|k| k.to_key());
+        self.inner.extend(iter);
+        self.sort_and_dedup();
+    }
+}
+
+impl<K, S, P> FromIterator<K> for UniCaseVecSet<S, P>
+where
+    K: ToKey<S, P>,
+    S: AsRef<str>,
+    P: FoldPolicy,
+{
+    fn from_iter<T: IntoIterator<Item = K>>(iter: T) -> Self {
+        let mut set = Self::default();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<'a, S, P> IntoIterator for &'a UniCaseVecSet<S, P>
+where
+    S: AsRef<str>,
+    P: FoldPolicy,
+{
+    type Item = &'a Key<S, P>;
+    type IntoIter = Iter<'a, Key<S, P>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<S, P> IntoIterator for UniCaseVecSet<S, P> {
+    type Item = Key<S, P>;
+    type IntoIter = IntoIter<Key<S, P>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.inner.into_iter()
+    }
+}
+
+impl UniCaseVecSet {
+    /// Creates a new UniCaseVecSet.
+    pub fn new() -> Self {
+        Self {
+            inner: Default::default(),
+        }
+    }
+}
+
+impl<S, P> UniCaseVecSet<S, P>
+where
+    S: AsRef<str>,
+    P: FoldPolicy,
+{
+    /// Creates a set from a vector of keys that is sorted by the folded keys, without
+    /// sorting it again. If it is not sorted after all, it is sorted, and of equal keys the
+    /// first one is kept, like inserting the keys one by one.
+    /// The allocation of the vector can be reused when the key types have the same layout.
+    pub fn from_sorted_vec<K: ToKey<S, P>>(keys: Vec<K>) -> Self {
+        let inner: Vec<_> = keys.into_iter().map(|k| k.to_key()).collect();
+        let mut set = Self { inner };
+        if !set.inner.windows(2).all(|w| w[0] < w[1]) {
+            set.sort_and_dedup();
+        }
+        set
+    }
+
+    /// Clears the set, removing all keys. Keeps the allocated memory for reuse.
+    pub fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    /// Returns true if the set contains the specified key.
+    /// The key may be a String, str or UniCase value.
+    pub fn contains<K: AsKeyStr>(&self, k: K) -> bool {
+        self.search(k.as_key_str::<P>()).is_ok()
+    }
+
+    /// Returns a reference to the stored key that is equal to the given key.
+    /// The key may be a String, str or UniCase value.
+    pub fn get<K: AsKeyStr>(&self, k: K) -> Option<&Key<S, P>> {
+        let index = self.search(k.as_key_str::<P>()).ok()?;
+        Some(&self.inner[index])
+    }
+
+    /// Adds a key to the set.
+    /// If the set did not previously contain an equal key, true is returned.
+    /// If the set already contained an equal key, false is returned, and the entry is not updated.
+    pub fn insert<K: ToKey<S, P>>(&mut self, k: K) -> bool {
+        let key = k.to_key();
+        match self.search(key.as_key_str::<P>()) {
+            Ok(_) => false,
+            Err(index) => {
+                self.inner.insert(index, key);
+                true
+            }
+        }
+    }
+
+    /// Returns true if the set contains no keys.
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// An iterator visiting all keys in folded key order.
+    pub fn iter(&self) -> Iter<'_, Key<S, P>> {
+        self.inner.iter()
+    }
+
+    /// Returns the number of keys in the set.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Removes a key from the set, returning whether the key was previously in the set.
+    /// The key may be a String, str or UniCase value.
+    pub fn remove<K: AsKeyStr>(&mut self, k: K) -> bool {
+        match self.search(k.as_key_str::<P>()) {
+            Ok(index) => {
+                self.inner.remove(index);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Retains only the keys specified by the predicate.
+    /// In other words, remove all keys k such that f(&k) returns false.
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&Key<S, P>) -> bool,
+    {
+        self.inner.retain(f);
+    }
+
+    /// Adds a key to the set like [`insert`](Self::insert), but first checks the key
+    /// with [`FoldPolicy::validate`] and returns an error if the policy rejects it.
+    pub fn try_insert<K: ToKey<S, P>>(&mut self, k: K) -> Result<bool, InvalidKey> {
+        let key = k.to_key();
+        P::validate(key.as_str())?;
+        Ok(self.insert(key))
+    }
+
+    fn search(&self, k: &KeyStr<P>) -> Result<usize, usize> {
+        self.inner
+            .binary_search_by(|key| key.as_key_str::<P>().cmp(k))
+    }
+
+    fn sort_and_dedup(&mut self) {
+        // The sort is stable, so of equal keys the earlier one is kept.
+        self.inner.sort();
+        self.inner.dedup();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UniCaseVecSet;
+    use crate::key::Key;
+    use unicase::UniCase;
+
+    #[test]
+    fn new() {
+        let set = UniCaseVecSet::new();
+        assert_eq!(set.len(), 0);
+    }
+
+    #[test]
+    fn insert_and_contains() {
+        let mut set = UniCaseVecSet::new();
+        assert!(set.insert("b"));
+        assert!(set.insert("A"));
+        assert!(!set.insert("B"));
+        assert!(set.contains("a"));
+        assert!(set.contains("B".to_string()));
+        assert!(set.contains(UniCase::new("b")));
+        assert!(!set.contains("Å"));
+        assert_eq!(set.get("b"), Some(&Key::new("b".to_string())));
+        assert_eq!(set.get("B").unwrap().as_str(), "b");
+
+        let keys: Vec<_> = set.iter().map(|k| k.as_str()).collect();
+        assert_eq!(keys, vec!["A", "b"]);
+    }
+
+    #[test]
+    fn remove_and_retain() {
+        let mut set: UniCaseVecSet = vec!["A", "B", "C"].into_iter().collect();
+        assert!(set.remove("b"));
+        assert!(!set.remove("b"));
+        set.retain(|k| k.as_str() != "C");
+        assert_eq!(set.len(), 1);
+        set.clear();
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn extend_keeps_first_key() {
+        let mut set = UniCaseVecSet::new();
+        set.insert("A");
+        set.extend(vec!["c", "a", "B", "C"]);
+        let keys: Vec<_> = set.iter().map(|k| k.as_str()).collect();
+        assert_eq!(keys, vec!["A", "B", "c"]);
+    }
+
+    #[test]
+    fn from_sorted_vec() {
+        let set = UniCaseVecSet::<&'static str>::from_sorted_vec(vec!["DELETE", "get", "Put"]);
+        assert!(set.contains("Get"));
+        assert!(set.contains("PUT"));
+
+        let unsorted = UniCaseVecSet::<String>::from_sorted_vec(vec!["b", "A", "a"]);
+        let keys: Vec<_> = unsorted.iter().map(|k| k.as_str()).collect();
+        assert_eq!(keys, vec!["A", "b"]);
+    }
+
+    #[test]
+    fn partial_eq() {
+        let set1: UniCaseVecSet = vec!["A", "B"].into_iter().collect();
+        let set2: UniCaseVecSet = vec!["b", "a"].into_iter().collect();
+        let set3: UniCaseVecSet = vec!["a"].into_iter().collect();
+        assert_eq!(set1, set2);
+        assert_ne!(set1, set3);
+    }
+
+    #[test]
+    fn into_iterator_impls() {
+        let set: UniCaseVecSet = vec!["A", "B"].into_iter().collect();
+
+        // These should all compile.
+        for _ in &set {}
+        for _ in set {}
+    }
+
+    #[test]
+    fn full_folding_expansions() {
+        let set: UniCaseVecSet = vec!["straße", "STRASSE", "strasz", "ﬁx", "FIX"]
+            .into_iter()
+            .collect();
+        let keys: Vec<_> = set.iter().map(|k| k.as_str()).collect();
+        assert_eq!(keys, vec!["ﬁx", "straße", "strasz"]);
+    }
+
+    #[test]
+    fn try_insert() {
+        use crate::fold::{Identifier, InvalidKey};
+
+        let mut set = UniCaseVecSet::<String, Identifier>::default();
+        assert_eq!(set.try_insert("Ⅸ"), Ok(true));
+        assert_eq!(set.try_insert("ix"), Ok(false));
+        assert_eq!(set.try_insert("a b"), Err(InvalidKey::at(1, ' ')));
+        assert_eq!(set.len(), 1);
+    }
+}
@@ -0,0 +1,36 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+use unicase_collections::fold::Ascii;
+use unicase_collections::unicase_phf_map::PhfMapCodegen;
+use unicase_collections::unicase_phf_set::PhfSetCodegen;
+
+fn main() {
+    let out_dir = env::var("OUT_DIR").unwrap();
+    let out_dir = Path::new(&out_dir);
+
+    let mut keywords = PhfMapCodegen::new();
+    keywords
+        .entry("SELECT", "Keyword::Select")
+        .entry("From", "Keyword::From")
+        .entry("where", "Keyword::Where")
+        .entry("Straße", "Keyword::Street")
+        .entry("\u{212a}elvin", "Keyword::Kelvin");
+    fs::write(out_dir.join("keywords.rs"), keywords.build()).unwrap();
+
+    let mut numbers = PhfMapCodegen::new();
+    for i in 0..1000 {
+        numbers.entry(&format!("Number{}", i), &i.to_string());
+    }
+    fs::write(out_dir.join("numbers.rs"), numbers.build()).unwrap();
+
+    let mut methods = PhfSetCodegen::<Ascii>::default();
+    for method in ["GET", "PUT", "POST", "DELETE", "K"] {
+        methods.entry(method);
+    }
+    fs::write(out_dir.join("methods.rs"), methods.build()).unwrap();
+
+    let mut renamed = PhfSetCodegen::new();
+    renamed.crate_path("::renamed_collections").entry("Renamed");
+    fs::write(out_dir.join("renamed.rs"), renamed.build()).unwrap();
+}
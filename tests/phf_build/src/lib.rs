@@ -0,0 +1,85 @@
+//! Declares the tables that `build.rs` generates with `PhfMapCodegen` and `PhfSetCodegen`.
+
+extern crate unicase_collections as renamed_collections;
+
+use unicase_collections::fold::Ascii;
+use unicase_collections::unicase_phf_map::UniCasePhfMap;
+use unicase_collections::unicase_phf_set::UniCasePhfSet;
+use unicase_collections::{unicase_phf_map, unicase_phf_set};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Keyword {
+    Select,
+    From,
+    Where,
+    Street,
+    Kelvin,
+}
+
+unicase_phf_map! {
+    /// A few SQL keywords and keys that only match with full case folding.
+    pub static KEYWORDS: UniCasePhfMap<Keyword> = "keywords.rs";
+}
+
+unicase_phf_map! {
+    static NUMBERS: UniCasePhfMap<u32> = "numbers.rs";
+}
+
+unicase_phf_set! {
+    pub static METHODS: UniCasePhfSet<Ascii> = "methods.rs";
+}
+
+unicase_phf_set! {
+    static RENAMED: UniCasePhfSet = "renamed.rs";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Keyword, KEYWORDS, METHODS, NUMBERS, RENAMED};
+    use unicase_collections::unicase_phf_map::PhfMapCodegen;
+
+    #[test]
+    fn lookups() {
+        assert_eq!(KEYWORDS.len(), 5);
+        assert_eq!(KEYWORDS.get("select"), Some(&Keyword::Select));
+        assert_eq!(KEYWORDS.get("FROM"), Some(&Keyword::From));
+        assert_eq!(KEYWORDS["Where"], Keyword::Where);
+        assert_eq!(KEYWORDS.get_key_value("from").unwrap().0.as_str(), "From");
+        assert!(METHODS.contains("get"));
+        assert!(METHODS.contains("Delete"));
+        assert!(RENAMED.contains("RENAMED"));
+        for i in 0..1000 {
+            assert_eq!(NUMBERS.get(format!("NUMBER{}", i)), Some(&i));
+        }
+    }
+
+    #[test]
+    fn misses() {
+        assert_eq!(KEYWORDS.get("selects"), None);
+        assert_eq!(KEYWORDS.get(""), None);
+        assert!(!METHODS.contains("PATCH"));
+        assert!(!METHODS.contains("GE"));
+        assert_eq!(NUMBERS.get("Number1000"), None);
+        assert_eq!(NUMBERS.get("Number-1"), None);
+    }
+
+    #[test]
+    fn folding_collisions() {
+        // Keys that fold to the same chars as a key of the table find its entry.
+        assert_eq!(KEYWORDS.get("STRASSE"), Some(&Keyword::Street));
+        assert_eq!(KEYWORDS.get("straẞe"), Some(&Keyword::Street));
+        assert_eq!(KEYWORDS.get("KELVIN"), Some(&Keyword::Kelvin));
+        // The Kelvin sign only folds to `k` with Unicode folding.
+        assert!(METHODS.contains("k"));
+        assert!(!METHODS.contains("\u{212a}"));
+    }
+
+    #[test]
+    #[should_panic(expected = "duplicate key")]
+    fn duplicate_keys() {
+        PhfMapCodegen::new()
+            .entry("Straße", "1")
+            .entry("STRASSE", "2")
+            .build();
+    }
+}